@@ -0,0 +1,98 @@
+//! A CLI tool for deleting old BigML resources in bulk.
+
+use bigml::{
+    cleanup::{cleanup_by_name, CleanupOptions},
+    resource::{Id, Project},
+    Client,
+};
+use chrono::{Duration, Utc};
+use common_failures::{quick_main, Result};
+use failure::format_err;
+use log::debug;
+use structopt::StructOpt;
+use tokio::runtime::Runtime;
+
+/// Our command-line arguments.
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "bigml-cleanup",
+    about = "Delete old BigML resources matching tags or a project"
+)]
+struct Opt {
+    /// The type of resource to delete, such as "dataset". May be specified
+    /// more than once.
+    #[structopt(long = "type", short = "t", required = true)]
+    resource_types: Vec<String>,
+
+    /// Only delete resources created more than this many days ago.
+    #[structopt(long = "older-than-days", short = "d")]
+    older_than_days: u32,
+
+    /// Only delete resources with this tag. May be specified more than once,
+    /// in which case resources with any of the tags are deleted.
+    #[structopt(long = "tag")]
+    tags: Vec<String>,
+
+    /// Only delete resources in this project.
+    #[structopt(long = "project")]
+    project: Option<Id<Project>>,
+
+    /// How many resources should we delete at a time?
+    #[structopt(long = "max-tasks", short = "J", default_value = "4")]
+    max_tasks: usize,
+
+    /// Print the IDs of matching resources without deleting them.
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+quick_main!(run);
+
+/// Our real `main` function, called by the code generated by `quick_main!`.
+fn run() -> Result<()> {
+    env_logger::init();
+    let opt = Opt::from_args();
+    debug!("command-line options: {:?}", opt);
+
+    if opt.tags.is_empty() && opt.project.is_none() {
+        return Err(format_err!(
+            "refusing to delete resources without --tag or --project"
+        ));
+    }
+
+    let runtime = Runtime::new().expect("Unable to create a runtime");
+    runtime.block_on(run_async(opt))
+}
+
+/// Our main entry point, once we have an async runtime.
+async fn run_async(opt: Opt) -> Result<()> {
+    let client = Client::new_from_env()?;
+    let created_before = Utc::now() - Duration::days(i64::from(opt.older_than_days));
+    let mut options = CleanupOptions::new(created_before)
+        .concurrency(opt.max_tasks)
+        .dry_run(opt.dry_run);
+    for tag in &opt.tags {
+        options = options.tag(tag.to_owned());
+    }
+    if let Some(project) = &opt.project {
+        options = options.project(project.to_owned());
+    }
+
+    for resource_type in &opt.resource_types {
+        let ids = cleanup_by_name(&client, resource_type, &options).await?;
+        for id in &ids {
+            println!("{}", id);
+        }
+        eprintln!(
+            "{} {} {} resources",
+            if opt.dry_run {
+                "Would delete"
+            } else {
+                "Deleted"
+            },
+            ids.len(),
+            resource_type,
+        );
+    }
+    Ok(())
+}
@@ -16,6 +16,20 @@ pub struct ExecutionInput {
     pub value: Value,
 }
 
+impl ExecutionInput {
+    /// Create an input from a CSV field. Like command-line inputs, the field
+    /// will be parsed as JSON if possible, but we don't warn when it isn't,
+    /// because plain strings are common in CSV files.
+    pub fn from_csv_field(name: &str, field: &str) -> ExecutionInput {
+        let value = serde_json::from_str(field)
+            .unwrap_or_else(|_| Value::String(field.to_owned()));
+        ExecutionInput {
+            name: name.to_owned(),
+            value,
+        }
+    }
+}
+
 /// Declare a `FromStr` implementation for `Input` so that `structopt` can parse
 /// command-line arguments directly into `Input` values.
 impl FromStr for ExecutionInput {
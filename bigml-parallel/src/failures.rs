@@ -0,0 +1,95 @@
+//! Reporting failed executions.
+
+use common_failures::Result;
+use failure::{Error, ResultExt};
+use log::error;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::input_row::RowId;
+
+/// A single line in our errors file.
+#[derive(Debug, Serialize)]
+struct ErrorRecord<'a> {
+    /// The input row, stored as a "resource" field and, for input formats
+    /// where rows may share a resource, a "row" number.
+    #[serde(flatten)]
+    id: &'a RowId,
+    /// A description of the error, including any underlying causes.
+    error: String,
+}
+
+/// Reports failed executions to standard error and, optionally, an errors
+/// file containing line-delimited JSON.
+pub struct FailureReporter {
+    /// The path to our errors file, and the file itself.
+    errors_file: Option<(PathBuf, Mutex<File>)>,
+    /// How many failures have we reported using `report`?
+    count: AtomicUsize,
+}
+
+impl FailureReporter {
+    /// Create a new `FailureReporter`, creating `errors_file` if specified.
+    pub fn new(errors_file: Option<&Path>) -> Result<FailureReporter> {
+        let errors_file = match errors_file {
+            Some(path) => {
+                let file = File::create(path).with_context(|_| {
+                    format!("could not create {}", path.display())
+                })?;
+                Some((path.to_owned(), Mutex::new(file)))
+            }
+            None => None,
+        };
+        Ok(FailureReporter {
+            errors_file,
+            count: AtomicUsize::new(0),
+        })
+    }
+
+    /// Report that processing row `id` failed with `err`, and that we're
+    /// going to keep going anyway.
+    pub fn report(&self, id: &RowId, err: &Error) -> Result<()> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        error!("failed to process {}: {}", id, describe(err));
+        self.record(id, err)
+    }
+
+    /// Record that processing row `id` failed with `err` in our errors file,
+    /// if we have one, without logging anything.
+    pub fn record(&self, id: &RowId, err: &Error) -> Result<()> {
+        if let Some((path, file)) = &self.errors_file {
+            let record = ErrorRecord {
+                id,
+                error: describe(err),
+            };
+            let mut line = serde_json::to_string(&record)?;
+            line.push('\n');
+            let mut file = file.lock().expect("lock poisoned");
+            file.write_all(line.as_bytes())
+                .and_then(|()| file.flush())
+                .with_context(|_| format!("could not write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// How many failures have we reported using `report`?
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+/// Describe `err`, including any underlying causes.
+fn describe(err: &Error) -> String {
+    err.iter_chain()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<_>>()
+        .join(": ")
+}
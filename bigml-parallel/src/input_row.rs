@@ -0,0 +1,272 @@
+//! Reading the rows of input we process, each of which becomes a single
+//! execution.
+
+use common_failures::Result;
+use failure::{format_err, Error};
+use futures::{channel::mpsc, executor::block_on, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{fmt, io, str::FromStr};
+use tokio::task;
+
+use crate::execution_input::ExecutionInput;
+use crate::BoxStream;
+
+/// How our input is formatted on standard input.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InputFormat {
+    /// One resource ID per line.
+    Lines,
+    /// A CSV file with headers. Each header names a WhizzML input.
+    Csv,
+    /// One JSON object per line, specifying a resource, inputs and a name.
+    Json,
+}
+
+impl FromStr for InputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "lines" => Ok(InputFormat::Lines),
+            "csv" => Ok(InputFormat::Csv),
+            "json" => Ok(InputFormat::Json),
+            _ => Err(format_err!("unknown input format {:?}", s)),
+        }
+    }
+}
+
+/// Identifies a row of input in our output, errors and state files.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RowId {
+    /// The resource this row processes, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+
+    /// The position of this row in our input, starting at 1. This is only
+    /// present for input formats where several rows may share a resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<usize>,
+}
+
+impl fmt::Display for RowId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.resource, self.row) {
+            (Some(resource), Some(row)) => write!(f, "{} (row {})", resource, row),
+            (Some(resource), None) => write!(f, "{}", resource),
+            (None, Some(row)) => write!(f, "row {}", row),
+            (None, None) => write!(f, "(unknown row)"),
+        }
+    }
+}
+
+/// A single row of input, which we'll process using one execution.
+#[derive(Debug)]
+pub struct InputRow {
+    /// Identifies this row in our output, errors and state files.
+    pub id: RowId,
+
+    /// The inputs to pass to our WhizzML script. These override any inputs
+    /// with the same names specified on the command line.
+    pub inputs: Vec<ExecutionInput>,
+
+    /// The name to use for this row's execution, overriding `--name`.
+    pub name: Option<String>,
+}
+
+/// A line of `--input-format json` input.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonLine {
+    /// The resource to process, passed as `--resource-input-name`.
+    #[serde(default)]
+    resource: Option<String>,
+
+    /// Inputs to pass to our WhizzML script.
+    #[serde(default)]
+    inputs: Map<String, Value>,
+
+    /// The name of this execution.
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl InputRow {
+    /// Create an `InputRow` which passes `resource` as `input_name`.
+    pub fn from_resource(input_name: &str, resource: String) -> InputRow {
+        InputRow {
+            inputs: vec![ExecutionInput {
+                name: input_name.to_owned(),
+                value: Value::String(resource.clone()),
+            }],
+            id: RowId {
+                resource: Some(resource),
+                row: None,
+            },
+            name: None,
+        }
+    }
+
+    /// Create an `InputRow` from a line of JSON containing an object with
+    /// optional "resource", "inputs" and "name" fields. Our ID includes
+    /// `line_number`, so that it's unique even when several lines process the
+    /// same resource.
+    pub fn from_json_line(
+        input_name: &str,
+        line: &str,
+        line_number: usize,
+    ) -> Result<InputRow> {
+        let json: JsonLine = serde_json::from_str(line).map_err(|err| {
+            format_err!("could not parse input line {}: {}", line_number, err)
+        })?;
+        let mut inputs = vec![];
+        if let Some(resource) = &json.resource {
+            inputs.push(ExecutionInput {
+                name: input_name.to_owned(),
+                value: Value::String(resource.clone()),
+            });
+        }
+        for (name, value) in json.inputs {
+            inputs.push(ExecutionInput { name, value });
+        }
+        Ok(InputRow {
+            id: RowId {
+                resource: json.resource,
+                row: Some(line_number),
+            },
+            inputs,
+            name: json.name,
+        })
+    }
+
+    /// Create an `InputRow` from a CSV record, using `headers` as our input
+    /// names. Our ID includes `row_number`, so that it's unique even when
+    /// several rows process the same resource, and the value of the column
+    /// named `id_column`, if it's present and non-empty.
+    fn from_csv_record(
+        headers: &csv::StringRecord,
+        record: &csv::StringRecord,
+        row_number: usize,
+        id_column: &str,
+    ) -> InputRow {
+        let mut resource = None;
+        let mut inputs = vec![];
+        for (name, field) in headers.iter().zip(record.iter()) {
+            // Leave empty fields out, so the script can use its defaults.
+            if field.is_empty() {
+                continue;
+            }
+            if name == id_column {
+                resource = Some(field.to_owned());
+            }
+            inputs.push(ExecutionInput::from_csv_field(name, field));
+        }
+        InputRow {
+            id: RowId {
+                resource,
+                row: Some(row_number),
+            },
+            inputs,
+            name: None,
+        }
+    }
+}
+
+/// Read CSV data from standard input, returning a stream of `InputRow`s.
+pub fn read_csv_rows(id_column: String) -> BoxStream<InputRow> {
+    // The `csv` crate is synchronous, so read it on a blocking thread and send
+    // the rows back over a channel. If our receiver is dropped, `send` will
+    // fail and we'll stop reading.
+    let (mut tx, rx) = mpsc::channel(16);
+    task::spawn_blocking(move || {
+        let stdin = io::stdin();
+        let mut rdr = csv::Reader::from_reader(stdin.lock());
+        let headers = match rdr.headers() {
+            Ok(headers) => headers.to_owned(),
+            Err(err) => {
+                let _ = block_on(tx.send(Err(err.into())));
+                return;
+            }
+        };
+        for (i, record) in rdr.records().enumerate() {
+            let row = record.map_err(Error::from).map(|record| {
+                InputRow::from_csv_record(&headers, &record, i + 1, &id_column)
+            });
+            if block_on(tx.send(row)).is_err() {
+                return;
+            }
+        }
+    });
+    rx.boxed()
+}
+
+#[test]
+fn csv_records_become_inputs() {
+    let headers = csv::StringRecord::from(vec!["resource", "depth", "label"]);
+
+    let record = csv::StringRecord::from(vec!["dataset/1", "3", "deep"]);
+    let row = InputRow::from_csv_record(&headers, &record, 1, "resource");
+    assert_eq!(row.id.resource.as_deref(), Some("dataset/1"));
+    assert_eq!(row.id.row, Some(1));
+    assert_eq!(row.id.to_string(), "dataset/1 (row 1)");
+    let inputs = row
+        .inputs
+        .iter()
+        .map(|input| (input.name.as_str(), input.value.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        inputs,
+        vec![
+            ("resource", Value::from("dataset/1")),
+            ("depth", Value::from(3)),
+            ("label", Value::from("deep")),
+        ],
+    );
+
+    let record = csv::StringRecord::from(vec!["", "", "shallow"]);
+    let row = InputRow::from_csv_record(&headers, &record, 2, "resource");
+    assert_eq!(row.id.resource, None);
+    assert_eq!(row.id.to_string(), "row 2");
+    assert_eq!(row.inputs.len(), 1);
+}
+
+#[test]
+fn csv_rows_sharing_a_resource_have_unique_ids() {
+    let headers = csv::StringRecord::from(vec!["resource", "depth"]);
+    let shallow = csv::StringRecord::from(vec!["dataset/1", "4"]);
+    let deep = csv::StringRecord::from(vec!["dataset/1", "8"]);
+    let row1 = InputRow::from_csv_record(&headers, &shallow, 1, "resource");
+    let row2 = InputRow::from_csv_record(&headers, &deep, 2, "resource");
+    assert_ne!(row1.id, row2.id);
+    assert_eq!(row2.id.resource.as_deref(), Some("dataset/1"));
+    assert_eq!(row2.id.row, Some(2));
+}
+
+#[test]
+fn json_lines_become_inputs() {
+    let line = r#"{"resource": "dataset/1", "inputs": {"depth": 3}, "name": "deep"}"#;
+    let row = InputRow::from_json_line("resource", line, 1).unwrap();
+    assert_eq!(row.id.resource.as_deref(), Some("dataset/1"));
+    assert_eq!(row.id.row, Some(1));
+    assert_eq!(row.name.as_deref(), Some("deep"));
+    let inputs = row
+        .inputs
+        .iter()
+        .map(|input| (input.name.as_str(), input.value.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        inputs,
+        vec![
+            ("resource", Value::from("dataset/1")),
+            ("depth", Value::from(3))
+        ],
+    );
+
+    let row = InputRow::from_json_line("resource", r#"{"inputs": {}}"#, 2).unwrap();
+    assert_eq!(row.id.resource, None);
+    assert_eq!(row.id.row, Some(2));
+    assert!(row.inputs.is_empty());
+
+    assert!(InputRow::from_json_line("resource", r#"{"input": {}}"#, 3).is_err());
+    assert!(InputRow::from_json_line("resource", "dataset/1", 4).is_err());
+}
@@ -0,0 +1,78 @@
+//! Giving up on input rows which exceed `--per-item-timeout`.
+
+use bigml::{
+    resource::{Execution, Id},
+    Client,
+};
+use failure::Fail;
+use log::{error, warn};
+use std::{fmt, time::Duration};
+
+use crate::InFlight;
+
+/// Processing an input row took longer than `--per-item-timeout`.
+#[derive(Debug)]
+pub struct ItemTimedOut {
+    /// How long we waited.
+    pub limit: Duration,
+
+    /// The execution which was running when we gave up, if any.
+    pub execution: Option<Id<Execution>>,
+
+    /// Did we cancel `execution`?
+    pub cancelled: bool,
+}
+
+impl fmt::Display for ItemTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out after {} seconds", self.limit.as_secs())?;
+        match (&self.execution, self.cancelled) {
+            (Some(id), true) => write!(f, " (cancelled {})", id),
+            (Some(id), false) => write!(f, " ({} is still running)", id),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+impl Fail for ItemTimedOut {}
+
+/// Give up on input row number `index` after `limit`, optionally cancelling
+/// its running execution. Returns the error to report for this row.
+pub async fn time_out_row(
+    client: &Client,
+    in_flight: &InFlight,
+    index: usize,
+    limit: Duration,
+    cancel: bool,
+) -> ItemTimedOut {
+    let execution = in_flight.lock().expect("lock poisoned").remove(&index);
+    let mut cancelled = false;
+    if let Some(id) = &execution {
+        if cancel {
+            warn!("cancelling {}, which timed out", id);
+            match client.cancel_execution(id).await {
+                Ok(()) => cancelled = true,
+                Err(err) => error!("could not cancel {}: {}", id, err),
+            }
+        }
+    }
+    ItemTimedOut {
+        limit,
+        execution,
+        cancelled,
+    }
+}
+
+#[test]
+fn describe_timeouts() {
+    let id: Id<Execution> = "execution/5e5d87f6e476845bd8000000".parse().unwrap();
+    let timed_out = ItemTimedOut {
+        limit: Duration::from_secs(90),
+        execution: Some(id),
+        cancelled: true,
+    };
+    assert_eq!(
+        timed_out.to_string(),
+        "timed out after 90 seconds (cancelled execution/5e5d87f6e476845bd8000000)",
+    );
+}
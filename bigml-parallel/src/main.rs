@@ -2,26 +2,48 @@
 
 use bigml::{
     self,
-    resource::{execution, Execution, Id, Resource, Script},
-    try_wait, try_with_permanent_failure,
-    wait::{wait, BackoffType, WaitOptions, WaitStatus},
-    Client,
+    batch::{BatchObserver, ExecutionBatch},
+    resource::{execution, execution::LogEntry, Execution, Id, Resource, Script},
+    wait::{BackoffType, WaitOptions},
+    Client, ClientBuilder,
 };
 use common_failures::{quick_main, Result};
-use failure::Error;
-use futures::{self, stream, FutureExt, StreamExt, TryStreamExt};
-use log::{debug, error};
+use failure::{format_err, Error, ResultExt};
+use futures::{self, future, stream, FutureExt, StreamExt, TryStreamExt};
+use log::{debug, error, warn};
 use regex::Regex;
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use structopt::StructOpt;
-use tokio::{io, runtime::Runtime};
+use tokio::{fs, io, runtime::Runtime, time};
 use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
 
 mod execution_input;
+mod failures;
+mod input_row;
+mod item_timeout;
 mod line_delimited_json_codec;
+mod max_tasks;
+mod output_codec;
+mod script_file;
+mod shutdown;
+mod state_file;
 
 use execution_input::ExecutionInput;
-use line_delimited_json_codec::LineDelimitedJsonCodec;
+use failures::FailureReporter;
+use input_row::{read_csv_rows, InputFormat, InputRow, RowId};
+use item_timeout::{time_out_row, ItemTimedOut};
+use max_tasks::MaxTasks;
+use output_codec::{Emit, OutputCodec, OutputFormat};
+use script_file::{deploy_script, script_args, Declaration};
+use shutdown::{shutdown_signal, Interrupted, INTERRUPTED_EXIT_CODE};
+use state_file::{PreviousRun, StateFile};
 
 /// Our standard stream type, containing values of type `T`.
 type BoxStream<T> = futures::stream::BoxStream<'static, Result<T>>;
@@ -29,6 +51,11 @@ type BoxStream<T> = futures::stream::BoxStream<'static, Result<T>>;
 /// Our standard future type, yield a value of type `T`.
 type BoxFuture<T> = futures::future::BoxFuture<'static, Result<T>>;
 
+/// The executions which we have created but which have not finished yet,
+/// keyed by the index of the input row they're processing. We need to do
+/// something with these if we're interrupted.
+type InFlight = Arc<Mutex<HashMap<usize, Id<Execution>>>>;
+
 /// Our command-line arguments.
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -37,8 +64,30 @@ type BoxFuture<T> = futures::future::BoxFuture<'static, Result<T>>;
 )]
 struct Opt {
     /// The WhizzML script ID to run.
-    #[structopt(long = "script", short = "s")]
-    script: Id<Script>,
+    #[structopt(
+        long = "script",
+        short = "s",
+        required_unless = "script-file",
+        conflicts_with = "script-file"
+    )]
+    script: Option<Id<Script>>,
+
+    /// A local WhizzML file to run instead of --script. We create a script
+    /// from this file before starting, reusing the script from a previous
+    /// run if the source code hasn't changed.
+    #[structopt(long = "script-file", parse(from_os_str))]
+    script_file: Option<PathBuf>,
+
+    /// With --script-file, declare a script input, specified as "name:type".
+    /// Otherwise, we guess the types of --input values, and assume that
+    /// --resource-input-name is a resource ID.
+    #[structopt(long = "script-input")]
+    script_inputs: Vec<Declaration>,
+
+    /// With --script-file, declare a script output, specified as
+    /// "name:type". Every --output must be declared.
+    #[structopt(long = "script-output")]
+    script_outputs: Vec<Declaration>,
 
     /// The name to use for our execution objects.
     #[structopt(long = "name", short = "n")]
@@ -49,7 +98,8 @@ struct Opt {
     #[structopt(long = "resource", short = "r")]
     resources: Vec<String>,
 
-    /// The input name used to pass the dataset.
+    /// The input name used to pass the dataset. With `--input-format csv`, a
+    /// column with this name will be used to identify each row.
     #[structopt(
         long = "resource-input-name",
         short = "R",
@@ -57,8 +107,20 @@ struct Opt {
     )]
     resource_input_name: String,
 
+    /// The format of standard input: "lines" contains one resource ID per
+    /// line, "csv" contains a header naming each script input, followed by
+    /// one row of inputs per execution, and "json" contains one JSON object
+    /// per line, with optional "resource", "inputs" and "name" fields.
+    #[structopt(
+        long = "input-format",
+        default_value = "lines",
+        possible_values = &["lines", "csv", "json"]
+    )]
+    input_format: InputFormat,
+
     /// Extra inputs to our WhizzML script, specified as "name=value". These
     /// will be parsed as JSON if possible, or treated as strings otherwise.
+    /// Inputs read from standard input override these.
     #[structopt(long = "input", short = "i")]
     inputs: Vec<ExecutionInput>,
 
@@ -66,9 +128,38 @@ struct Opt {
     #[structopt(long = "output", short = "o")]
     outputs: Vec<String>,
 
-    /// How many BigML tasks should we use at a time?
+    /// How to write our results to standard output: "execution" writes each
+    /// execution as JSON, "values" writes a JSON object containing the input
+    /// ID and each --emit value, "resources" writes the ID of each resource
+    /// created by our executions, and "csv" writes the input ID and each
+    /// --emit value as CSV.
+    #[structopt(
+        long = "output-format",
+        default_value = "execution",
+        possible_values = &["execution", "values", "resources", "csv"]
+    )]
+    output_format: OutputFormat,
+
+    /// A value to include in "values" and "csv" output, either "execution"
+    /// or "output:NAME". Defaults to each --output.
+    #[structopt(long = "emit")]
+    emit: Vec<Emit>,
+
+    /// How many BigML tasks should we use at a time? Use "auto" to ask
+    /// BigML how many parallel tasks our subscription allows.
     #[structopt(long = "max-tasks", short = "J", default_value = "2")]
-    max_tasks: usize,
+    max_tasks: MaxTasks,
+
+    /// With --max-tasks auto, how many of our subscription's parallel tasks
+    /// should we leave free for other work?
+    #[structopt(long = "reserve-tasks", default_value = "0")]
+    reserve_tasks: usize,
+
+    /// The maximum number of requests per minute to send to BigML, including
+    /// the requests we use to check whether executions have finished. This is
+    /// shared by all our tasks.
+    #[structopt(long = "max-requests-per-minute")]
+    max_requests_per_minute: Option<NonZeroU32>,
 
     /// Apply a tag to the BigML resources we create.
     #[structopt(long = "tag")]
@@ -81,6 +172,73 @@ struct Opt {
     /// How many times should we retry a failed execution matching --retry-on?
     #[structopt(long = "retry-count", default_value = "0")]
     retry_count: u16,
+
+    /// How many seconds should we wait before retrying a failed attempt to
+    /// create an execution?
+    #[structopt(long = "retry-interval", default_value = "60")]
+    retry_interval: u64,
+
+    /// How many times should we retry a failed attempt to create an
+    /// execution? This often fails when we hit BigML's API limits.
+    #[structopt(long = "max-retries", default_value = "6")]
+    max_retries: u16,
+
+    /// How many seconds should we spend trying to create an execution before
+    /// giving up?
+    #[structopt(long = "timeout")]
+    timeout: Option<u64>,
+
+    /// How many seconds should we spend processing a single resource,
+    /// including any retries? Resources which take longer are reported as
+    /// failed, and we keep processing the others.
+    #[structopt(long = "per-item-timeout")]
+    per_item_timeout: Option<u64>,
+
+    /// Cancel executions which exceed --per-item-timeout. By default, we
+    /// leave them running.
+    #[structopt(long = "cancel-timed-out")]
+    cancel_timed_out: bool,
+
+    /// How should we increase the time between retries?
+    #[structopt(
+        long = "backoff",
+        default_value = "exponential",
+        possible_values = &["linear", "exponential"]
+    )]
+    backoff: BackoffType,
+
+    /// When interrupted, how many seconds should we wait for running
+    /// executions to finish? No new executions will be started.
+    #[structopt(long = "shutdown-timeout", default_value = "0")]
+    shutdown_timeout: u64,
+
+    /// When interrupted, write the IDs of any executions which are still
+    /// running to this file, and leave them running. By default, we cancel
+    /// them.
+    #[structopt(long = "running-executions-file", parse(from_os_str))]
+    running_executions_file: Option<PathBuf>,
+
+    /// Record which resources we've processed in this file. If we're run
+    /// again with the same file, skip resources which have already been
+    /// processed, and wait for executions which were still running.
+    #[structopt(long = "state-file", parse(from_os_str))]
+    state_file: Option<PathBuf>,
+
+    /// If processing a resource fails, report the error and keep processing
+    /// the remaining resources. We exit with an error at the end if anything
+    /// failed.
+    #[structopt(long = "keep-going", short = "k")]
+    keep_going: bool,
+
+    /// Write failures to this file as line-delimited JSON, with "resource"
+    /// and "error" fields, and a "row" number with --input-format csv or json.
+    #[structopt(long = "errors-file", parse(from_os_str))]
+    errors_file: Option<PathBuf>,
+
+    /// Print log entries from running executions to standard error as soon
+    /// as BigML reports them.
+    #[structopt(long = "tail-logs")]
+    tail_logs: bool,
 }
 
 // Generate a `main` function that prints out pretty errors.
@@ -95,158 +253,446 @@ fn run() -> Result<()> {
     // Create a future for our async code, and pass it to an async runtime.
     let fut = run_async(opt);
     let runtime = Runtime::new().expect("Unable to create a runtime");
-    runtime.block_on(fut.boxed())?;
-    Ok(())
+    match runtime.block_on(fut.boxed()) {
+        Err(err) if err.downcast_ref::<Interrupted>().is_some() => {
+            eprintln!("{}", err);
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        result => result,
+    }
 }
 
 /// And finally, a third `main` function, but this time asynchronous. This runs
 /// the actual BigML script executions using the configuration in `opt`.
 async fn run_async(opt: Opt) -> Result<()> {
-    // We want to represent our input resource IDs as an asynchronous stream,
-    // which will make it very easy to have controlled parallel execution.
-    let resources: BoxStream<String> = if !opt.resources.is_empty() {
-        // Turn our `--resource` arguments into a stream.
-        let resources = opt.resources.clone();
-        stream::iter(resources.into_iter().map(Ok)).boxed()
+    // We want to represent our input rows as an asynchronous stream, which
+    // will make it very easy to have controlled parallel execution.
+    let input_name = opt.resource_input_name.clone();
+    let rows: BoxStream<InputRow> = match opt.input_format {
+        InputFormat::Csv | InputFormat::Json if !opt.resources.is_empty() => {
+            return Err(format_err!(
+                "--input-format csv and json read from standard input, and can't be used with --resource"
+            ));
+        }
+        InputFormat::Csv => read_csv_rows(input_name),
+        InputFormat::Lines if !opt.resources.is_empty() => {
+            // Turn our `--resource` arguments into a stream.
+            let resources = opt.resources.clone();
+            let rows = resources.into_iter().map(move |resource| {
+                Ok(InputRow::from_resource(&input_name, resource))
+            });
+            stream::iter(rows).boxed()
+        }
+        InputFormat::Lines => {
+            // Parse standard input as a stream of dataset IDs.
+            let lines = FramedRead::new(io::stdin(), LinesCodec::new());
+            lines
+                .map_err(|e| -> Error { e.into() })
+                .map_ok(move |resource| InputRow::from_resource(&input_name, resource))
+                .boxed()
+        }
+        InputFormat::Json => {
+            // Parse standard input as a stream of JSON objects, skipping
+            // blank lines.
+            let lines = FramedRead::new(io::stdin(), LinesCodec::new());
+            lines
+                .map_err(|e| -> Error { e.into() })
+                .enumerate()
+                .filter(|(_, line)| {
+                    future::ready(!matches!(line, Ok(line) if line.trim().is_empty()))
+                })
+                .map(move |(i, line)| {
+                    InputRow::from_json_line(&input_name, &line?, i + 1)
+                })
+                .boxed()
+        }
+    };
+
+    // Decide how many tasks to run at once. If we were asked to choose
+    // automatically, look up our subscription's limit.
+    let status = match opt.max_tasks {
+        MaxTasks::Auto => Some(Client::new_from_env()?.status().await?),
+        MaxTasks::Fixed(_) => None,
+    };
+    let max_tasks = opt.max_tasks.resolve(status.as_ref(), opt.reserve_tasks)?;
+    debug!("running up to {} tasks at a time", max_tasks);
+
+    // Create a single BigML client, and clone it for each task, so that all
+    // our tasks share the same connection pool and rate limit. Keep enough
+    // idle connections around for all our tasks.
+    let mut client_builder = ClientBuilder::from_env()?
+        .pool_max_idle_per_host(max_tasks)
+        .tcp_keepalive(Duration::from_secs(60));
+    if let Some(max) = opt.max_requests_per_minute {
+        client_builder = client_builder.rate_limit(f64::from(max.get()) / 60.0);
+    }
+    let client = client_builder.build()?;
+
+    // Decide which script to run, creating it from --script-file if needed.
+    let script = match (&opt.script, &opt.script_file) {
+        (Some(script), _) => script.to_owned(),
+        (None, Some(path)) => {
+            let args = script_args(path, &opt)?;
+            let tag = format!(
+                "bigml-parallel-script:{}",
+                args.name.as_deref().unwrap_or("script"),
+            );
+            let script = deploy_script(&client, args, &tag).await?;
+            debug!("running {} created from {}", script, path.display());
+            script
+        }
+        (None, None) => return Err(format_err!("please specify --script")),
+    };
+
+    // Describe how to run our script. Each row adds its own inputs to a copy
+    // of these arguments.
+    let mut args = execution::Args::default();
+    args.set_script(script);
+    args.name = opt.name.clone();
+    for output in &opt.outputs {
+        args.add_output(output);
+    }
+    args.tags = opt.tags.clone();
+    let retry_on = opt.retry_on.clone();
+    let mut batch = ExecutionBatch::from_args(args)
+        .retry_count(opt.retry_count)
+        .retry_on(move |message| {
+            matches!(&retry_on, Some(retry_on) if retry_on.is_match(message))
+        })
+        .create_wait_options(create_wait_options(&opt));
+    if opt.tail_logs {
+        batch = batch.stream_logs(Duration::from_secs(10));
+    }
+    let batch = Arc::new(batch);
+
+    // Figure out how to write our output before we start any executions.
+    let emit = if opt.emit.is_empty()
+        && matches!(opt.output_format, OutputFormat::Values | OutputFormat::Csv)
+    {
+        opt.outputs.iter().cloned().map(Emit::Output).collect()
     } else {
-        // Parse standard input as a stream of dataset IDs.
-        let lines = FramedRead::new(io::stdin(), LinesCodec::new());
-        lines.map_err(|e| -> Error { e.into() }).boxed()
+        opt.emit.clone()
+    };
+    let output_codec = OutputCodec::new(opt.output_format, emit)?;
+
+    // If we have a state file, skip any rows we've already processed.
+    let state_file = match &opt.state_file {
+        Some(path) => Some(Arc::new(StateFile::open(path)?)),
+        None => None,
     };
+    let state_file2 = state_file.clone();
+    let rows = rows
+        .try_filter(move |row| {
+            let previous_run = state_file2
+                .as_ref()
+                .and_then(|state_file| state_file.previous_run(&row.id));
+            let finished = matches!(previous_run, Some(PreviousRun::Finished));
+            if finished {
+                debug!("skipping {}, which was already processed", row.id);
+            }
+            future::ready(!finished)
+        })
+        .boxed();
+
+    // When we're asked to shut down, stop reading new rows. This will cause
+    // our stream of executions to end once the running ones finish.
+    let shutdown = shutdown_signal().boxed().shared();
+    let rows = rows.take_until(shutdown.clone()).boxed();
 
     // Wrap our command line arguments in a thread-safe reference counter, so
     // that all our parallel tasks can access them.
     let opt = Arc::new(opt);
+    let in_flight = InFlight::default();
 
-    // Transform our stream of IDs into a stream of _futures_, each of which will
-    // return an `Execution` object from BigML.
+    // Transform our stream of rows into a stream of _futures_, each of which
+    // will return an `Execution` object from BigML, along with the ID of the
+    // row we processed.
     let opt2 = opt.clone();
-    let execution_futures: BoxStream<BoxFuture<Execution>> = resources
-        .map_ok(move |resource| {
-            resource_id_to_execution(opt2.clone(), resource).boxed()
+    let client2 = client.clone();
+    let in_flight2 = in_flight.clone();
+    let execution_futures: BoxStream<BoxFuture<(RowId, Result<Execution>)>> = rows
+        .enumerate()
+        .map(|(index, row)| row.map(|row| (index, row)))
+        .map_ok(move |(index, row)| {
+            let id = row.id.clone();
+            let execution = input_row_to_execution(
+                opt2.clone(),
+                client2.clone(),
+                in_flight2.clone(),
+                state_file.clone(),
+                batch.clone(),
+                index,
+                row,
+            );
+            let (opt, client, in_flight) =
+                (opt2.clone(), client2.clone(), in_flight2.clone());
+            async move {
+                // If we have a --per-item-timeout, give up on this row once
+                // it expires.
+                let result = match opt.per_item_timeout.map(Duration::from_secs) {
+                    Some(limit) => match time::timeout(limit, execution).await {
+                        Ok(result) => result,
+                        Err(_) => Err(time_out_row(
+                            &client,
+                            &in_flight,
+                            index,
+                            limit,
+                            opt.cancel_timed_out,
+                        )
+                        .await
+                        .into()),
+                    },
+                    None => execution.await,
+                };
+                Ok((id, result))
+            }
+            .boxed()
         })
         .boxed();
 
     // Now turn the stream of futures into a stream of executions, using
-    // `buffer_unordered` to execute up to `opt.max_tasks` in parallel. This is
+    // `buffer_unordered` to execute up to `max_tasks` in parallel. This is
     // basically the "payoff" for all the async code up above, and it is
     // wonderful.
     //
     // TODO: In tokio 0.1, this had weird buffering behavior, and
-    // appeared to wait until it buffered `opt.max_tasks` items. I have
+    // appeared to wait until it buffered `max_tasks` items. I have
     // not verified this in tokio 0.2.
-    let executions: BoxStream<Execution> = execution_futures
-        .try_buffer_unordered(opt.max_tasks)
+    //
+    // If we're asked to `--keep-going`, we report failed executions and drop
+    // them from the stream. Otherwise, the first failure stops everything,
+    // except for rows which exceeded `--per-item-timeout`.
+    let failures = Arc::new(FailureReporter::new(opt.errors_file.as_deref())?);
+    let failures2 = failures.clone();
+    let keep_going = opt.keep_going;
+    let executions: BoxStream<(RowId, Execution)> = execution_futures
+        .try_buffer_unordered(max_tasks)
+        .try_filter_map(move |(id, result)| {
+            future::ready(match result {
+                Ok(execution) => Ok(Some((id, execution))),
+                Err(err)
+                    if keep_going || err.downcast_ref::<ItemTimedOut>().is_some() =>
+                {
+                    failures2.report(&id, &err).map(|()| None)
+                }
+                Err(err) => failures2.record(&id, &err).and(Err(err)),
+            })
+        })
         .boxed();
 
-    // Copy our stream of `Execution`s to standard output as line-delimited
-    // JSON.
+    // Copy our stream of `Execution`s to standard output in the requested
+    // format.
     //
     // TODO: `forward` may also have weird buffering behavior.
-    let stdout = FramedWrite::new(io::stdout(), LineDelimitedJsonCodec::new());
-    executions.forward(stdout).await?;
+    let stdout = FramedWrite::new(io::stdout(), output_codec);
+    let forward = executions.forward(stdout);
+    tokio::pin!(forward);
+    tokio::select! {
+        result = &mut forward => {
+            result?;
+            match failures.count() {
+                0 => Ok(()),
+                count => Err(format_err!("could not process {} resources", count)),
+            }
+        }
+        () = shutdown => {
+            // We've stopped reading new resources, so give our running
+            // executions a chance to finish and be written out.
+            let grace_period = Duration::from_secs(opt.shutdown_timeout);
+            if let Ok(result) = time::timeout(grace_period, &mut forward).await {
+                result?;
+            }
+            let still_running = in_flight
+                .lock()
+                .expect("lock poisoned")
+                .drain()
+                .map(|(_, id)| id)
+                .collect::<Vec<_>>();
+            let action = if let Some(path) = &opt.running_executions_file {
+                write_running_executions(path, &still_running).await?;
+                format!("IDs written to {}", path.display())
+            } else {
+                let cancelled = cancel_executions(&client, &still_running).await?;
+                format!("cancelled {}", cancelled)
+            };
+            Err(Interrupted {
+                running: still_running.len(),
+                action,
+            }
+            .into())
+        }
+    }
+}
+
+/// Write the IDs of `executions` to `path`, one per line.
+async fn write_running_executions(
+    path: &Path,
+    executions: &[Id<Execution>],
+) -> Result<()> {
+    let mut data = String::new();
+    for id in executions {
+        data.push_str(id.as_str());
+        data.push('\n');
+    }
+    fs::write(path, data)
+        .await
+        .with_context(|_| format!("could not write {}", path.display()))?;
     Ok(())
 }
 
-/// Use our command-line options and a resource ID to create and run a BigML
-/// execution.
-async fn resource_id_to_execution(
+/// Cancel all of `executions`, returning how many we cancelled.
+async fn cancel_executions(
+    client: &Client,
+    executions: &[Id<Execution>],
+) -> Result<usize> {
+    let results = future::join_all(executions.iter().map(|id| async move {
+        warn!("cancelling {}", id);
+        client.cancel_execution(id).await
+    }))
+    .await;
+    let mut cancelled = 0;
+    for (id, result) in executions.iter().zip(results) {
+        match result {
+            Ok(()) => cancelled += 1,
+            Err(err) => error!("could not cancel {}: {}", id, err),
+        }
+    }
+    Ok(cancelled)
+}
+
+/// Use our command-line options and row number `index` of our inputs to
+/// create and run a BigML execution.
+async fn input_row_to_execution(
     opt: Arc<Opt>,
-    resource: String,
+    client: Client,
+    in_flight: InFlight,
+    state_file: Option<Arc<StateFile>>,
+    batch: Arc<ExecutionBatch>,
+    index: usize,
+    row: InputRow,
 ) -> Result<Execution> {
-    debug!("running {} on {}", opt.script, resource);
+    let row_id = &row.id;
+    // If a previous run started an execution for this row, try waiting for it
+    // before starting a new one.
+    let previous_run = state_file
+        .as_ref()
+        .and_then(|state_file| state_file.previous_run(row_id));
+    if let Some(PreviousRun::Started(id)) = previous_run {
+        debug!("resuming {} on {}", id, row_id);
+        in_flight
+            .lock()
+            .expect("lock poisoned")
+            .insert(index, id.to_owned());
+        let result = client.wait(id).await;
+        in_flight.lock().expect("lock poisoned").remove(&index);
+        match result {
+            Ok(execution) => {
+                if let Some(state_file) = &state_file {
+                    state_file.record_finished(row_id, id)?;
+                }
+                return Ok(execution);
+            }
+            Err(err) => {
+                warn!("could not resume {}, starting over: {}", id, err);
+            }
+        }
+    }
 
-    // Specify what script to run.
-    let mut args = execution::Args::default();
-    args.script = Some(opt.script.clone());
+    // Start with the arguments shared by every row, which specify our
+    // script, outputs and tags.
+    let mut args = batch.args().clone();
+    if let Some(script) = &args.script {
+        debug!("running {} on {}", script, row_id);
+    }
 
-    // Optionally set the script name.
-    if let Some(name) = opt.name.as_ref() {
+    // Prefer a script name specified by our row.
+    if let Some(name) = &row.name {
         args.name = Some(name.to_owned());
     }
 
-    // Specify the input dataset.
-    args.add_input(&opt.resource_input_name, &resource)?;
-
-    // Add any other inputs.
-    for input in &opt.inputs {
+    // Specify the inputs from our row, typically just the input dataset.
+    for input in &row.inputs {
         args.add_input(&input.name, &input.value)?;
     }
 
-    // Add outputs.
-    for output in &opt.outputs {
-        args.add_output(output);
+    // Add any other inputs, unless our row overrides them.
+    for input in &opt.inputs {
+        if !row
+            .inputs
+            .iter()
+            .any(|row_input| row_input.name == input.name)
+        {
+            args.add_input(&input.name, &input.value)?;
+        }
     }
 
-    // Add tags.
-    args.tags = opt.tags.clone();
-
     // Execute our script, with three types of retries.
     //
-    // 1. Retry the entire execution if it fails with an error that looks
-    //    transient. This is often caused by BigML overload, as far as we can
+    // 1. Retry the entire execution if it fails with an error matching
+    //    --retry-on. This is often caused by BigML overload, as far as we can
     //    tell.
     //     a. Retry the creation if that fails with a transient error. This is often
     //        caused by running out of slots.
     //     b. Internally retry the `wait` if it fails with a transient network error.
-    let exec_wait_opt = WaitOptions::default()
-        .retry_interval(Duration::from_secs(2 * 60))
-        .backoff_type(BackoffType::Exponential)
-        .allowed_errors(opt.retry_count);
-    let execution = wait(&exec_wait_opt, || {
-        create_and_wait_execution(&args, opt.retry_on.as_ref())
-    })
-    .await?;
+    let observer = RowObserver {
+        in_flight: &in_flight,
+        index,
+        state: state_file
+            .as_ref()
+            .map(|state_file| (state_file.as_ref(), row_id)),
+    };
+    let execution = batch.run_one_with(&client, index, &args, &observer).await?;
+    if let Some(state_file) = &state_file {
+        state_file.record_finished(row_id, execution.id())?;
+    }
     Ok(execution)
 }
 
-/// Create a BigML execution and wait for it to finish.
-///
-/// Returns a `WaitStatus`, allowing our caller to retry us as necessary.
-async fn create_and_wait_execution(
-    args: &execution::Args,
-    retry_on: Option<&Regex>,
-) -> WaitStatus<Execution, bigml::Error> {
-    // If we can't create a client, just give up immediately.
-    let client = try_with_permanent_failure!(Client::new_from_env());
-
-    // Attempt to create a new execution. This has custom retry logic with
-    // unusually long timeouts because temporary failures here are generally
-    // caused by hitting API limits, and if we wait 30 minutes, somebody else's
-    // batch job may finish. But if those retries fail, we want to fail
-    // permanently.
-    let create_wait_opt = WaitOptions::default()
-        .retry_interval(Duration::from_secs(60))
-        .backoff_type(BackoffType::Exponential)
-        .allowed_errors(6);
-    let execution = try_with_permanent_failure!(
-        wait(&create_wait_opt, || {
-            async {
-                // We use `try_wait`, because it knows which errors are
-                // permanent and which are temporary.
-                WaitStatus::Finished(try_wait!(client.create(args).await))
-            }
-        })
-        .await
-    );
-
-    // `client.wait` has its own internal retry logic, but it only triggers for
-    // things like failed HTTP calls to BigML. We also want to retry any script
-    // errors that match `retry_on`.
-    match client.wait(&execution.id()).await {
-        Ok(execution) => WaitStatus::Finished(execution),
-        Err(err) => match (err.original_bigml_error(), retry_on) {
-            // We failed with a `WaitError`, we have a `retry_on` pattern, and that
-            // pattern matches our error message from BigML.
-            (bigml::Error::WaitFailed { message, .. }, Some(retry_on))
-                if retry_on.is_match(&message) =>
-            {
-                error!("{} failed with temporary error: {}", execution.id(), err);
-                WaitStatus::FailedTemporarily(err)
-            }
+/// The `WaitOptions` we use when creating an execution, as specified by our
+/// command-line options.
+fn create_wait_options(opt: &Opt) -> WaitOptions {
+    WaitOptions::default()
+        .timeout(opt.timeout.map(Duration::from_secs))
+        .retry_interval(Duration::from_secs(opt.retry_interval))
+        .backoff_type(opt.backoff)
+        .allowed_errors(opt.max_retries)
+}
+
+/// Follows an execution started by `ExecutionBatch` for input row number
+/// `index`, so that we can find it again if we're interrupted.
+struct RowObserver<'a> {
+    /// The executions which are still running.
+    in_flight: &'a InFlight,
+
+    /// The index of the input row we're processing.
+    index: usize,
+
+    /// Our state file and the row we're processing, if we have one.
+    state: Option<(&'a StateFile, &'a RowId)>,
+}
+
+impl BatchObserver for RowObserver<'_> {
+    fn started(&self, execution: &Id<Execution>) -> bigml::Result<()> {
+        if let Some((state_file, row_id)) = self.state {
+            state_file
+                .record_started(row_id, execution)
+                .map_err(|err| bigml::Error::other(err.compat()))?;
+        }
+        self.in_flight
+            .lock()
+            .expect("lock poisoned")
+            .insert(self.index, execution.to_owned());
+        Ok(())
+    }
+
+    fn finished(&self, _execution: &Id<Execution>) {
+        self.in_flight
+            .lock()
+            .expect("lock poisoned")
+            .remove(&self.index);
+    }
 
-            // We have a different kind of error.
-            _ => WaitStatus::FailedPermanently(err),
-        },
+    fn log_entry(&self, execution: &Id<Execution>, entry: &LogEntry) {
+        eprintln!("{} {}", execution, entry);
     }
 }
@@ -0,0 +1,84 @@
+//! How many BigML tasks we should run at once.
+
+use bigml::account::AccountStatus;
+use common_failures::Result;
+use failure::{format_err, Error};
+use std::{convert::TryFrom, str::FromStr};
+
+/// The value of `--max-tasks`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MaxTasks {
+    /// Ask BigML how many tasks our subscription allows.
+    Auto,
+    /// Use exactly this many tasks.
+    Fixed(usize),
+}
+
+impl MaxTasks {
+    /// Decide how many tasks to use, given our account status and the number
+    /// of tasks we should leave free for other work. We always return at
+    /// least 1.
+    pub fn resolve(
+        self,
+        status: Option<&AccountStatus>,
+        reserve: usize,
+    ) -> Result<usize> {
+        match self {
+            MaxTasks::Fixed(max_tasks) => Ok(max_tasks),
+            MaxTasks::Auto => {
+                let limit = status
+                    .and_then(|status| status.max_parallel_tasks)
+                    .ok_or_else(|| {
+                        format_err!(
+                            "BigML did not report a task limit for --max-tasks auto"
+                        )
+                    })?;
+                let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+                Ok(limit.saturating_sub(reserve).max(1))
+            }
+        }
+    }
+}
+
+/// Declare a `FromStr` implementation so that `structopt` can parse
+/// `--max-tasks` directly.
+impl FromStr for MaxTasks {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "auto" {
+            return Ok(MaxTasks::Auto);
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Err(format_err!("--max-tasks must be at least 1")),
+            Ok(max_tasks) => Ok(MaxTasks::Fixed(max_tasks)),
+            Err(_) => Err(format_err!(
+                "--max-tasks must be a number or \"auto\", not {:?}",
+                s,
+            )),
+        }
+    }
+}
+
+#[test]
+fn parses_max_tasks() {
+    assert_eq!("auto".parse::<MaxTasks>().unwrap(), MaxTasks::Auto);
+    assert_eq!("4".parse::<MaxTasks>().unwrap(), MaxTasks::Fixed(4));
+    assert!("0".parse::<MaxTasks>().is_err());
+    assert!("lots".parse::<MaxTasks>().is_err());
+}
+
+#[test]
+fn auto_max_tasks_leaves_reserve() {
+    let status: AccountStatus = serde_json::from_value(serde_json::json!({
+        "parallelism": 8,
+        "running_tasks": 3,
+    }))
+    .unwrap();
+    let unknown: AccountStatus =
+        serde_json::from_value(serde_json::json!({})).unwrap();
+    assert_eq!(MaxTasks::Auto.resolve(Some(&status), 2).unwrap(), 6);
+    assert_eq!(MaxTasks::Auto.resolve(Some(&status), 10).unwrap(), 1);
+    assert_eq!(MaxTasks::Fixed(3).resolve(None, 2).unwrap(), 3);
+    assert!(MaxTasks::Auto.resolve(Some(&unknown), 0).is_err());
+}
@@ -0,0 +1,201 @@
+//! A [`tokio::codec::Encoder`] that outputs finished executions in the format
+//! requested on the command line.
+
+use bigml::resource::{Execution, Resource};
+use bytes::{BufMut, BytesMut};
+use common_failures::Result;
+use failure::{format_err, Error};
+use serde_json::{Map, Value};
+use std::str::FromStr;
+use tokio_util::codec::Encoder;
+
+use crate::input_row::RowId;
+use crate::line_delimited_json_codec::LineDelimitedJsonCodec;
+
+/// How should we write our results to standard output?
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Full execution objects, as line-delimited JSON.
+    Execution,
+    /// A JSON object for each execution, containing the input ID and the
+    /// values we were asked to `--emit`.
+    Values,
+    /// The ID of each resource created by an execution, one per line.
+    Resources,
+    /// A CSV file containing the input ID and the values we were asked to
+    /// `--emit`.
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "execution" => Ok(OutputFormat::Execution),
+            "values" => Ok(OutputFormat::Values),
+            "resources" => Ok(OutputFormat::Resources),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format_err!("unknown output format {:?}", s)),
+        }
+    }
+}
+
+/// A value to extract from each execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Emit {
+    /// The ID of the execution itself.
+    Execution,
+    /// The named output of the execution.
+    Output(String),
+}
+
+impl Emit {
+    /// The name to use for this value as a JSON key or a CSV header.
+    fn name(&self) -> &str {
+        match self {
+            Emit::Execution => "execution",
+            Emit::Output(name) => name,
+        }
+    }
+
+    /// Extract this value from `execution`, returning `Value::Null` if it's
+    /// missing.
+    fn value(&self, execution: &Execution) -> Value {
+        match self {
+            Emit::Execution => Value::String(execution.id().to_string()),
+            Emit::Output(name) => execution
+                .execution
+                .outputs
+                .iter()
+                .find(|output| &output.name == name)
+                .and_then(|output| output.value.clone())
+                .unwrap_or(Value::Null),
+        }
+    }
+}
+
+impl FromStr for Emit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "execution" {
+            Ok(Emit::Execution)
+        } else if let Some(name) = s.strip_prefix("output:") {
+            Ok(Emit::Output(name.to_owned()))
+        } else {
+            Err(format_err!(
+                "cannot emit {:?}, expected \"execution\" or \"output:NAME\"",
+                s,
+            ))
+        }
+    }
+}
+
+/// Encodes a stream of `(input_id, execution)` pairs in the specified
+/// `OutputFormat`.
+pub struct OutputCodec {
+    /// The format to use.
+    format: OutputFormat,
+    /// The values to extract from each execution.
+    emit: Vec<Emit>,
+    /// Have we written our CSV headers yet?
+    wrote_headers: bool,
+}
+
+impl OutputCodec {
+    /// Create a new `OutputCodec`.
+    pub fn new(format: OutputFormat, emit: Vec<Emit>) -> Result<Self> {
+        match format {
+            OutputFormat::Execution | OutputFormat::Resources if !emit.is_empty() => {
+                return Err(format_err!(
+                    "--emit can only be used with --output-format values or csv"
+                ));
+            }
+            OutputFormat::Values | OutputFormat::Csv if emit.is_empty() => {
+                return Err(format_err!(
+                    "--output-format values and csv need --emit or --output"
+                ));
+            }
+            _ => {}
+        }
+        Ok(Self {
+            format,
+            emit,
+            wrote_headers: false,
+        })
+    }
+
+    /// Write a single CSV record to `buf`.
+    fn encode_csv_record<I>(record: I, buf: &mut BytesMut) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.write_record(record)?;
+        let data = wtr.into_inner().map_err(|err| err.into_error())?;
+        buf.put(&data[..]);
+        Ok(())
+    }
+}
+
+impl Encoder<(RowId, Execution)> for OutputCodec {
+    type Error = Error;
+
+    fn encode(
+        &mut self,
+        (row_id, execution): (RowId, Execution),
+        buf: &mut BytesMut,
+    ) -> Result<()> {
+        match self.format {
+            OutputFormat::Execution => {
+                LineDelimitedJsonCodec::new().encode(execution, buf)
+            }
+            OutputFormat::Values => {
+                let mut values = Map::new();
+                let input = row_id.resource.map(Value::String).unwrap_or(Value::Null);
+                values.insert("input".to_owned(), input);
+                for emit in &self.emit {
+                    values.insert(emit.name().to_owned(), emit.value(&execution));
+                }
+                LineDelimitedJsonCodec::new().encode(values, buf)
+            }
+            OutputFormat::Resources => {
+                for resource in &execution.execution.output_resources {
+                    buf.put(resource.id.as_bytes());
+                    buf.put_u8(b'\n');
+                }
+                Ok(())
+            }
+            OutputFormat::Csv => {
+                if !self.wrote_headers {
+                    let headers = Some("input")
+                        .into_iter()
+                        .chain(self.emit.iter().map(|emit| emit.name()));
+                    Self::encode_csv_record(headers, buf)?;
+                    self.wrote_headers = true;
+                }
+                let fields = Some(row_id.resource.unwrap_or_default())
+                    .into_iter()
+                    .chain(self.emit.iter().map(|emit| match emit.value(&execution) {
+                        Value::Null => String::new(),
+                        Value::String(s) => s,
+                        value => value.to_string(),
+                    }))
+                    .collect::<Vec<_>>();
+                Self::encode_csv_record(fields, buf)
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_emit() {
+    assert_eq!("execution".parse::<Emit>().unwrap(), Emit::Execution);
+    assert_eq!(
+        "output:my_result".parse::<Emit>().unwrap(),
+        Emit::Output("my_result".to_owned()),
+    );
+    assert!("my_result".parse::<Emit>().is_err());
+}
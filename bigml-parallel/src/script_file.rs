@@ -0,0 +1,184 @@
+//! Creating the WhizzML script we run from a local `--script-file`.
+
+use bigml::{
+    list::ListQuery,
+    resource::{
+        script::{self, ScriptUpdate, Type},
+        AnyResourceId, Id, Resource, Script,
+    },
+    Client, ListOptions,
+};
+use common_failures::Result;
+use failure::{format_err, Error, ResultExt};
+use log::debug;
+use serde_json::Value;
+use std::{path::Path, str::FromStr};
+
+use crate::{input_row::InputFormat, Opt};
+
+/// A script input or output declaration, specified as "name:type".
+#[derive(Clone, Debug)]
+pub struct Declaration {
+    /// The name of the input or output.
+    pub name: String,
+
+    /// The BigML type of the input or output.
+    pub type_: Type,
+}
+
+impl FromStr for Declaration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let split = s.rsplitn(2, ':').collect::<Vec<&str>>();
+        if split.len() != 2 {
+            return Err(format_err!(
+                "declaration {:?} must have form \"name:type\"",
+                s
+            ));
+        }
+        Ok(Declaration {
+            name: split[1].to_owned(),
+            type_: split[0].parse()?,
+        })
+    }
+}
+
+/// Build the arguments for a script using the source code at `path`.
+///
+/// We declare every input and output in `opt.script_inputs` and
+/// `opt.script_outputs`. We also declare the resource input when reading
+/// resource IDs, and each `--input`, guessing its type from its value. Every
+/// `--output` must be declared explicitly, because we can't guess its type.
+pub fn script_args(path: &Path, opt: &Opt) -> Result<script::Args> {
+    let source_code = std::fs::read_to_string(path)
+        .with_context(|_| format!("could not read {}", path.display()))?;
+    let mut args = script::Args::new(source_code);
+    args.name = path
+        .file_stem()
+        .map(|name| name.to_string_lossy().into_owned());
+    args.tags = opt.tags.clone();
+
+    let declared =
+        |name: &str, decls: &[Declaration]| decls.iter().any(|decl| decl.name == name);
+    for decl in &opt.script_inputs {
+        args.inputs.push(script::Input::new(&decl.name, decl.type_));
+    }
+    if opt.input_format == InputFormat::Lines
+        && !declared(&opt.resource_input_name, &opt.script_inputs)
+    {
+        args.inputs.push(script::Input::new(
+            &opt.resource_input_name,
+            Type::ResourceId,
+        ));
+    }
+    for input in &opt.inputs {
+        if !declared(&input.name, &opt.script_inputs) {
+            if let Some(type_) = guess_type(&input.value) {
+                args.inputs.push(script::Input::new(&input.name, type_));
+            }
+        }
+    }
+
+    for decl in &opt.script_outputs {
+        args.outputs
+            .push(script::Output::new(&decl.name, decl.type_));
+    }
+    for output in &opt.outputs {
+        if !declared(output, &opt.script_outputs) {
+            return Err(format_err!(
+                "please declare the type of --output {} using --script-output {}:TYPE",
+                output,
+                output,
+            ));
+        }
+    }
+    Ok(args)
+}
+
+/// Guess the BigML type of an input from its `value`. Returns `None` for
+/// `null`, which we never pass to scripts.
+fn guess_type(value: &Value) -> Option<Type> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(Type::Boolean),
+        Value::Number(n) if n.is_i64() || n.is_u64() => Some(Type::Integer),
+        Value::Number(_) => Some(Type::Number),
+        Value::String(s) if s.parse::<AnyResourceId>().is_ok() => {
+            Some(Type::ResourceId)
+        }
+        Value::String(_) => Some(Type::String),
+        Value::Array(_) => Some(Type::List),
+        Value::Object(_) => Some(Type::Map),
+    }
+}
+
+/// Make sure a script matching `args` exists, and return its ID.
+///
+/// We tag each script we create with `tag`. If the newest script with that
+/// tag has the same source code and imports, we reuse it, updating its
+/// input and output declarations if necessary. Otherwise, we create a new
+/// script, because BigML doesn't allow changing a script's source code.
+pub async fn deploy_script(
+    client: &Client,
+    mut args: script::Args,
+    tag: &str,
+) -> Result<Id<Script>> {
+    // BigML can't search for tags containing commas.
+    if tag.contains(',') {
+        return Err(format_err!(
+            "cannot tag a script with {:?}, which has a comma",
+            tag
+        ));
+    }
+    let query = ListQuery::new().tag(tag).order_by_desc("created");
+    let options = ListOptions::default().limit(1).query(query);
+    if let Some(existing) = client.list::<Script>(&options).await?.into_iter().next() {
+        let existing = client.fetch(existing.id()).await?;
+        if existing.source_code == args.source_code && existing.imports == args.imports
+        {
+            debug!("reusing {}", existing.id());
+            let script = if existing.inputs != args.inputs
+                || existing.outputs != args.outputs
+            {
+                let update = ScriptUpdate {
+                    inputs: Some(args.inputs.into()),
+                    outputs: Some(args.outputs.into()),
+                    ..ScriptUpdate::default()
+                };
+                client.update(existing.id(), &update).await?
+            } else {
+                client.wait(existing.id()).await?
+            };
+            return Ok(script.id().to_owned());
+        }
+    }
+    args.tags.push(tag.to_owned());
+    let script = client.create_and_wait(&args).await?;
+    debug!("created {}", script.id());
+    Ok(script.id().to_owned())
+}
+
+#[test]
+fn parse_declarations() {
+    let decl = "model:model-id".parse::<Declaration>().unwrap();
+    assert_eq!(decl.name, "model");
+    assert_eq!(decl.type_, Type::ModelId);
+    assert!("model".parse::<Declaration>().is_err());
+    assert!("model:widget".parse::<Declaration>().is_err());
+}
+
+#[test]
+fn guess_input_types() {
+    use serde_json::json;
+
+    assert_eq!(guess_type(&json!(3)), Some(Type::Integer));
+    assert_eq!(guess_type(&json!(0.5)), Some(Type::Number));
+    assert_eq!(guess_type(&json!("deep")), Some(Type::String));
+    assert_eq!(
+        guess_type(&json!("dataset/5e5d87f6e476845bd8000000")),
+        Some(Type::ResourceId),
+    );
+    assert_eq!(guess_type(&json!([1, 2])), Some(Type::List));
+    assert_eq!(guess_type(&json!(null)), None);
+}
@@ -0,0 +1,68 @@
+//! Support for shutting down gracefully when we receive a signal.
+
+use failure::Fail;
+use futures::future;
+use log::{error, warn};
+use std::fmt;
+use tokio::signal;
+
+/// The exit code we use when we were interrupted by a signal. This is the
+/// traditional shell exit code for SIGINT.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// We were interrupted before we finished processing all our input.
+#[derive(Debug)]
+pub struct Interrupted {
+    /// The number of executions which were still running when we gave up
+    /// waiting.
+    pub running: usize,
+
+    /// A description of what we did with the executions which were still
+    /// running.
+    pub action: String,
+}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "interrupted: {} executions were still running ({})",
+            self.running, self.action,
+        )
+    }
+}
+
+impl Fail for Interrupted {}
+
+/// Wait until we receive SIGINT (Ctrl-C) or, on Unix, SIGTERM. If we can't
+/// listen for signals, this never returns.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(err) => {
+                error!("could not listen for SIGTERM: {}", err);
+                ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    ctrl_c().await;
+    warn!("shutting down: no new executions will be started");
+}
+
+/// Wait until we receive SIGINT (Ctrl-C). If we can't listen for it, this
+/// never returns.
+async fn ctrl_c() {
+    if let Err(err) = signal::ctrl_c().await {
+        error!("could not listen for Ctrl-C: {}", err);
+        future::pending::<()>().await;
+    }
+}
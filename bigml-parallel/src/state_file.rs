@@ -0,0 +1,200 @@
+//! A state file recording which input rows we've processed, so that we can
+//! resume after being interrupted.
+
+use bigml::resource::{Execution, Id};
+use common_failures::Result;
+use failure::{Fail, ResultExt};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::input_row::RowId;
+
+/// What happened to an execution.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ExecutionState {
+    /// We started the execution.
+    Started,
+    /// The execution finished successfully.
+    Finished,
+}
+
+/// A single line in our state file.
+#[derive(Debug, Deserialize, Serialize)]
+struct Record {
+    /// The input row, stored as a "resource" field and, for input formats
+    /// where rows may share a resource, a "row" number.
+    #[serde(flatten)]
+    id: RowId,
+    /// The execution processing row `id`.
+    execution: Id<Execution>,
+    /// What happened to `execution`.
+    state: ExecutionState,
+}
+
+/// What we did with an input row in a previous run.
+#[derive(Clone, Debug)]
+pub enum PreviousRun {
+    /// We started an execution, but it had not finished successfully. It may
+    /// still be running.
+    Started(Id<Execution>),
+    /// We finished processing this row.
+    Finished,
+}
+
+/// A state file, stored as line-delimited JSON. We only ever append to this
+/// file, so that it remains valid if we crash.
+pub struct StateFile {
+    /// The path to our state file.
+    path: PathBuf,
+    /// The file we append to.
+    file: Mutex<File>,
+    /// What happened to each input row in previous runs.
+    previous_runs: HashMap<RowId, PreviousRun>,
+}
+
+impl StateFile {
+    /// Open the state file at `path`, creating it if it doesn't exist, and
+    /// load any records left by previous runs.
+    pub fn open(path: &Path) -> Result<StateFile> {
+        let mut previous_runs = HashMap::new();
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line.with_context(|_| {
+                        format!("could not read {}", path.display())
+                    })?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    // If we crashed while writing a record, the last line may
+                    // be incomplete, so don't treat this as fatal.
+                    let record = match serde_json::from_str::<Record>(&line) {
+                        Ok(record) => record,
+                        Err(err) => {
+                            warn!("skipping bad line in {}: {}", path.display(), err);
+                            continue;
+                        }
+                    };
+                    let previous_run = match record.state {
+                        ExecutionState::Started => {
+                            PreviousRun::Started(record.execution)
+                        }
+                        ExecutionState::Finished => PreviousRun::Finished,
+                    };
+                    previous_runs.insert(record.id, previous_run);
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err
+                    .context(format!("could not open {}", path.display()))
+                    .into());
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|_| format!("could not open {}", path.display()))?;
+        Ok(StateFile {
+            path: path.to_owned(),
+            file: Mutex::new(file),
+            previous_runs,
+        })
+    }
+
+    /// What happened to row `id` in previous runs?
+    pub fn previous_run(&self, id: &RowId) -> Option<&PreviousRun> {
+        self.previous_runs.get(id)
+    }
+
+    /// Record that we've started `execution` to process row `id`.
+    pub fn record_started(&self, id: &RowId, execution: &Id<Execution>) -> Result<()> {
+        self.append(id, execution, ExecutionState::Started)
+    }
+
+    /// Record that `execution` has finished processing row `id`.
+    pub fn record_finished(
+        &self,
+        id: &RowId,
+        execution: &Id<Execution>,
+    ) -> Result<()> {
+        self.append(id, execution, ExecutionState::Finished)
+    }
+
+    /// Append a record to our state file.
+    fn append(
+        &self,
+        id: &RowId,
+        execution: &Id<Execution>,
+        state: ExecutionState,
+    ) -> Result<()> {
+        let record = Record {
+            id: id.to_owned(),
+            execution: execution.to_owned(),
+            state,
+        };
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        let mut file = self.file.lock().expect("lock poisoned");
+        file.write_all(line.as_bytes())
+            .and_then(|()| file.flush())
+            .with_context(|_| format!("could not write {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[test]
+fn state_file_remembers_previous_runs() {
+    let path = std::env::temp_dir().join(format!(
+        "bigml-parallel-state-file-test-{}.jsonl",
+        std::process::id(),
+    ));
+    let _ = std::fs::remove_file(&path);
+    let running: Id<Execution> = "execution/5bd0e4a1fd4d9f1cbd000000".parse().unwrap();
+    let finished: Id<Execution> =
+        "execution/5bd0e4a1fd4d9f1cbd000001".parse().unwrap();
+
+    let row_id = |resource: &str, row: Option<usize>| RowId {
+        resource: Some(resource.to_owned()),
+        row,
+    };
+
+    let state_file = StateFile::open(&path).unwrap();
+    state_file
+        .record_started(&row_id("dataset/1", None), &running)
+        .unwrap();
+    state_file
+        .record_started(&row_id("dataset/2", Some(1)), &finished)
+        .unwrap();
+    state_file
+        .record_finished(&row_id("dataset/2", Some(1)), &finished)
+        .unwrap();
+    drop(state_file);
+
+    let state_file = StateFile::open(&path).unwrap();
+    match state_file.previous_run(&row_id("dataset/1", None)) {
+        Some(PreviousRun::Started(id)) => assert_eq!(id.as_str(), running.as_str()),
+        other => panic!("unexpected previous run: {:?}", other),
+    }
+    assert!(matches!(
+        state_file.previous_run(&row_id("dataset/2", Some(1))),
+        Some(PreviousRun::Finished)
+    ));
+    // Rows which share a resource are tracked separately.
+    assert!(state_file
+        .previous_run(&row_id("dataset/2", Some(2)))
+        .is_none());
+    assert!(state_file
+        .previous_run(&row_id("dataset/3", None))
+        .is_none());
+    std::fs::remove_file(&path).unwrap();
+}
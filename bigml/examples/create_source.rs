@@ -1,6 +1,4 @@
-// This demo script doesn't currently work, because it relies on
-// `create_source_from_path`.
-#![allow(deprecated)]
+// Upload a local file to BigML as a new source.
 
 use bigml::{self, resource::Resource};
 use futures::{executor::block_on, FutureExt};
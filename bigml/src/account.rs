@@ -0,0 +1,66 @@
+//! Information about a BigML account, including its subscription and limits.
+//! See `Client::status`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The current status of a BigML account.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AccountStatus {
+    /// The number of credits remaining, for accounts which pay per use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits: Option<f64>,
+
+    /// The maximum number of tasks this account may run in parallel.
+    #[serde(
+        default,
+        alias = "parallelism",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_parallel_tasks: Option<u64>,
+
+    /// The number of tasks currently running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub running_tasks: Option<u64>,
+
+    /// The name of this account's subscription plan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<String>,
+
+    /// Any other fields returned by BigML.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl AccountStatus {
+    /// How many more tasks can we start before we hit our parallel task
+    /// limit? Returns `None` if BigML did not report both the limit and the
+    /// number of running tasks.
+    pub fn available_tasks(&self) -> Option<u64> {
+        Some(self.max_parallel_tasks?.saturating_sub(self.running_tasks?))
+    }
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn account_status_can_be_fetched() {
+    use crate::mock::MockServer;
+
+    let server = MockServer::start().await;
+    let json = serde_json::json!({
+        "credits": 1250.5,
+        "parallelism": 8,
+        "running_tasks": 3,
+        "subscription": "pro",
+        "username": "example",
+    });
+    server.mount_json("GET", "/status", 200, json).await;
+    let client = server.client().unwrap();
+    let status = client.status().await.unwrap();
+    assert_eq!(status.credits, Some(1250.5));
+    assert_eq!(status.max_parallel_tasks, Some(8));
+    assert_eq!(status.available_tasks(), Some(5));
+    assert_eq!(status.subscription.as_deref(), Some("pro"));
+    assert_eq!(status.extra["username"], "example");
+}
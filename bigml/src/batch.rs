@@ -0,0 +1,462 @@
+//! Running a WhizzML script many times in parallel, with different inputs.
+//!
+//! This is the engine behind `bigml-parallel`, packaged so that other Rust
+//! programs can use it directly.
+//!
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> bigml::Result<()> {
+//! use bigml::{batch::ExecutionBatch, resource::Script, Client};
+//! use futures::prelude::*;
+//! use std::collections::HashMap;
+//!
+//! let client = Client::new_from_env()?;
+//! let script = "script/5e5d87f6e476845bd8000000".parse()?;
+//! let batch = ExecutionBatch::new(script).concurrency(4).retry_count(2);
+//! let inputs = (0..10).map(|i| {
+//!     let mut inputs = HashMap::new();
+//!     inputs.insert("seed".to_owned(), serde_json::json!(i));
+//!     inputs
+//! });
+//! let mut results = batch.run(&client, inputs);
+//! while let Some(result) = results.next().await {
+//!     println!("input {}: {:?}", result.index, result.result.map(|e| e.resource));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use futures::{prelude::*, stream::BoxStream};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use crate::cache::stable_hash;
+use crate::client::Client;
+use crate::errors::*;
+use crate::resource::{
+    execution, execution::LogEntry, Execution, Id, Resource, Script,
+};
+use crate::wait::{wait, BackoffType, WaitOptions, WaitStatus};
+
+/// Named inputs for a single execution.
+pub type Inputs = HashMap<String, Value>;
+
+/// A function which decides whether a failed execution should be retried,
+/// given the error message reported by BigML.
+type RetryFilter = dyn Fn(&str) -> bool + Send + Sync;
+
+/// Hooks which allow callers of `ExecutionBatch::run_one_with` to follow an
+/// execution while it runs. Every method has a default which does nothing.
+pub trait BatchObserver: Send + Sync {
+    /// Called when we've created `execution`, before we wait for it. If this
+    /// returns an error, we stop without retrying.
+    fn started(&self, _execution: &Id<Execution>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when we've stopped waiting for `execution`, whether or not it
+    /// succeeded.
+    fn finished(&self, _execution: &Id<Execution>) {}
+
+    /// Called for each new log entry written by `execution`, if we were
+    /// configured using `ExecutionBatch::stream_logs`.
+    fn log_entry(&self, _execution: &Id<Execution>, _entry: &LogEntry) {}
+}
+
+/// A `BatchObserver` which ignores everything.
+struct NoopObserver;
+
+impl BatchObserver for NoopObserver {}
+
+/// Runs a WhizzML script once for each of many sets of inputs.
+#[derive(Clone)]
+pub struct ExecutionBatch {
+    /// The arguments shared by every execution.
+    args: execution::Args,
+
+    /// How many executions should we run at once?
+    concurrency: usize,
+
+    /// How many times should we retry an execution which fails?
+    retry_count: u16,
+
+    /// How long should we wait before retrying a failed execution?
+    retry_interval: Duration,
+
+    /// Which failed executions should we retry? If `None`, we retry all of
+    /// them.
+    retry_on: Option<Arc<RetryFilter>>,
+
+    /// How should we retry failed attempts to create an execution?
+    create_wait_options: WaitOptions,
+
+    /// How often should we poll for new log entries? If `None`, we don't.
+    log_poll_interval: Option<Duration>,
+
+    /// A random number chosen when this batch was created, which keeps our
+    /// idempotency tags from matching executions created by other batches.
+    nonce: u64,
+}
+
+impl ExecutionBatch {
+    /// Run `script`, with no other shared arguments.
+    pub fn new(script: Id<Script>) -> Self {
+        let mut args = execution::Args::default();
+        args.set_script(script);
+        Self::from_args(args)
+    }
+
+    /// Use `args` as a template for each execution. This can be used to
+    /// set a name, tags, expected outputs or inputs shared by every
+    /// execution.
+    pub fn from_args(args: execution::Args) -> Self {
+        ExecutionBatch {
+            args,
+            concurrency: 2,
+            retry_count: 0,
+            retry_interval: Duration::from_secs(2 * 60),
+            retry_on: None,
+            create_wait_options: WaitOptions::default()
+                .retry_interval(Duration::from_secs(60))
+                .backoff_type(BackoffType::Exponential)
+                .allowed_errors(6),
+            log_poll_interval: None,
+            nonce: rand::random(),
+        }
+    }
+
+    /// The arguments shared by every execution.
+    pub fn args(&self) -> &execution::Args {
+        &self.args
+    }
+
+    /// Run up to `concurrency` executions at once. Defaults to 2.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Retry each failed execution up to `retry_count` times, if it matches
+    /// `retry_on`. Defaults to 0.
+    pub fn retry_count(mut self, retry_count: u16) -> Self {
+        self.retry_count = retry_count;
+        self
+    }
+
+    /// Wait for `interval` before retrying a failed execution, increasing
+    /// exponentially. Defaults to 2 minutes.
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Only retry executions for which `retry_on` returns true when passed
+    /// the error message reported by BigML. By default, we retry any failed
+    /// execution.
+    pub fn retry_on<F>(mut self, retry_on: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.retry_on = Some(Arc::new(retry_on));
+        self
+    }
+
+    /// How to retry failed attempts to create an execution. These usually
+    /// fail because we've hit BigML's API limits, so the default waits
+    /// patiently.
+    pub fn create_wait_options(mut self, options: WaitOptions) -> Self {
+        self.create_wait_options = options;
+        self
+    }
+
+    /// Poll for new log entries every `interval` while each execution runs,
+    /// and pass them to `BatchObserver::log_entry`.
+    pub fn stream_logs(mut self, interval: Duration) -> Self {
+        self.log_poll_interval = Some(interval);
+        self
+    }
+
+    /// Run our script once for each item in `inputs`, returning results in
+    /// the order they finish.
+    pub fn run<'a, I>(
+        &'a self,
+        client: &'a Client,
+        inputs: I,
+    ) -> BoxStream<'a, BatchResult>
+    where
+        I: IntoIterator<Item = Inputs>,
+        I::IntoIter: Send + 'a,
+    {
+        stream::iter(inputs.into_iter().enumerate())
+            .map(move |(index, inputs)| async move {
+                let result = match self.args_for(inputs) {
+                    Ok(args) => self.run_one(client, index, &args).await,
+                    Err(err) => Err(err),
+                };
+                BatchResult { index, result }
+            })
+            .buffer_unordered(self.concurrency.max(1))
+            .boxed()
+    }
+
+    /// Run a single execution using `args`, which should already contain
+    /// our script and inputs, and wait for it to finish. Failures will be
+    /// retried as configured.
+    ///
+    /// `index` identifies this execution within the batch, and must be
+    /// different for each call.
+    pub async fn run_one(
+        &self,
+        client: &Client,
+        index: usize,
+        args: &execution::Args,
+    ) -> Result<Execution> {
+        self.run_one_with(client, index, args, &NoopObserver).await
+    }
+
+    /// Like `run_one`, but report each attempt's progress to `observer`.
+    pub async fn run_one_with(
+        &self,
+        client: &Client,
+        index: usize,
+        args: &execution::Args,
+        observer: &dyn BatchObserver,
+    ) -> Result<Execution> {
+        let exec_wait_options = WaitOptions::default()
+            .retry_interval(self.retry_interval)
+            .backoff_type(BackoffType::Exponential)
+            .allowed_errors(self.retry_count);
+        let attempt = AtomicU32::new(0);
+        wait(&exec_wait_options, || {
+            let attempt = attempt.fetch_add(1, Ordering::SeqCst);
+            let key = self.idempotency_tag(args, index, attempt);
+            let mut args = args.clone();
+            args.tags.push(key.clone());
+            async move {
+                let execution = try_with_permanent_failure!(
+                    client
+                        .create_opt_idempotent(&args, &key, &self.create_wait_options)
+                        .await
+                );
+                let id = execution.id();
+                try_with_permanent_failure!(observer.started(id));
+                let result = match self.log_poll_interval {
+                    Some(interval) => {
+                        let tail =
+                            client.stream_logs(id, interval).try_for_each(|entry| {
+                                observer.log_entry(id, &entry);
+                                future::ready(Ok(()))
+                            });
+                        let (result, tail_result) =
+                            future::join(client.wait(id), tail).await;
+                        if let Err(err) = tail_result {
+                            warn!("could not read logs for {}: {}", id, err);
+                        }
+                        result
+                    }
+                    None => client.wait(id).await,
+                };
+                observer.finished(id);
+                match result {
+                    Ok(execution) => WaitStatus::Finished(execution),
+                    Err(err) if self.should_retry(&err) => {
+                        error!(
+                            "{} failed with temporary error: {}",
+                            execution.id(),
+                            err
+                        );
+                        WaitStatus::FailedTemporarily(err)
+                    }
+                    Err(err) => WaitStatus::FailedPermanently(err),
+                }
+            }
+        })
+        .await
+    }
+
+    /// Add `inputs` to a copy of our shared arguments. Inputs are added in
+    /// order of name, so that identical inputs produce identical arguments.
+    fn args_for(&self, inputs: Inputs) -> Result<execution::Args> {
+        let mut inputs = inputs.into_iter().collect::<Vec<_>>();
+        inputs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut args = self.args.clone();
+        for (name, value) in inputs {
+            args.add_input(name, value)?;
+        }
+        Ok(args)
+    }
+
+    /// A tag which identifies attempt number `attempt` to run `args` as
+    /// execution `index` of this batch, for use with
+    /// `Client::create_opt_idempotent`.
+    ///
+    /// If creating an execution fails with a transient error, BigML may have
+    /// created it anyway. Tagging each attempt with a unique key allows us to
+    /// find and adopt that execution instead of creating a duplicate. The key
+    /// includes our `nonce`, so that it never matches executions created by
+    /// another batch or an earlier run.
+    fn idempotency_tag(
+        &self,
+        args: &execution::Args,
+        index: usize,
+        attempt: u32,
+    ) -> String {
+        let mut bytes =
+            serde_json::to_vec(args).expect("could not serialize execution arguments");
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        bytes.extend_from_slice(&(index as u64).to_le_bytes());
+        bytes.extend_from_slice(&attempt.to_le_bytes());
+        format!("bigml-parallel-key:{:016x}", stable_hash(&bytes))
+    }
+
+    /// Should we retry an execution which failed with `err`?
+    fn should_retry(&self, err: &Error) -> bool {
+        match err.original_bigml_error() {
+            Error::WaitFailed { message, .. } => match &self.retry_on {
+                Some(retry_on) => retry_on(message),
+                None => true,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for ExecutionBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutionBatch")
+            .field("args", &self.args)
+            .field("concurrency", &self.concurrency)
+            .field("retry_count", &self.retry_count)
+            .field("retry_interval", &self.retry_interval)
+            .field("retry_on", &self.retry_on.as_ref().map(|_| ".."))
+            .field("create_wait_options", &self.create_wait_options)
+            .field("log_poll_interval", &self.log_poll_interval)
+            .field("nonce", &self.nonce)
+            .finish()
+    }
+}
+
+/// The outcome of one execution in an `ExecutionBatch`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BatchResult {
+    /// The position of this execution's inputs in the iterator passed to
+    /// `ExecutionBatch::run`.
+    pub index: usize,
+
+    /// The finished execution, or the error which stopped it.
+    pub result: Result<Execution>,
+}
+
+#[test]
+fn idempotency_tags_are_unique_per_request() {
+    let script: Id<Script> = "script/5e5d87f6e476845bd8000000".parse().unwrap();
+    let batch = ExecutionBatch::new(script);
+    let args = batch.args().clone();
+    let tag = batch.idempotency_tag(&args, 0, 0);
+    assert!(tag.starts_with("bigml-parallel-key:"));
+    assert_eq!(tag, batch.idempotency_tag(&args.clone(), 0, 0));
+    assert_ne!(tag, batch.idempotency_tag(&args, 0, 1));
+    assert_ne!(tag, batch.idempotency_tag(&args, 1, 0));
+
+    // Another batch with identical arguments, such as a later run of the
+    // same job, uses different tags.
+    let other = ExecutionBatch::from_args(args.clone());
+    assert_ne!(tag, other.idempotency_tag(&args, 0, 0));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn batches_run_one_execution_per_input() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Execution> = "execution/5e5d87f6e476845bd8000000".parse().unwrap();
+    let execution = fixtures::execution(&id, &[]);
+    server.mount_create::<Execution>(execution.clone()).await;
+    server.mount_fetch(&id, execution).await;
+    let client = server.client().unwrap();
+
+    let script = "script/5e5d87f6e476845bd8000001".parse().unwrap();
+    let batch = ExecutionBatch::new(script).concurrency(2);
+    let inputs = (0..3).map(|i| {
+        let mut inputs = Inputs::new();
+        inputs.insert("seed".to_owned(), serde_json::json!(i));
+        inputs
+    });
+    let mut indices = batch
+        .run(&client, inputs)
+        .map(|result| {
+            assert_eq!(result.result.unwrap().resource, id);
+            result.index
+        })
+        .collect::<Vec<_>>()
+        .await;
+    indices.sort_unstable();
+    assert_eq!(indices, &[0, 1, 2]);
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let mut seeds = requests
+        .iter()
+        .filter(|req| req.method == wiremock::http::Method::Post)
+        .map(|req| {
+            let body: Value = serde_json::from_slice(&req.body).unwrap();
+            body["inputs"][0][1].as_u64().unwrap()
+        })
+        .collect::<Vec<_>>();
+    seeds.sort_unstable();
+    assert_eq!(seeds, &[0, 1, 2]);
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn observers_see_each_execution_start_and_finish() {
+    use crate::mock::{fixtures, MockServer};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl BatchObserver for Recorder {
+        fn started(&self, execution: &Id<Execution>) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("started {}", execution));
+            Ok(())
+        }
+
+        fn finished(&self, execution: &Id<Execution>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("finished {}", execution));
+        }
+    }
+
+    let server = MockServer::start().await;
+    let id: Id<Execution> = "execution/5e5d87f6e476845bd8000000".parse().unwrap();
+    let execution = fixtures::execution(&id, &[]);
+    server.mount_create::<Execution>(execution.clone()).await;
+    server.mount_fetch(&id, execution).await;
+    let client = server.client().unwrap();
+
+    let script = "script/5e5d87f6e476845bd8000001".parse().unwrap();
+    let batch = ExecutionBatch::new(script);
+    let recorder = Recorder::default();
+    let result = batch
+        .run_one_with(&client, 0, batch.args(), &recorder)
+        .await
+        .unwrap();
+    assert_eq!(result.resource, id);
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        &[format!("started {}", id), format!("finished {}", id)],
+    );
+}
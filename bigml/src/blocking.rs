@@ -0,0 +1,147 @@
+//! A synchronous client for use in code which doesn't use `async`.
+//!
+//! This is only available with the `blocking` feature. Like
+//! `reqwest::blocking`, it runs the async [`crate::Client`] on an internal
+//! runtime, so it must not be used from inside an async runtime.
+//!
+//! ```no_run
+//! use bigml::{blocking::Client, resource::{Id, Source}};
+//! use std::str::FromStr;
+//!
+//! # fn main() -> bigml::Result<()> {
+//! let client = Client::new_from_env()?;
+//! let id: Id<Source> = Id::from_str("source/123abc")?;
+//! let source = client.wait(&id)?;
+//! println!("{:?}", source);
+//! #   Ok(())
+//! # }
+//! ```
+
+use std::{future::Future, io::Write};
+use tokio::runtime::{self, Runtime};
+
+use crate::errors::*;
+use crate::progress::ProgressOptions;
+use crate::resource::{self, Id, Readiness, Resource, Updatable};
+use crate::wait::{WaitEvent, WaitOptions};
+
+/// A synchronous client connection to BigML.
+pub struct Client {
+    /// The async client which does the real work.
+    client: crate::Client,
+    /// The runtime we use to run `client`.
+    runtime: Runtime,
+}
+
+impl Client {
+    /// Create a new `Client` that will connect to
+    /// [`crate::DEFAULT_BIGML_DOMAIN`].
+    pub fn new<S1, S2>(username: S1, api_key: S2) -> Result<Client>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Client::from_async(crate::Client::new(username, api_key)?)
+    }
+
+    /// Create a new `Client` using the environment variables `BIGML_USERNAME`,
+    /// `BIGML_API_KEY` and (optionally) `BIGML_DOMAIN`.
+    pub fn new_from_env() -> Result<Client> {
+        Client::from_async(crate::Client::new_from_env()?)
+    }
+
+    /// Wrap an existing async `Client`, which may have been configured using
+    /// [`crate::ClientBuilder`].
+    pub fn from_async(client: crate::Client) -> Result<Client> {
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Client { client, runtime })
+    }
+
+    /// Run `fut` to completion on our runtime.
+    fn block_on<F: Future>(&self, fut: F) -> F::Output {
+        self.runtime.block_on(fut)
+    }
+
+    /// Create a new resource.
+    pub fn create<Args>(&self, args: &Args) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        self.block_on(self.client.create(args))
+    }
+
+    /// Create a new resource, and wait until it is ready.
+    pub fn create_and_wait<Args>(&self, args: &Args) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        self.block_on(self.client.create_and_wait(args))
+    }
+
+    /// Update the specified `resource` using `update`.
+    pub fn update<R: Resource + Updatable>(
+        &self,
+        resource: &Id<R>,
+        update: &<R as Updatable>::Update,
+    ) -> Result<R> {
+        self.block_on(self.client.update(resource, update))
+    }
+
+    /// Fetch an existing resource.
+    pub fn fetch<R: Resource>(&self, resource: &Id<R>) -> Result<R> {
+        self.block_on(self.client.fetch(resource))
+    }
+
+    /// Poll an existing resource, returning it once it's ready.
+    pub fn wait<R: Resource>(&self, resource: &Id<R>) -> Result<R> {
+        self.block_on(self.client.wait(resource))
+    }
+
+    /// Poll an existing resource, returning it once it's ready for use
+    /// according to `readiness`.
+    pub fn wait_until<R: Resource>(
+        &self,
+        resource: &Id<R>,
+        readiness: Readiness,
+    ) -> Result<R> {
+        self.block_on(self.client.wait_until(resource, readiness))
+    }
+
+    /// Poll an existing resource, returning it once it's ready, and honoring
+    /// wait and progress options.
+    pub fn wait_opt<'b, R: Resource>(
+        &self,
+        resource: &Id<R>,
+        wait_options: &WaitOptions,
+        progress_options: &mut ProgressOptions<'b, WaitEvent<R>>,
+    ) -> Result<R> {
+        self.block_on(
+            self.client
+                .wait_opt(resource, wait_options, progress_options),
+        )
+    }
+
+    /// Download a resource as a CSV file, writing it to `wtr`. This only makes
+    /// sense for certain kinds of resources.
+    pub fn download<R: Resource, W: Write>(
+        &self,
+        resource: &Id<R>,
+        mut wtr: W,
+    ) -> Result<()> {
+        self.block_on(async {
+            let mut res = self.client.download(resource).await?;
+            while let Some(chunk) = res.chunk().await? {
+                wtr.write_all(&chunk)?;
+            }
+            wtr.flush()?;
+            Ok(())
+        })
+    }
+
+    /// Delete the specified resource.
+    pub fn delete<R: Resource>(&self, resource: &Id<R>) -> Result<()> {
+        self.block_on(self.client.delete(resource))
+    }
+}
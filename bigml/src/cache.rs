@@ -0,0 +1,127 @@
+//! Remembering which resources we've created, so that `Client::create_cached`
+//! can reuse them instead of creating identical resources again.
+//!
+//! The cache is a JSON file mapping a hash of each resource's creation
+//! arguments to the ID of the resource we created. The hash also covers the
+//! BigML domain and any organization or project the client is scoped to,
+//! because resources created in one can't be reused in another. Since
+//! arguments include
+//! the IDs of any parent resources, such as the source used to create a
+//! dataset, a whole pipeline can be re-run cheaply as long as its inputs are
+//! unchanged.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use url::Url;
+
+use crate::errors::*;
+use crate::resource::{Args, Resource};
+
+/// A persistent map from creation arguments to resource IDs.
+#[derive(Debug)]
+pub(crate) struct ResourceCache {
+    /// The path to our cache file.
+    path: PathBuf,
+    /// Resource IDs, keyed by `cache_key`.
+    ids: Mutex<BTreeMap<String, String>>,
+}
+
+impl ResourceCache {
+    /// Load the cache at `path`. If it doesn't exist yet, start with an empty
+    /// cache, and create the file the first time we add something.
+    pub(crate) fn open(path: PathBuf) -> Result<ResourceCache> {
+        let ids = match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|err| Error::could_not_read_file(&path, err))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(Error::could_not_read_file(&path, err)),
+        };
+        Ok(ResourceCache {
+            path,
+            ids: Mutex::new(ids),
+        })
+    }
+
+    /// Look up the resource created using `key`.
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        self.ids.lock().expect("lock poisoned").get(key).cloned()
+    }
+
+    /// Record that we created `id` using `key`, and save the cache.
+    pub(crate) fn insert(&self, key: String, id: String) -> Result<()> {
+        let mut ids = self.ids.lock().expect("lock poisoned");
+        ids.insert(key, id);
+        save(&self.path, &ids)
+    }
+}
+
+/// Write `ids` to `path`.
+fn save(path: &Path, ids: &BTreeMap<String, String>) -> Result<()> {
+    let data = serde_json::to_vec_pretty(ids)?;
+    fs::write(path, data).map_err(|err| Error::could_not_write_file(path, err))
+}
+
+/// The cache key for `args`, which includes the resource type and a hash of
+/// `scope` and the arguments. `scope` should identify where the resource will
+/// be created, without any credentials.
+///
+/// We hash the JSON form of `args`, whose object keys are always sorted. We
+/// use FNV-1a instead of Rust's `DefaultHasher`, because the cache may
+/// outlive the program that wrote it, and `DefaultHasher` may change between
+/// Rust releases.
+pub(crate) fn cache_key<A: Args>(scope: &Url, args: &A) -> Result<String> {
+    let mut bytes = scope.as_str().as_bytes().to_vec();
+    // Separate the scope from the arguments, which can't contain a NUL byte.
+    bytes.push(0);
+    bytes.extend(serde_json::to_vec(&serde_json::to_value(args)?)?);
+    Ok(format!(
+        "{}:{:016x}",
+        A::Resource::api_name(),
+        stable_hash(&bytes)
+    ))
+}
+
+/// Hash `bytes` using FNV-1a, which will give the same result in every
+/// version of Rust.
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[test]
+fn cache_keys_depend_on_args() {
+    use crate::resource::{dataset, Id, Source};
+
+    let scope: Url = "https://bigml.io/andromeda".parse().unwrap();
+    let source1: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    let source2: Id<Source> = "source/5e5d87f6e476845bd8000001".parse().unwrap();
+    let args1 = dataset::Args::from_source(source1.clone());
+    let key1 = cache_key(&scope, &args1).unwrap();
+    assert!(key1.starts_with("dataset:"));
+    assert_eq!(
+        key1,
+        cache_key(&scope, &dataset::Args::from_source(source1)).unwrap()
+    );
+    assert_ne!(
+        key1,
+        cache_key(&scope, &dataset::Args::from_source(source2)).unwrap()
+    );
+
+    // The same arguments in a different domain or project are different
+    // resources.
+    let domain: Url = "https://au.bigml.io/andromeda".parse().unwrap();
+    assert_ne!(key1, cache_key(&domain, &args1).unwrap());
+    let project: Url = "https://bigml.io/andromeda?project=project%2F1"
+        .parse()
+        .unwrap();
+    assert_ne!(key1, cache_key(&project, &args1).unwrap());
+}
@@ -0,0 +1,273 @@
+//! Deleting old resources in bulk.
+//!
+//! Tools like `bigml-parallel` can create thousands of temporary resources.
+//! This module finds resources which are older than a cutoff and which match
+//! the specified tags and project, and deletes them.
+//!
+//! To avoid deleting everything in an account by accident, `cleanup` fails
+//! unless a tag or project is specified, or `CleanupOptions::all_resources`
+//! is used.
+//!
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> bigml::Result<()> {
+//! use bigml::{cleanup::{cleanup, CleanupOptions}, resource::Dataset, Client};
+//! use chrono::{Duration, Utc};
+//!
+//! let client = Client::new_from_env()?;
+//! let options = CleanupOptions::new(Utc::now() - Duration::days(7))
+//!     .tag("temporary")
+//!     .dry_run(true);
+//! let doomed = cleanup::<Dataset>(&client, &options).await?;
+//! println!("would delete {} datasets", doomed.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, Utc};
+use futures::prelude::*;
+
+use crate::client::Client;
+use crate::errors::*;
+use crate::list::{ListOptions, ListQuery};
+use crate::resource::{self, Id, Project, Resource};
+
+/// Which resources should `cleanup` delete, and how?
+#[derive(Clone, Debug)]
+pub struct CleanupOptions {
+    /// Only delete resources created before this time.
+    created_before: DateTime<Utc>,
+
+    /// Only delete resources with at least one of these tags.
+    tags: Vec<String>,
+
+    /// Only delete resources in this project.
+    project: Option<Id<Project>>,
+
+    /// Allow deleting resources without specifying any tags or a project.
+    all_resources: bool,
+
+    /// How many resources should we delete at once?
+    concurrency: usize,
+
+    /// If true, find matching resources but don't delete them.
+    dry_run: bool,
+}
+
+impl CleanupOptions {
+    /// Delete resources created before `created_before`.
+    pub fn new(created_before: DateTime<Utc>) -> Self {
+        CleanupOptions {
+            created_before,
+            tags: vec![],
+            project: None,
+            all_resources: false,
+            concurrency: 4,
+            dry_run: false,
+        }
+    }
+
+    /// Only delete resources with the specified tag. If called more than
+    /// once, resources with any of the specified tags will be deleted.
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Only delete resources in `project`.
+    pub fn project(mut self, project: Id<Project>) -> Self {
+        self.project = Some(project);
+        self
+    }
+
+    /// Delete every resource created before our cutoff, even if no tags or
+    /// project have been specified. Without this, `cleanup` refuses to run
+    /// unless it's limited to a tag or project.
+    pub fn all_resources(mut self) -> Self {
+        self.all_resources = true;
+        self
+    }
+
+    /// Delete up to `concurrency` resources at a time. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// If `dry_run` is true, find matching resources but don't delete them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Make sure our tags are valid, and that we won't delete every resource
+    /// of a type by accident.
+    fn check(&self) -> Result<()> {
+        if let Some(tag) = self.tags.iter().find(|tag| tag.contains(',')) {
+            return Err(format_err!(
+                "cannot clean up tag {:?} containing a comma",
+                tag
+            ));
+        }
+        if self.tags.is_empty() && self.project.is_none() && !self.all_resources {
+            return Err(format_err!(
+                "refusing to clean up resources without a tag or project (use \
+                 CleanupOptions::all_resources to override)"
+            ));
+        }
+        Ok(())
+    }
+
+    /// The `ListOptions` which will find matching resources.
+    fn list_options(&self) -> ListOptions {
+        let mut query = ListQuery::new().created_before(self.created_before);
+        if !self.tags.is_empty() {
+            query = query.tags_in(&self.tags);
+        }
+        if let Some(project) = &self.project {
+            query = query.filter("project", project.as_str());
+        }
+        ListOptions::default().limit(200).query(query)
+    }
+}
+
+/// Delete all resources of type `R` matching `options`, and return their IDs.
+/// If `options` specifies a dry run, return the IDs without deleting
+/// anything. Fails if `options` doesn't specify a tag or project, unless it
+/// uses `CleanupOptions::all_resources`.
+///
+/// We find all matching resources before deleting any of them, so that
+/// deletions don't interfere with paging through the list.
+pub async fn cleanup<R: Resource>(
+    client: &Client,
+    options: &CleanupOptions,
+) -> Result<Vec<Id<R>>> {
+    options.check()?;
+    let list_options = options.list_options();
+    let ids = client
+        .list_id_stream::<R>(&list_options)
+        .try_collect::<Vec<_>>()
+        .await?;
+    if options.dry_run {
+        debug!("dry run, not deleting {} resources", ids.len());
+    } else {
+        debug!("deleting {} resources", ids.len());
+        client
+            .delete_all(ids.iter().cloned(), options.concurrency)
+            .await?;
+    }
+    Ok(ids)
+}
+
+/// Like `cleanup`, but for resources with the specified API name, such as
+/// `"dataset"`. This is useful when the resource type is chosen at runtime.
+pub async fn cleanup_by_name(
+    client: &Client,
+    api_name: &str,
+    options: &CleanupOptions,
+) -> Result<Vec<String>> {
+    /// Call `cleanup` for the matching resource type, and convert the
+    /// resulting IDs to strings.
+    macro_rules! dispatch {
+        ($($ty:ident,)*) => {
+            $(
+                if api_name == resource::$ty::api_name() {
+                    let ids = cleanup::<resource::$ty>(client, options).await?;
+                    return Ok(ids.iter().map(|id| id.to_string()).collect());
+                }
+            )*
+        };
+    }
+    dispatch! {
+        Association,
+        BatchCentroid,
+        BatchPrediction,
+        BatchProjection,
+        Centroid,
+        Cluster,
+        Dataset,
+        Deepnet,
+        Ensemble,
+        Evaluation,
+        Execution,
+        ExternalConnector,
+        Fusion,
+        Library,
+        LogisticRegression,
+        Model,
+        OptiML,
+        Pca,
+        Prediction,
+        Project,
+        Projection,
+        Sample,
+        Script,
+        Source,
+    }
+    Err(format_err!("unknown BigML resource type {:?}", api_name))
+}
+
+#[test]
+fn list_options_match_cleanup_options() {
+    let project: Id<Project> = "project/5e5d87f6e476845bd8000000".parse().unwrap();
+    let created_before = "2020-03-01T12:00:00Z".parse().unwrap();
+    let options = CleanupOptions::new(created_before)
+        .tag("tmp")
+        .tag("scratch")
+        .project(project);
+    let mut url = url::Url::parse("https://bigml.io/dataset").unwrap();
+    options.list_options().add_to_url(&mut url, 0);
+    assert_eq!(
+        url.query(),
+        Some(
+            "tags__in=tmp%2Cscratch&created__lt=2020-03-01T12%3A00%3A00\
+             &project=project%2F5e5d87f6e476845bd8000000&limit=200&offset=0"
+        ),
+    );
+}
+
+#[test]
+fn cleanup_must_be_limited() {
+    let created_before = "2020-03-01T12:00:00Z".parse().unwrap();
+    let options = CleanupOptions::new(created_before);
+    assert!(options.check().is_err());
+    assert!(options.clone().tag("tmp").check().is_ok());
+    assert!(options.clone().tag("a,b").check().is_err());
+    assert!(options.all_resources().check().is_ok());
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn cleanup_only_needs_resource_ids() {
+    use crate::mock::MockServer;
+    use crate::resource::Dataset;
+
+    let server = MockServer::start().await;
+    let id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    server
+        .mount_json(
+            "GET",
+            "/dataset",
+            200,
+            serde_json::json!({
+                "meta": {
+                    "limit": 200,
+                    "next": null,
+                    "offset": 0,
+                    "previous": null,
+                    "total_count": 1,
+                },
+                "objects": [{ "resource": id.as_str() }],
+            }),
+        )
+        .await;
+    let client = server.client().unwrap();
+
+    let created_before = "2020-03-01T12:00:00Z".parse().unwrap();
+    let options = CleanupOptions::new(created_before).tag("tmp").dry_run(true);
+    let ids = cleanup::<Dataset>(&client, &options).await.unwrap();
+    assert_eq!(ids, vec![id]);
+
+    let unlimited = CleanupOptions::new(created_before).dry_run(true);
+    assert!(cleanup::<Dataset>(&client, &unlimited).await.is_err());
+}
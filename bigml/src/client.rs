@@ -1,34 +1,79 @@
 //! A client connection to BigML.
 
 use bytes::Bytes;
-use failure::Fail;
-use futures::{prelude::*, FutureExt};
+use futures::{channel::mpsc, prelude::*, stream::BoxStream, FutureExt};
 use reqwest::{self, multipart, StatusCode};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::error;
+use std::fmt;
 use std::future::Future;
+use std::io;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tokio::fs;
-use tokio_util::codec;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock,
+};
+use std::time::{Duration, Instant};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncWrite},
+    time::sleep,
+};
+use tokio_util::{codec, io::StreamReader};
 use url::Url;
 
+use crate::account::AccountStatus;
+use crate::cache::{cache_key, ResourceCache};
 use crate::errors::*;
-use crate::progress::ProgressOptions;
-use crate::resource::{self, Id, Resource, Source, Updatable};
-use crate::wait::{wait, BackoffType, WaitOptions, WaitStatus};
+use crate::export::ExportFormat;
+use crate::list::{ListOptions, ListPage, ListQuery};
+use crate::metrics::{endpoint_and_id, ApiCall, Metrics};
+use crate::progress::{ProgressOptions, UploadProgress};
+use crate::resource::{
+    self,
+    execution::{Execution, ExecutionProgress, LogEntry},
+    library::{self, LocalLibrary},
+    prediction, sample, script, source, BatchPrediction, Dataset, Id, Library,
+    Prediction, Project, Readiness, Resource, Sample, Script, Source, Updatable,
+};
+#[cfg(feature = "csv")]
+use crate::rows::CsvRecordDecoder;
+use crate::vcr::{RecordedRequest, Vcr, VcrMode};
+use crate::wait::{
+    wait, wait_observed, BackoffType, Percentage, RateLimiter, RetryPolicy, WaitEvent,
+    WaitLoopEvent, WaitOptions, WaitStatus,
+};
+
+/// How many times should we retry a request after being rate limited?
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// How long should we wait after the first time we're rate limited, if
+/// BigML doesn't tell us? This doubles after each retry.
+const MIN_RATE_LIMIT_DELAY_SECS: u64 = 5;
 
 /// The default domain to use for making API requests to BigML.
 pub static DEFAULT_BIGML_DOMAIN: &str = "bigml.io";
 
 /// A client connection to BigML.
+///
+/// Cloning a `Client` is cheap, and all clones share the same connection
+/// pool, rate limit and metrics. So instead of creating a new `Client` for
+/// each task, create one and clone it.
+#[derive(Clone)]
 pub struct Client {
     url: Url,
     username: String,
     api_key: String,
+    scope: Vec<(&'static str, String)>,
+    http: reqwest::Client,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_policy: Option<RetryPolicy>,
+    metrics: Option<Arc<dyn Metrics>>,
+    vcr: Option<Arc<Vcr>>,
+    cache: Option<Arc<ResourceCache>>,
 }
 
 impl Client {
@@ -40,7 +85,7 @@ impl Client {
         S1: Into<String>,
         S2: Into<String>,
     {
-        Self::new_with_domain(DEFAULT_BIGML_DOMAIN, username, api_key)
+        ClientBuilder::new(username, api_key).build()
     }
 
     /// Create a new `Client`, specifying the BigML domain to connect to. Use
@@ -56,27 +101,57 @@ impl Client {
         S1: Into<String>,
         S2: Into<String>,
     {
-        let url_str = format!("https://{}/", domain);
-        let url = url_str
-            .parse()
-            .map_err(|err| Error::could_not_parse_url_with_domain(domain, err))?;
-        Ok(Client {
-            url,
-            username: username.into(),
-            api_key: api_key.into(),
-        })
+        ClientBuilder::new(username, api_key).domain(domain).build()
+    }
+
+    /// Create a new `Client` which connects to `base_url` instead of a BigML
+    /// domain. This is mostly useful for testing against a mock server, such
+    /// as the one in `bigml::mock`.
+    pub fn new_with_base_url<S1, S2>(
+        base_url: Url,
+        username: S1,
+        api_key: S2,
+    ) -> Result<Client>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        ClientBuilder::new(username, api_key)
+            .base_url(base_url)
+            .build()
+    }
+
+    /// Create a new `Client` that will access resources belonging to the
+    /// specified BigML organization, such as `"organization/123abc"`.
+    pub fn new_for_organization<S1, S2, S3>(
+        username: S1,
+        api_key: S2,
+        organization: S3,
+    ) -> Result<Client>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        ClientBuilder::new(username, api_key)
+            .organization(organization)
+            .build()
     }
 
     /// Create a new client, using the environment variables `BIGML_USERNAME`,
     /// `BIGML_API_KEY` and optionally `BIGML_DOMAIN` to configure it.
     pub fn new_from_env() -> Result<Client> {
-        let domain = env::var("BIGML_DOMAIN")
-            .unwrap_or_else(|_| DEFAULT_BIGML_DOMAIN.to_owned());
-        let username = env::var("BIGML_USERNAME")
-            .map_err(|_| format_err!("must specify BIGML_USERNAME"))?;
-        let api_key = env::var("BIGML_API_KEY")
-            .map_err(|_| format_err!("must specify BIGML_API_KEY"))?;
-        Self::new_with_domain(&domain, username, api_key)
+        ClientBuilder::from_env()?.build()
+    }
+
+    /// Create a `ClientBuilder`, which can be used to customize how we
+    /// connect to BigML.
+    pub fn builder<S1, S2>(username: S1, api_key: S2) -> ClientBuilder
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        ClientBuilder::new(username, api_key)
     }
 
     /// Format our BigML auth credentials.
@@ -84,11 +159,26 @@ impl Client {
         format!("username={}&api_key={}", self.username, self.api_key)
     }
 
+    /// Our base URL, plus any organization or project we're scoped to, but
+    /// without our credentials.
+    fn scope_url(&self) -> Url {
+        let mut url = self.url.clone();
+        url.set_query(None);
+        if !self.scope.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.scope);
+        }
+        url
+    }
+
     /// Generate an authenticated URL with the specified path.
     fn url(&self, path: &str) -> Url {
         let mut url: Url = self.url.clone();
-        url.set_path(path);
+        let base_path = self.url.path().trim_end_matches('/');
+        url.set_path(&format!("{}/{}", base_path, path.trim_start_matches('/')));
         url.set_query(Some(&self.auth()));
+        if !self.scope.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.scope);
+        }
         url
     }
 
@@ -97,17 +187,19 @@ impl Client {
     where
         Args: resource::Args,
     {
+        check_supported::<Args::Resource>(
+            Args::Resource::SUPPORTS_CREATE,
+            "creating",
+        )?;
         let url = self.url(Args::Resource::create_path());
         debug!(
             "POST {} {:#?}",
             Args::Resource::create_path(),
             &serde_json::to_string(args)
         );
-        let client = reqwest::Client::new();
-        let res = client
-            .post(url.clone())
-            .json(args)
-            .send()
+        let client = &self.http;
+        let res = self
+            .send(client.post(url.clone()).json(args))
             .await
             .map_err(|e| Error::could_not_access_url(&url, e))?;
         self.handle_response_and_deserialize(&url, res).await
@@ -125,10 +217,174 @@ impl Client {
         self.wait(resource.id()).await
     }
 
+    /// Create a new resource, and wait until it is ready, honoring wait and
+    /// progress options. Failed attempts to create the resource will be
+    /// retried if they look temporary. Any timeout in `wait_options` covers
+    /// both creating the resource and waiting for it.
+    pub async fn create_and_wait_opt<'a, 'b, Args>(
+        &self,
+        args: &'a Args,
+        wait_options: &'a WaitOptions,
+        progress_options: &'a mut ProgressOptions<'b, WaitEvent<Args::Resource>>,
+    ) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        let wait_options = wait_options.clone().start_clock();
+        let resource = self.create_opt(args, &wait_options).await?;
+        self.wait_opt(resource.id(), &wait_options, progress_options)
+            .await
+    }
+
+    /// Create a new resource, without waiting for BigML to finish building
+    /// it. This is the same as `create`, but the name makes it clear that
+    /// the returned resource will usually not be ready to use yet. Pass its
+    /// ID to `wait` once you need it.
+    pub async fn create_no_wait<'a, Args>(
+        &'a self,
+        args: &'a Args,
+    ) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        self.create(args).await
+    }
+
+    /// Create a new resource, retrying failed attempts which look temporary
+    /// as specified by `wait_options`, but without waiting for BigML to
+    /// finish building it. This is useful if you need to record the ID of
+    /// the new resource before waiting.
+    pub async fn create_opt<'a, Args>(
+        &'a self,
+        args: &'a Args,
+        wait_options: &'a WaitOptions,
+    ) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        wait(wait_options, || async {
+            WaitStatus::Finished(try_wait!(self.create(args).await))
+        })
+        .await
+    }
+
+    /// Like `create_opt`, but safe to retry even if BigML created the
+    /// resource before the failure was reported to us.
+    ///
+    /// `args` must include `idempotency_tag` in its tags, and the tag should
+    /// be unique to this request. Before each retry, we look for the newest
+    /// resource with that tag, and return it instead of creating another.
+    pub async fn create_opt_idempotent<'a, Args>(
+        &'a self,
+        args: &'a Args,
+        idempotency_tag: &'a str,
+        wait_options: &'a WaitOptions,
+    ) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        let has_tag = serde_json::to_value(args)?
+            .get("tags")
+            .and_then(|tags| tags.as_array())
+            .map(|tags| tags.iter().any(|tag| tag == idempotency_tag))
+            .unwrap_or(false);
+        if !has_tag {
+            return Err(format_err!(
+                "arguments must include the idempotency tag {:?}",
+                idempotency_tag,
+            ));
+        }
+
+        let attempted = AtomicBool::new(false);
+        wait(wait_options, || async {
+            if attempted.swap(true, Ordering::SeqCst) {
+                let query = ListQuery::new()
+                    .tag(idempotency_tag)
+                    .order_by_desc("created");
+                let options = ListOptions::default().limit(1).query(query);
+                let existing =
+                    try_wait!(self.list_ids::<Args::Resource>(&options).await);
+                if let Some(existing) = existing.into_iter().next() {
+                    debug!("adopting previously created {}", existing);
+                    return WaitStatus::Finished(try_wait!(
+                        self.fetch(&existing).await
+                    ));
+                }
+            }
+            WaitStatus::Finished(try_wait!(self.create(args).await))
+        })
+        .await
+    }
+
+    /// Like `create_and_wait`, but if we've already created a resource using
+    /// identical `args`, return that resource instead of creating another.
+    ///
+    /// Resources are remembered in the file passed to
+    /// `ClientBuilder::cache_to`. If no cache was configured, this is the same
+    /// as `create_and_wait`. Cached resources which have been deleted or which
+    /// failed will be created again.
+    pub async fn create_cached<'a, Args>(
+        &'a self,
+        args: &'a Args,
+    ) -> Result<Args::Resource>
+    where
+        Args: resource::Args,
+    {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.create_and_wait(args).await,
+        };
+        let key = cache_key(&self.scope_url(), args)?;
+        if let Some(id) = cache.get(&key) {
+            let id: Id<Args::Resource> = id.parse()?;
+            match self.fetch(&id).await {
+                Ok(resource) if resource.status().code().is_err() => {
+                    debug!("cached {} failed, creating it again", id);
+                }
+                Ok(_) => {
+                    debug!("reusing cached {}", id);
+                    return self.wait(&id).await;
+                }
+                Err(err) if err.api_error_kind() == Some(BigMlApiError::NotFound) => {
+                    debug!("cached {} was deleted, creating it again", id);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        let resource = self.create_and_wait(args).await?;
+        cache.insert(key, resource.id().to_string())?;
+        Ok(resource)
+    }
+
+    /// Get a URL pointing at the human-readable version of `resource`, on the
+    /// dashboard which corresponds to the BigML domain we connect to. For
+    /// example, if we use the API at `au.bigml.io`, this returns a URL on
+    /// `au.bigml.com`.
+    pub fn dashboard_url<R: Resource>(&self, resource: &Id<R>) -> Url {
+        resource::dashboard_url_for_api(&self.url, resource.as_str())
+    }
+
+    /// Make a single prediction using `predictor`, which may be the ID of a
+    /// model or an ensemble, and wait for the result.
+    ///
+    /// The predicted value is available as `Prediction::output`.
+    pub async fn predict<P>(
+        &self,
+        predictor: P,
+        input_data: HashMap<String, serde_json::Value>,
+    ) -> Result<Prediction>
+    where
+        P: Into<prediction::Predictor>,
+    {
+        let args = prediction::Args::new(predictor, input_data);
+        self.create_and_wait(&args).await
+    }
+
     /// Create a BigML data source using data from the specified stream.  We
     /// stream the data over the network without trying to load it all into
     /// memory at once.
-    #[deprecated = "This won't work until BigML fixes Transfer-Encoding: chunked"]
+    #[deprecated = "This won't work until BigML fixes Transfer-Encoding: chunked. \
+                    Try `create_source_from_rows` instead."]
     pub async fn create_source_from_stream<S>(
         &self,
         filename: &str,
@@ -147,41 +403,226 @@ impl Client {
 
         // Post our request.
         let url = self.url("/source");
-        let client = reqwest::Client::new();
-        let res = client
-            .post(url.clone())
-            .multipart(form)
-            .send()
+        let client = &self.http;
+        let res = self
+            .send(client.post(url.clone()).multipart(form))
             .await
             .map_err(|e| Error::could_not_access_url(&url, e))?;
         self.handle_response_and_deserialize(&url, res).await
     }
 
+    /// Create a BigML data source using data from the specified reader, which
+    /// must return exactly `length` bytes. We stream the data over the network
+    /// without trying to load it all into memory at once.
+    ///
+    /// We need to know `length` in advance, because BigML does not support
+    /// `Transfer-Encoding: chunked` uploads.
+    pub async fn create_source_from_reader<R>(
+        &self,
+        reader: R,
+        filename: &str,
+        content_type: &str,
+        length: u64,
+    ) -> Result<Source>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        let mut progress_options = ProgressOptions::default();
+        self.create_source_from_reader_opt(
+            reader,
+            filename,
+            content_type,
+            length,
+            &mut progress_options,
+        )
+        .await
+    }
+
+    /// Create a BigML data source using data from the specified reader, and
+    /// report upload progress using `progress_options`. See
+    /// `create_source_from_reader` for details.
+    pub async fn create_source_from_reader_opt<'a, R>(
+        &self,
+        reader: R,
+        filename: &str,
+        content_type: &str,
+        length: u64,
+        progress_options: &mut ProgressOptions<'a, UploadProgress>,
+    ) -> Result<Source>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        debug!("uploading {} ({} bytes) from reader", filename, length);
+        let (progress_tx, progress_rx) = mpsc::unbounded();
+        let form =
+            source_upload_form(reader, filename, content_type, length, progress_tx)?;
+
+        // Post our request.
+        let url = self.url("/source");
+        let client = &self.http;
+        let request = client.post(url.clone()).multipart(form);
+        let res = send_with_progress(self.send(request), progress_rx, |progress| {
+            progress_options.report(progress)
+        })
+        .await?
+        .map_err(|e| Error::could_not_access_url(&url, e))?;
+        self.report_streamed_upload(length);
+        self.handle_response_and_deserialize(&url, res).await
+    }
+
+    /// Create a BigML data source from a stream of CSV rows, each of which is
+    /// a list of column values. The first row should normally contain column
+    /// names.
+    ///
+    /// BigML needs to know the length of an upload in advance, so we encode
+    /// rows to a temporary file as they arrive, and then upload the file.
+    /// This only keeps one row in memory at a time. The temporary file has a
+    /// random name, and is deleted when we finish or if we're dropped part way
+    /// through; `filename` is only used to name the upload.
+    #[cfg(feature = "csv")]
+    pub async fn create_source_from_rows<S, R>(
+        &self,
+        filename: &str,
+        rows: S,
+    ) -> Result<Source>
+    where
+        S: Stream<Item = Result<R>>,
+        R: IntoIterator,
+        R::Item: AsRef<[u8]>,
+    {
+        use std::io::SeekFrom;
+        use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+        let path =
+            env::temp_dir().join(format!("bigml-{:016x}.csv", rand::random::<u64>()));
+        debug!("encoding {} as CSV in {}", filename, path.display());
+        // Other users can write to the temporary directory, so never open a
+        // file or symlink which already exists.
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+            .map_err(|err| Error::could_not_write_file(&path, err))?;
+        let _temp_file = TempFile(path.clone());
+
+        let mut length = 0;
+        let mut buffer = vec![];
+        futures::pin_mut!(rows);
+        while let Some(row) = rows.try_next().await? {
+            buffer.clear();
+            let mut writer = csv::Writer::from_writer(&mut buffer);
+            writer.write_record(row).map_err(Error::other)?;
+            writer.flush()?;
+            drop(writer);
+            file.write_all(&buffer)
+                .await
+                .map_err(|err| Error::could_not_write_file(&path, err))?;
+            length += buffer.len() as u64;
+        }
+        file.flush()
+            .await
+            .map_err(|err| Error::could_not_write_file(&path, err))?;
+        file.seek(SeekFrom::Start(0))
+            .await
+            .map_err(|err| Error::could_not_read_file(&path, err))?;
+        self.create_source_from_reader(file, filename, "text/csv", length)
+            .await
+    }
+
     /// Create a BigML data source using data from the specified path.  We
     /// stream the data over the network without trying to load it all into
-    /// memory at once.
-    #[allow(clippy::needless_lifetimes, deprecated)]
-    #[deprecated = "This won't work until BigML fixes Transfer-Encoding: chunked"]
+    /// memory at once. If the upload fails because of a network error, we
+    /// retry it a couple of times.
     pub async fn create_source_from_path(&self, path: PathBuf) -> Result<Source> {
-        // Convert our path to a stream of `Bytes`.
-        let file = fs::File::open(&path)
+        let mut progress_options = ProgressOptions::default();
+        self.create_source_from_path_opt(
+            path,
+            &WaitOptions::default(),
+            &mut progress_options,
+        )
+        .await
+    }
+
+    /// Create a BigML data source using data from the specified path,
+    /// retrying failed uploads as specified by `wait_options` and reporting
+    /// upload progress using `progress_options`.
+    ///
+    /// BigML's API has no way to upload a source in chunks or to resume a
+    /// partial upload, so each retry opens `path` again and restarts the
+    /// upload from the beginning. For very large files over unreliable
+    /// connections, consider copying the file to cloud storage and creating
+    /// the source using [`resource::source::Args::remote_source`], so that BigML
+    /// fetches the data itself.
+    pub async fn create_source_from_path_opt<'a, 'b>(
+        &self,
+        path: PathBuf,
+        wait_options: &'a WaitOptions,
+        progress_options: &'a mut ProgressOptions<'b, UploadProgress>,
+    ) -> Result<Source> {
+        let length = fs::metadata(&path)
             .await
-            .map_err(|err| Error::could_not_read_file(&path, err))?;
-        let err_path = path.clone();
-        let stream = codec::FramedRead::new(file, codec::BytesCodec::new())
-            .map_ok(|bytes| bytes.freeze())
-            .map_err(move |err| Error::could_not_read_file(&err_path, err).compat());
+            .map_err(|err| Error::could_not_read_file(&path, err))?
+            .len();
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned());
+
+        // See `wait_opt` for why we need a lock here.
+        let progress_options = Arc::new(RwLock::new(progress_options));
 
-        // Create our source.
-        let filename = path.to_string_lossy();
-        self.create_source_from_stream(&filename, stream).await
+        let url = self.url("/source");
+        let client = &self.http;
+        wait(
+            &wait_options,
+            || -> Pin<Box<dyn Future<Output = WaitStatus<_, Error>> + Send>> {
+                let (path, filename, url, client) = (&path, &filename, &url, &client);
+                let progress_options = progress_options.clone();
+                async move {
+                    debug!("uploading {} ({} bytes)", path.display(), length);
+                    let file = try_with_permanent_failure!(fs::File::open(path)
+                        .await
+                        .map_err(|err| Error::could_not_read_file(path, err)));
+                    let (progress_tx, progress_rx) = mpsc::unbounded();
+                    let form = try_with_permanent_failure!(source_upload_form(
+                        file,
+                        filename,
+                        "application/octet-stream",
+                        length,
+                        progress_tx,
+                    ));
+                    let request = client.post(url.clone()).multipart(form);
+                    let res = try_with_permanent_failure!(
+                        send_with_progress(
+                            self.send(request),
+                            progress_rx,
+                            |progress| {
+                                progress_options.write().unwrap().report(progress)
+                            }
+                        )
+                        .await
+                    );
+                    // Network errors in the middle of an upload are generally
+                    // worth retrying.
+                    let res = try_with_temporary_failure!(res);
+                    self.report_streamed_upload(length);
+                    let source = try_wait!(
+                        self.handle_response_and_deserialize(url, res).await
+                    );
+                    WaitStatus::Finished(source)
+                }
+                .boxed()
+            },
+        )
+        .await
+        .map_err(|e| Error::could_not_access_url(&url, e))
     }
 
     /// Create a BigML data source using data from the specified path.  We
     /// stream the data over the network without trying to load it all into
     /// memory.
-    #[allow(clippy::needless_lifetimes, deprecated)]
-    #[deprecated = "This won't work until BigML fixes Transfer-Encoding: chunked"]
     pub async fn create_source_from_path_and_wait(
         &self,
         path: PathBuf,
@@ -194,121 +635,522 @@ impl Client {
             .await
     }
 
-    /// Update the specified `resource` using `update`. We do not return the
-    /// updated resource because of peculiarities with BigML's API, but you
-    /// can always use `Client::fetch` if you need the updated version.
+    /// Create a WhizzML script using the source code in the specified file,
+    /// and wait for BigML to finish compiling it. The script will be named
+    /// after the file. If you need to declare inputs, outputs or imports, use
+    /// `script::Args` and `Client::create_and_wait` instead.
+    pub async fn create_script_from_file(&self, path: PathBuf) -> Result<Script> {
+        let source_code = fs::read_to_string(&path)
+            .await
+            .map_err(|err| Error::could_not_read_file(&path, err))?;
+        let mut args = script::Args::new(source_code);
+        args.name = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned());
+        self.create_and_wait(&args).await
+    }
+
+    /// Create WhizzML libraries which may import each other, uploading them
+    /// in dependency order and waiting for each to be ready before creating
+    /// the libraries which import it. Returns the ID of each library, keyed
+    /// by its local name. Pass this to `script::Args::add_local_imports` to
+    /// create scripts which use these libraries.
+    pub async fn create_libraries(
+        &self,
+        libraries: Vec<LocalLibrary>,
+    ) -> Result<HashMap<String, Id<Library>>> {
+        let order = library::dependency_order(&libraries)?;
+        let mut libraries = libraries.into_iter().map(Some).collect::<Vec<_>>();
+        let mut ids = HashMap::<String, Id<Library>>::new();
+        for i in order {
+            let mut local = libraries[i].take().expect("library used twice");
+            for import in &local.imports {
+                local.args.imports.push(ids[import].clone());
+            }
+            debug!("creating WhizzML library {:?}", local.name);
+            let created = self.create_and_wait(&local.args).await?;
+            ids.insert(local.name, created.resource);
+        }
+        Ok(ids)
+    }
+
+    /// Update the specified `resource` using `update`, wait for BigML to
+    /// finish applying the update, and return the updated resource.
+    ///
+    /// We re-fetch the resource instead of using the PUT response, because
+    /// BigML's response often seems to be missing fields like `name` for
+    /// `Source`. It's not always a complete, valid resource.
     pub async fn update<'a, R: Resource + Updatable>(
         &'a self,
         resource: &'a Id<R>,
         update: &'a <R as Updatable>::Update,
-    ) -> Result<()> {
-        let url = self.url(resource.as_str());
-        debug!("PUT {}: {:?}", url, update);
-        let client = reqwest::Client::new();
-        let res = client
-            .request(reqwest::Method::PUT, url.clone())
-            .json(update)
-            .send()
+    ) -> Result<R> {
+        self.put_and_wait(resource, update).await
+    }
+
+    /// Add `tags` to `resource`, keeping any existing tags, and return the
+    /// updated resource.
+    ///
+    /// This fetches the current tags and then updates them, so concurrent
+    /// changes to the same resource's tags may be lost.
+    pub async fn add_tags<'a, R: Resource>(
+        &'a self,
+        resource: &'a Id<R>,
+        tags: &'a [&'a str],
+    ) -> Result<R> {
+        let mut new_tags = self.fetch(resource).await?.common().tags.clone();
+        for tag in tags {
+            if !new_tags.iter().any(|t| t == tag) {
+                new_tags.push((*tag).to_owned());
+            }
+        }
+        self.put_and_wait(resource, &serde_json::json!({ "tags": new_tags }))
+            .await
+    }
+
+    /// Remove `tags` from `resource`, and return the updated resource. Tags
+    /// which aren't present are ignored.
+    ///
+    /// This has the same limitations as `add_tags`.
+    pub async fn remove_tags<'a, R: Resource>(
+        &'a self,
+        resource: &'a Id<R>,
+        tags: &'a [&'a str],
+    ) -> Result<R> {
+        let mut new_tags = self.fetch(resource).await?.common().tags.clone();
+        new_tags.retain(|t| !tags.contains(&t.as_str()));
+        self.put_and_wait(resource, &serde_json::json!({ "tags": new_tags }))
+            .await
+    }
+
+    /// Send `update` to `resource` using PUT, and then wait for it.
+    async fn put_and_wait<'a, R, U>(
+        &'a self,
+        resource: &'a Id<R>,
+        update: &'a U,
+    ) -> Result<R>
+    where
+        R: Resource,
+        U: Serialize + fmt::Debug,
+    {
+        check_supported::<R>(R::SUPPORTS_UPDATE, "updating")?;
+        let url = self.url(&R::resource_path(resource));
+        debug!("PUT {}: {:?}", url_without_api_key(&url), update);
+        let client = &self.http;
+        let res = self
+            .send(
+                client
+                    .request(reqwest::Method::PUT, url.clone())
+                    .json(update),
+            )
             .await
             .map_err(|e| Error::could_not_access_url(&url, e))?;
-        // Parse our result as JSON, because it often seems to be missing
-        // fields like `name` for `Source`. It's not always a complete,
-        // valid resource.
         let _json: serde_json::Value =
             self.handle_response_and_deserialize(&url, res).await?;
-
-        Ok(())
+        self.wait(resource).await
     }
 
     /// Fetch an existing resource.
     pub async fn fetch<'a, R: Resource>(&'a self, resource: &'a Id<R>) -> Result<R> {
-        let url = self.url(resource.as_str());
-        let client = reqwest::Client::new();
-        let res = client
-            .get(url.clone())
-            .send()
+        let url = self.url(&R::resource_path(resource));
+        let client = &self.http;
+        let res = self
+            .send(client.get(url.clone()))
             .await
             .map_err(|e| Error::could_not_access_url(&url, e))?;
         self.handle_response_and_deserialize(&url, res).await
     }
 
-    /// Poll an existing resource, returning it once it's ready.
-    ///
-    /// If an underlying BigML error occurs, it can be accessed using
-    /// [`Error::original_bigml_error`].
-    pub async fn wait<'a, R: Resource>(&'a self, resource: &'a Id<R>) -> Result<R> {
-        let options = WaitOptions::default()
-            .backoff_type(BackoffType::Exponential)
-            .retry_interval(Duration::from_secs(10))
-            .allowed_errors(6);
-        let mut progress_options = ProgressOptions::default();
-        self.wait_opt(resource, &options, &mut progress_options)
+    /// Fetch rows from a sample, as specified by `options`.
+    pub async fn fetch_sample_rows(
+        &self,
+        sample: &Id<Sample>,
+        options: &sample::RowsOptions,
+    ) -> Result<sample::Rows> {
+        let mut url = self.url(sample.as_str());
+        url.query_pairs_mut().extend_pairs(options.query_pairs());
+        let client = &self.http;
+        let res = self
+            .send(client.get(url.clone()))
             .await
+            .map_err(|e| Error::could_not_access_url(&url, e))?;
+        let sample: Sample = self.handle_response_and_deserialize(&url, res).await?;
+        sample.sample.ok_or_else(|| {
+            Error::could_not_access_url(&url, format_err!("no rows in sample"))
+        })
     }
 
-    /// Poll an existing resource, returning it once it's ready, and honoring
-    /// wait and progress options.
-    ///
-    /// If an underlying BigML error occurs, it can be accessed using
-    /// [`Error::original_bigml_error`].
-    pub async fn wait_opt<'a, 'b, R: Resource>(
+    /// Fetch the status of our BigML account, including its subscription and
+    /// parallel task limits.
+    pub async fn status(&self) -> Result<AccountStatus> {
+        let url = self.url("/status");
+        debug!("GET {}", url_without_api_key(&url));
+        let client = &self.http;
+        let res = self
+            .send(client.get(url.clone()))
+            .await
+            .map_err(|e| Error::could_not_access_url(&url, e))?;
+        self.handle_response_and_deserialize(&url, res).await
+    }
+
+    /// Fetch a report of the data which BigML could not parse in `source`.
+    /// BigML only includes a sample of the bad rows and values, so check
+    /// `total_errors` to see how many there were in all.
+    pub async fn source_errors(
         &self,
-        resource: &'a Id<R>,
-        wait_options: &'a WaitOptions,
-        progress_options: &'a mut ProgressOptions<'b, R>,
-    ) -> Result<R> {
-        let url = self.url(resource.as_str());
-        debug!("Waiting for {}", url_without_api_key(&url));
+        source: &Id<Source>,
+    ) -> Result<source::SourceErrorReport> {
+        Ok(self.fetch(source).await?.status.error_report())
+    }
 
-        // We actually want to pass an `aync || { ... }` to `wait`, below, but
-        // async closures are going to stablize later than the rest of
-        // `async_await`. So we need to use `|| { async { ... } }`, which is a
-        // regular closure that returns a future. Except that this doesn't
-        // _quite_ work, because the `async { ... }` would contain a mutable
-        // reference `progress_options`, which can't be allowed to escape the
-        // outer `|| { ... }` block. So we cheat, and wrap our mutable state in
-        // a lock. When `async || { ... }` stablizes, we can just delete this
-        // line.
-        let progress_options = Arc::new(RwLock::new(progress_options));
+    /// Find all resources of type `R` with the specified tag. Fails if `tag`
+    /// contains a comma, which BigML can't search for.
+    pub async fn find_by_tag<R: Resource>(&self, tag: &str) -> Result<Vec<R>> {
+        if tag.contains(',') {
+            return Err(format_err!("cannot search for tag {:?} with a comma", tag));
+        }
+        let options = ListOptions::default().query(ListQuery::new().tag(tag));
+        self.list_stream::<R>(&options)
+            .try_filter(|resource| {
+                future::ready(resource.common().tags.iter().any(|t| t == tag))
+            })
+            .try_collect()
+            .await
+    }
 
-        wait(&wait_options, || {
-            let progress_options = progress_options.clone();
-            async move {
-                // TODO: Consider replacing `try_with_temporary_failure!`
-                // and `try_with_permanent_failure!` with `try_wait!` and
-                // appropriate error wrapping.
-                let res = try_with_temporary_failure!(self.fetch(resource).await);
-                if let Some(ref mut callback) =
-                    progress_options.write().unwrap().callback
-                {
-                    try_with_permanent_failure!(callback(&res));
-                }
-                if res.status().code().is_ready() {
-                    WaitStatus::Finished(res)
-                } else if res.status().code().is_err() {
-                    let message = res.status().message();
-                    let err = Error::WaitFailed {
-                        id: resource.to_string(),
-                        message: message.to_owned(),
-                    };
-                    // In general, we want to fail for good here, because even
-                    // if this error could be fixed, it's going to have to be
-                    // fixed at a higher level than this call to `wait_opt`.
-                    // Most likely, the underlying BigML resource will need to
-                    // be recreated from scratch and waited on again.
-                    //
-                    // DO NOT USE `Error::might_be_temporary` here, because we
-                    // know that `Error::WaitFailed` represents an error that
-                    // won't get fixed by waiting more.
-                    WaitStatus::FailedPermanently(err)
-                } else {
-                    WaitStatus::Waiting
-                }
-            }
+    /// Fetch many existing resources, running up to `concurrency` requests at
+    /// a time. Each resource is fetched using `Client::wait`, so temporary
+    /// errors will be retried, and we'll wait for any resources which aren't
+    /// ready yet. Results are returned in the same order as `resources`.
+    pub fn fetch_many<'a, R, I>(
+        &'a self,
+        resources: I,
+        concurrency: usize,
+    ) -> BoxStream<'a, Result<R>>
+    where
+        R: Resource,
+        I: IntoIterator<Item = Id<R>>,
+        I::IntoIter: Send + 'a,
+    {
+        stream::iter(resources)
+            .map(move |resource| async move { self.wait(&resource).await })
+            .buffered(concurrency.max(1))
             .boxed()
+    }
+
+    /// List existing resources of type `R`, returning a single page of
+    /// results as specified by `options`.
+    ///
+    /// BigML may omit some fields when listing resources, so this will only
+    /// work for resource types where those fields are optional.
+    pub async fn list<'a, R: Resource>(
+        &'a self,
+        options: &'a ListOptions,
+    ) -> Result<Vec<R>> {
+        let page: ListPage<R> = self
+            .list_page::<R, _>(options, options.first_offset())
+            .await?;
+        Ok(page.objects)
+    }
+
+    /// Like `list`, but only return the IDs of the resources. This works for
+    /// every resource type, no matter which fields BigML omits.
+    pub(crate) async fn list_ids<'a, R: Resource>(
+        &'a self,
+        options: &'a ListOptions,
+    ) -> Result<Vec<Id<R>>> {
+        let page: ListPage<ListedId<R>> = self
+            .list_page::<R, _>(options, options.first_offset())
+            .await?;
+        Ok(page
+            .objects
+            .into_iter()
+            .map(|listed| listed.resource)
+            .collect())
+    }
+
+    /// List all existing resources of type `R`, starting at the offset
+    /// specified in `options`, and transparently fetching more pages as
+    /// needed.
+    pub fn list_stream<'a, R: Resource>(
+        &'a self,
+        options: &'a ListOptions,
+    ) -> BoxStream<'a, Result<R>> {
+        stream::try_unfold(Some(options.first_offset()), move |offset| async move {
+            let offset = match offset {
+                Some(offset) => offset,
+                None => return Ok::<_, Error>(None),
+            };
+            let page: ListPage<R> = self.list_page::<R, _>(options, offset).await?;
+            let next_offset = page.meta.next_offset(page.objects.len());
+            Ok(Some((page.objects, next_offset)))
+        })
+        .map_ok(|objects| stream::iter(objects.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Like `list_stream`, but only return the IDs of the resources. This
+    /// works for every resource type, no matter which fields BigML omits.
+    pub(crate) fn list_id_stream<'a, R: Resource>(
+        &'a self,
+        options: &'a ListOptions,
+    ) -> BoxStream<'a, Result<Id<R>>> {
+        stream::try_unfold(Some(options.first_offset()), move |offset| async move {
+            let offset = match offset {
+                Some(offset) => offset,
+                None => return Ok::<_, Error>(None),
+            };
+            let page: ListPage<ListedId<R>> =
+                self.list_page::<R, _>(options, offset).await?;
+            let next_offset = page.meta.next_offset(page.objects.len());
+            Ok(Some((page.objects, next_offset)))
         })
+        .map_ok(|objects| {
+            stream::iter(objects.into_iter().map(|listed| Ok(listed.resource)))
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Fetch a single page of resources of type `R`, starting at `offset`,
+    /// and deserialize each one as a `T`.
+    async fn list_page<'a, R: Resource, T: DeserializeOwned>(
+        &'a self,
+        options: &'a ListOptions,
+        offset: u64,
+    ) -> Result<ListPage<T>> {
+        let mut url = self.url(R::list_path());
+        options.add_to_url(&mut url, offset);
+        debug!("GET {}", url_without_api_key(&url));
+        let client = &self.http;
+        let res = self
+            .send(client.get(url.clone()))
+            .await
+            .map_err(|e| Error::could_not_access_url(&url, e))?;
+        self.handle_response_and_deserialize(&url, res).await
+    }
+
+    /// Poll an existing resource, returning it once it's ready. We back off
+    /// exponentially, but never wait more than 5 minutes between polls.
+    ///
+    /// If an underlying BigML error occurs, it can be accessed using
+    /// [`Error::original_bigml_error`].
+    pub async fn wait<'a, R: Resource>(&'a self, resource: &'a Id<R>) -> Result<R> {
+        self.wait_until(resource, Readiness::Finished).await
+    }
+
+    /// Like `wait`, but stop waiting as soon as the resource is ready for
+    /// use according to `readiness`. For example, sources and datasets may
+    /// be used to create other resources once they have been
+    /// `Readiness::Summarized`.
+    pub async fn wait_until<'a, R: Resource>(
+        &'a self,
+        resource: &'a Id<R>,
+        readiness: Readiness,
+    ) -> Result<R> {
+        let options = WaitOptions::default()
+            .backoff_type(BackoffType::Exponential)
+            .retry_interval(Duration::from_secs(10))
+            .max_interval(Duration::from_secs(5 * 60))
+            .jitter(Percentage::new(10.0))
+            .allowed_errors(6);
+        let mut progress_options = ProgressOptions::default();
+        self.wait_until_opt(resource, readiness, &options, &mut progress_options)
+            .await
+    }
+
+    /// Poll an existing resource, returning it once it's ready, and honoring
+    /// wait and progress options. The callback in `progress_options` will be
+    /// called with a [`WaitEvent`] each time we poll the resource, each time
+    /// its status changes, and whenever we retry or time out.
+    ///
+    /// If an underlying BigML error occurs, it can be accessed using
+    /// [`Error::original_bigml_error`].
+    pub async fn wait_opt<'a, 'b, R: Resource>(
+        &self,
+        resource: &'a Id<R>,
+        wait_options: &'a WaitOptions,
+        progress_options: &'a mut ProgressOptions<'b, WaitEvent<R>>,
+    ) -> Result<R> {
+        self.wait_until_opt(
+            resource,
+            Readiness::Finished,
+            wait_options,
+            progress_options,
+        )
         .await
-        .map_err(|e| Error::could_not_access_url(&url, e))
+    }
+
+    /// Like `wait_opt`, but stop waiting as soon as the resource is ready for
+    /// use according to `readiness`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "bigml_wait",
+            skip(self, wait_options, progress_options),
+            fields(id = %resource),
+        )
+    )]
+    pub async fn wait_until_opt<'a, 'b, R: Resource>(
+        &self,
+        resource: &'a Id<R>,
+        readiness: Readiness,
+        wait_options: &'a WaitOptions,
+        progress_options: &'a mut ProgressOptions<'b, WaitEvent<R>>,
+    ) -> Result<R> {
+        let url = self.url(&R::resource_path(resource));
+        debug!("Waiting for {}", url_without_api_key(&url));
+
+        // We actually want to pass an `aync || { ... }` to `wait`, below, but
+        // async closures are going to stablize later than the rest of
+        // `async_await`. So we need to use `|| { async { ... } }`, which is a
+        // regular closure that returns a future. Except that this doesn't
+        // _quite_ work, because the `async { ... }` would contain a mutable
+        // reference `progress_options`, which can't be allowed to escape the
+        // outer `|| { ... }` block. So we cheat, and wrap our mutable state in
+        // a lock. When `async || { ... }` stablizes, we can just delete this
+        // line. We also keep track of the last status code we saw here.
+        let state = Arc::new(RwLock::new((progress_options, None)));
+        let observer_state = state.clone();
+        let start = Instant::now();
+
+        let observer = move |event: WaitLoopEvent<'_, Error>| {
+            let event = match event {
+                WaitLoopEvent::Retrying {
+                    error,
+                    errors_seen,
+                    allowed_errors,
+                } => WaitEvent::Retrying {
+                    error: error.to_string(),
+                    errors_seen,
+                    allowed_errors,
+                },
+                WaitLoopEvent::TimedOut => WaitEvent::TimedOut,
+            };
+            observer_state.write().unwrap().0.report(&event)
+        };
+
+        let result = wait_observed(
+            &wait_options,
+            || {
+                let state = state.clone();
+                async move {
+                    // TODO: Consider replacing `try_with_temporary_failure!`
+                    // and `try_with_permanent_failure!` with `try_wait!` and
+                    // appropriate error wrapping.
+                    let res = try_with_temporary_failure!(self.fetch(resource).await);
+                    let code = res.status().code();
+                    let res = {
+                        let mut state = state.write().unwrap();
+                        let (progress_options, last_code) = &mut *state;
+                        if *last_code != Some(code) {
+                            try_with_permanent_failure!(progress_options.report(
+                                &WaitEvent::StatusChanged {
+                                    old: *last_code,
+                                    new: code,
+                                }
+                            ));
+                            *last_code = Some(code);
+                        }
+                        // Lend `res` to our callback, then take it back.
+                        let event = WaitEvent::Polled(res);
+                        try_with_permanent_failure!(progress_options.report(&event));
+                        match event {
+                            WaitEvent::Polled(res) => res,
+                            _ => unreachable!("we just created a Polled event"),
+                        }
+                    };
+                    if res.is_ready_for(readiness) {
+                        WaitStatus::Finished(res)
+                    } else if res.status().code().is_err() {
+                        let err = Error::WaitFailed {
+                            id: resource.to_string(),
+                            dashboard_url: Box::new(self.dashboard_url(resource)),
+                            message: res.status().full_message(),
+                        };
+                        // In general, we want to fail for good here, because even
+                        // if this error could be fixed, it's going to have to be
+                        // fixed at a higher level than this call to `wait_opt`.
+                        // Most likely, the underlying BigML resource will need to
+                        // be recreated from scratch and waited on again.
+                        //
+                        // DO NOT USE `Error::is_transient` here, because we
+                        // know that `Error::WaitFailed` represents an error that
+                        // won't get fixed by waiting more.
+                        WaitStatus::FailedPermanently(err)
+                    } else {
+                        WaitStatus::Waiting
+                    }
+                }
+                .boxed()
+            },
+            observer,
+        )
+        .await;
+        if let Some(metrics) = &self.metrics {
+            let resource_type = R::api_name();
+            metrics.wait_finished(resource_type, start.elapsed(), result.is_ok());
+        }
+        result.map_err(|e| Error::could_not_access_url(&url, e))
+    }
+
+    /// Wait for the specified execution to finish, calling the callback in
+    /// `progress_options` with an `ExecutionProgress` value each time we
+    /// check on it. This can be used to display live progress information.
+    pub async fn wait_for_execution_opt<'a, 'b>(
+        &self,
+        execution: &'a Id<Execution>,
+        wait_options: &'a WaitOptions,
+        progress_options: &'a mut ProgressOptions<'b, ExecutionProgress>,
+    ) -> Result<Execution> {
+        let mut callback = |event: &WaitEvent<Execution>| match event {
+            WaitEvent::Polled(execution) => {
+                progress_options.report(&execution.progress())
+            }
+            _ => Ok(()),
+        };
+        let mut execution_progress_options =
+            ProgressOptions::default().callback(&mut callback);
+        self.wait_opt(execution, wait_options, &mut execution_progress_options)
+            .await
+    }
+
+    /// Poll the specified execution every `poll_interval`, returning each new
+    /// log entry as soon as we see it. The stream ends once the execution
+    /// has finished or failed, and all of its log entries have been returned.
+    ///
+    /// This can be used alongside `Client::wait` to tail the logs of a
+    /// long-running script.
+    pub fn stream_logs<'a>(
+        &'a self,
+        execution: &'a Id<Execution>,
+        poll_interval: Duration,
+    ) -> BoxStream<'a, Result<LogEntry>> {
+        // Our state is the number of log entries we've already returned and
+        // whether we've polled before, or `None` if the execution is done.
+        stream::try_unfold(Some((0, false)), move |state| async move {
+            let (seen, polled) = match state {
+                Some(state) => state,
+                None => return Ok::<_, Error>(None),
+            };
+            if polled {
+                sleep(poll_interval).await;
+            }
+            let execution = self.fetch(execution).await?;
+            let logs = execution.execution.logs;
+            let next_seen = seen.max(logs.len());
+            let new_logs = logs.into_iter().skip(seen).collect::<Vec<_>>();
+            let code = execution.status.code;
+            if code.is_ready() || code.is_err() {
+                Ok(Some((new_logs, None)))
+            } else {
+                Ok(Some((new_logs, Some((next_seen, true)))))
+            }
+        })
+        .map_ok(|logs| stream::iter(logs.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
     }
 
     /// Download a resource as a CSV file.  This only makes sense for
@@ -335,7 +1177,7 @@ impl Client {
     ) -> Result<reqwest::Response> {
         let url = self.url(&format!("{}/download", &resource));
         debug!("Downloading {}", url_without_api_key(&url));
-        let client = reqwest::Client::new();
+        let client = &self.http;
         wait(
             &options,
             || -> Pin<Box<dyn Future<Output = WaitStatus<_, Error>> + Send>> {
@@ -344,7 +1186,7 @@ impl Client {
                     // and `try_with_permanent_failure!` with `try_wait!` and
                     // appropriate error wrapping.
                     let res = try_with_temporary_failure!(
-                        client.get(url.clone()).send().await
+                        self.send(client.get(url.clone())).await
                     );
                     if res.status().is_success() {
                         // Sometimes "/download" returns JSON instead of CSV, which
@@ -375,23 +1217,349 @@ impl Client {
         .map_err(|e| Error::could_not_access_url(&url, e))
     }
 
-    /// Delete the specified resource.
-    pub async fn delete<'a, R: Resource>(&'a self, resource: &'a Id<R>) -> Result<()> {
-        let url = self.url(resource.as_str());
-        let client = reqwest::Client::new();
-        let res = client
-            .request(reqwest::Method::DELETE, url.clone())
-            .send()
+    /// Export `resource` in the specified `format`, writing it to `writer`.
+    /// Returns the number of bytes written.
+    ///
+    /// This does not wait for `resource` to be ready, so you will normally
+    /// want to call `wait` first.
+    pub async fn export_model<R, W>(
+        &self,
+        resource: &Id<R>,
+        format: ExportFormat,
+        writer: &mut W,
+    ) -> Result<u64>
+    where
+        R: Resource,
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let mut url = self.url(&R::resource_path(resource));
+        url.query_pairs_mut().extend_pairs(format.query_pairs());
+        debug!("Exporting {}", url_without_api_key(&url));
+        let res = self
+            .send(self.http.get(url.clone()))
             .await
             .map_err(|e| Error::could_not_access_url(&url, e))?;
-        if res.status().is_success() {
-            debug!("Deleted {}", &resource);
-            Ok(())
-        } else {
-            self.response_to_err(&url, res).await
+        if !res.status().is_success() {
+            return self.response_to_err(&url, res).await;
+        }
+        let mut reader = StreamReader::new(
+            self.metered_bytes_stream(res)
+                .map_err(io::Error::other)
+                .boxed(),
+        );
+        tokio::io::copy(&mut reader, writer)
+            .await
+            .map_err(|e| Error::could_not_access_url(&url, e))
+    }
+
+    /// Download the CSV output of a batch prediction as a stream of bytes,
+    /// waiting until it is available. (If you asked BigML to create an output
+    /// dataset, you can find it in `BatchPrediction::output_dataset_resource`
+    /// instead.)
+    pub async fn download_batch_prediction<'a>(
+        &'a self,
+        batch_prediction: &'a Id<BatchPrediction>,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let res = self.download(batch_prediction).await?;
+        let url = url_without_api_key(res.url());
+        Ok(self
+            .metered_bytes_stream(res)
+            .map_err(move |e| Error::could_not_access_url(&url, e))
+            .boxed())
+    }
+
+    /// Download a dataset as CSV, returning a stream of lines, waiting until
+    /// it is available. The first line contains the column names.
+    ///
+    /// This does not attempt to parse the CSV data, so quoted values
+    /// containing newlines will be split across multiple lines. If your data
+    /// may contain these, enable the `csv` feature and use
+    /// `download_dataset_records` instead.
+    pub async fn download_dataset<'a>(
+        &'a self,
+        dataset: &'a Id<Dataset>,
+    ) -> Result<BoxStream<'static, Result<String>>> {
+        let res = self.download(dataset).await?;
+        let url = url_without_api_key(res.url());
+        let reader = StreamReader::new(
+            self.metered_bytes_stream(res).map_err(io::Error::other),
+        );
+        Ok(codec::FramedRead::new(reader, codec::LinesCodec::new())
+            .map_err(move |e| Error::could_not_access_url(&url, Error::other(e)))
+            .boxed())
+    }
+
+    /// Download a dataset as CSV, returning a stream of parsed records,
+    /// waiting until it is available. The first record contains the column
+    /// names.
+    #[cfg(feature = "csv")]
+    pub async fn download_dataset_records<'a>(
+        &'a self,
+        dataset: &'a Id<Dataset>,
+    ) -> Result<BoxStream<'static, Result<csv::StringRecord>>> {
+        let res = self.download(dataset).await?;
+        let url = url_without_api_key(res.url());
+        let reader = StreamReader::new(
+            self.metered_bytes_stream(res).map_err(io::Error::other),
+        );
+        Ok(codec::FramedRead::new(reader, CsvRecordDecoder::new())
+            .map_err(move |e| Error::could_not_access_url(&url, Error::other(e)))
+            .boxed())
+    }
+
+    /// Cancel a running execution. BigML stops an execution when it is
+    /// deleted, so this deletes `execution`, retrying temporary errors. Any
+    /// resources which the execution has already created will be kept.
+    pub async fn cancel_execution(&self, execution: &Id<Execution>) -> Result<()> {
+        debug!("cancelling {}", execution);
+        self.delete(execution).await
+    }
+
+    /// Delete the specified resource, retrying temporary errors.
+    pub async fn delete<'a, R: Resource>(&'a self, resource: &'a Id<R>) -> Result<()> {
+        let options = WaitOptions::default()
+            .backoff_type(BackoffType::Exponential)
+            .retry_interval(Duration::from_secs(4))
+            .allowed_errors(4);
+        self.delete_opt(resource, &options).await
+    }
+
+    /// Delete the specified resource, retrying temporary errors as specified
+    /// by `options`.
+    pub async fn delete_opt<'a, R: Resource>(
+        &'a self,
+        resource: &'a Id<R>,
+        options: &'a WaitOptions,
+    ) -> Result<()> {
+        check_supported::<R>(R::SUPPORTS_DELETE, "deleting")?;
+        let url = self.url(&R::resource_path(resource));
+        let client = &self.http;
+        let mut attempts = 0;
+        wait(
+            &options,
+            || -> Pin<Box<dyn Future<Output = WaitStatus<_, Error>> + Send>> {
+                attempts += 1;
+                let is_retry = attempts > 1;
+                let client = &client;
+                let url = &url;
+                async move {
+                    // Network errors are generally worth retrying.
+                    let res = try_with_temporary_failure!(
+                        self.send(
+                            client.request(reqwest::Method::DELETE, url.clone())
+                        )
+                        .await
+                    );
+                    if res.status().is_success() {
+                        debug!("Deleted {}", &resource);
+                        WaitStatus::Finished(())
+                    } else if is_retry && res.status() == StatusCode::NOT_FOUND {
+                        // An earlier attempt probably succeeded, but we didn't
+                        // find out.
+                        debug!("Deleted {} (already gone)", &resource);
+                        WaitStatus::Finished(())
+                    } else {
+                        try_wait!(self.response_to_err(url, res).await);
+                        // The above always returns `Err` and bails out, so we
+                        // can't get here.
+                        unreachable!()
+                    }
+                }
+                .boxed()
+            },
+        )
+        .await
+        .map_err(|e| Error::could_not_access_url(&url, e))
+    }
+
+    /// Delete all the specified resources, running up to `concurrency`
+    /// deletions at a time. Stops at the first error that can't be fixed by
+    /// retrying.
+    pub async fn delete_all<'a, R, I>(
+        &'a self,
+        resources: I,
+        concurrency: usize,
+    ) -> Result<()>
+    where
+        R: Resource,
+        I: IntoIterator<Item = Id<R>> + 'a,
+    {
+        stream::iter(resources)
+            .map(|resource| async move { self.delete(&resource).await })
+            .buffer_unordered(concurrency.max(1))
+            .try_collect::<()>()
+            .await
+    }
+
+    /// Send `request`, honoring our rate limit. If BigML tells us that we're
+    /// making too many requests, wait as long as it asks (using the
+    /// `Retry-After` header if present) and try again, unless `request` has a
+    /// streaming body which can't be resent.
+    ///
+    /// If we're recording or replaying interactions, this is where it
+    /// happens.
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let request = request.build()?;
+        let method = request.method().clone();
+        let (endpoint, _) = endpoint_and_id(request.url());
+        let body_len = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| bytes.len() as u64);
+        let start = Instant::now();
+
+        #[cfg(feature = "tracing")]
+        let result = {
+            use tracing::Instrument;
+
+            let span = request_span(&request);
+            let result = self.send_request(request).instrument(span.clone()).await;
+            if let Ok(res) = &result {
+                span.record("status", res.status().as_u16());
+            }
+            span.record("elapsed_ms", start.elapsed().as_millis() as u64);
+            result
+        };
+
+        #[cfg(not(feature = "tracing"))]
+        let result = self.send_request(request).await;
+
+        if let Some(metrics) = &self.metrics {
+            if let Some(body_len) = body_len {
+                metrics.bytes_uploaded(body_len);
+            }
+            metrics.api_call(&ApiCall {
+                method,
+                endpoint,
+                status: result.as_ref().ok().map(|res| res.status()),
+                elapsed: start.elapsed(),
+            });
+        }
+        result
+    }
+
+    /// Send `request`. This does all the work for `send`.
+    async fn send_request(
+        &self,
+        mut request: reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        if let Some(vcr) = &self.vcr {
+            if vcr.is_replaying() {
+                return vcr.replay(&request);
+            }
+        }
+        let (endpoint, _) = endpoint_and_id(request.url());
+        let mut retries = 0;
+        let mut attempts = 0;
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.wait().await;
+            }
+            attempts += 1;
+            let method = request.method().clone();
+            let retry_request = request.try_clone();
+            let recorded_request =
+                self.vcr.as_ref().map(|_| RecordedRequest::new(&request));
+            let res = match self.http.execute(request).await {
+                Ok(res) => res,
+                Err(err) => match (retry_request, &self.retry_policy) {
+                    (Some(retry_request), Some(policy))
+                        if policy.retries_error(&method, attempts, &err) =>
+                    {
+                        let delay = policy.delay(attempts);
+                        warn!(
+                            "{} request failed, retrying in {:?} (attempt {}): {}",
+                            method, delay, attempts, err,
+                        );
+                        self.report_retry(&method, &endpoint);
+                        sleep(delay).await;
+                        request = retry_request;
+                        continue;
+                    }
+                    _ => return Err(err.into()),
+                },
+            };
+            let server_error_delay = self
+                .retry_policy
+                .as_ref()
+                .filter(|policy| {
+                    policy.retries_status(&method, attempts, res.status())
+                })
+                .map(|policy| policy.delay(attempts));
+            match retry_request {
+                Some(retry_request)
+                    if res.status() == StatusCode::TOO_MANY_REQUESTS
+                        && retries < MAX_RATE_LIMIT_RETRIES =>
+                {
+                    let delay = retry_after(&res).unwrap_or_else(|| {
+                        Duration::from_secs(MIN_RATE_LIMIT_DELAY_SECS << retries)
+                    });
+                    retries += 1;
+                    warn!(
+                        "rate limited by BigML, retrying in {:?} ({}/{})",
+                        delay, retries, MAX_RATE_LIMIT_RETRIES,
+                    );
+                    self.report_retry(&method, &endpoint);
+                    sleep(delay).await;
+                    request = retry_request;
+                }
+                Some(retry_request) if server_error_delay.is_some() => {
+                    let delay = server_error_delay.unwrap_or_default();
+                    warn!(
+                        "{} request failed with {}, retrying in {:?} (attempt {})",
+                        method,
+                        res.status(),
+                        delay,
+                        attempts,
+                    );
+                    self.report_retry(&method, &endpoint);
+                    sleep(delay).await;
+                    request = retry_request;
+                }
+                _ => {
+                    return match (&self.vcr, recorded_request) {
+                        (Some(vcr), Some(recorded_request)) => {
+                            vcr.record(recorded_request, res).await
+                        }
+                        _ => Ok(res),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Tell our metrics, if any, that we're retrying a request.
+    fn report_retry(&self, method: &reqwest::Method, endpoint: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.api_call_retried(method, endpoint);
+        }
+    }
+
+    /// Tell our metrics, if any, that we've uploaded `bytes` bytes using a
+    /// streaming request body, which `send` can't measure.
+    fn report_streamed_upload(&self, bytes: u64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.bytes_uploaded(bytes);
         }
     }
 
+    /// Return the body of `res` as a stream, reporting each chunk to our
+    /// metrics, if any.
+    fn metered_bytes_stream(
+        &self,
+        res: reqwest::Response,
+    ) -> impl Stream<Item = reqwest::Result<Bytes>> + Send + 'static {
+        let metrics = self.metrics.clone();
+        res.bytes_stream().inspect_ok(move |chunk| {
+            if let Some(metrics) = &metrics {
+                metrics.bytes_downloaded(chunk.len() as u64);
+            }
+        })
+    }
+
     /// Handle a response from the server, deserializing it as the
     /// appropriate type.
     async fn handle_response_and_deserialize<'a, T>(
@@ -408,6 +1576,9 @@ impl Client {
                 .await
                 .map_err(|e| Error::could_not_access_url(&url, e))?;
             debug!("Success body: {}", &body);
+            if let Some(metrics) = &self.metrics {
+                metrics.bytes_downloaded(body.len() as u64);
+            }
             let properties = serde_json::from_str(&body)
                 .map_err(|e| Error::could_not_access_url(&url, e))?;
             Ok(properties)
@@ -425,6 +1596,9 @@ impl Client {
         let status: StatusCode = res.status().to_owned();
         let body = res.text().await?;
         debug!("Error status: {} body: {}", status, body);
+        if let Ok(error_body) = serde_json::from_str::<ApiErrorBody>(&body) {
+            return Err(Error::api_error(&url, status, error_body.status));
+        }
         match status {
             StatusCode::PAYMENT_REQUIRED => Err(Error::PaymentRequired { url, body }),
             _ => Err(Error::UnexpectedHttpStatus { url, status, body }),
@@ -432,6 +1606,400 @@ impl Client {
     }
 }
 
+/// Options used to create a `Client`. This uses a "builder" pattern, so you
+/// can write:
+///
+/// ```
+/// use bigml::ClientBuilder;
+/// use std::time::Duration;
+///
+/// let client = ClientBuilder::new("username", "api_key")
+///     .domain("example.bigml.io")
+///     .timeout(Duration::from_secs(300))
+///     .user_agent_suffix("my-tool/1.0")
+///     .build()?;
+/// # Ok::<(), bigml::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ClientBuilder {
+    domain: String,
+    base_url: Option<Url>,
+    username: String,
+    api_key: String,
+    organization: Option<String>,
+    project: Option<Id<Project>>,
+    timeout: Option<Duration>,
+    proxy: Option<reqwest::Proxy>,
+    user_agent_suffix: Option<String>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    max_requests_per_second: Option<f64>,
+    retry_policy: Option<RetryPolicy>,
+    metrics: Option<Arc<dyn Metrics>>,
+    vcr: Option<(VcrMode, PathBuf)>,
+    cache: Option<PathBuf>,
+}
+
+impl ClientBuilder {
+    /// Create a new `ClientBuilder` that will connect to
+    /// `DEFAULT_BIGML_DOMAIN` with the specified credentials.
+    pub fn new<S1, S2>(username: S1, api_key: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        ClientBuilder {
+            domain: DEFAULT_BIGML_DOMAIN.to_owned(),
+            base_url: None,
+            username: username.into(),
+            api_key: api_key.into(),
+            organization: None,
+            project: None,
+            timeout: None,
+            proxy: None,
+            user_agent_suffix: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            max_requests_per_second: None,
+            retry_policy: None,
+            metrics: None,
+            vcr: None,
+            cache: None,
+        }
+    }
+
+    /// Create a new `ClientBuilder` using the environment variables
+    /// `BIGML_USERNAME`, `BIGML_API_KEY` and optionally `BIGML_DOMAIN` and
+    /// `BIGML_ORGANIZATION`.
+    pub fn from_env() -> Result<Self> {
+        let username = env::var("BIGML_USERNAME")
+            .map_err(|_| format_err!("must specify BIGML_USERNAME"))?;
+        let api_key = env::var("BIGML_API_KEY")
+            .map_err(|_| format_err!("must specify BIGML_API_KEY"))?;
+        let mut builder = Self::new(username, api_key);
+        if let Ok(domain) = env::var("BIGML_DOMAIN") {
+            builder = builder.domain(domain);
+        }
+        if let Ok(organization) = env::var("BIGML_ORGANIZATION") {
+            builder = builder.organization(organization);
+        }
+        Ok(builder)
+    }
+
+    /// Connect to `base_url` instead of `https://{domain}/`. This is mostly
+    /// useful for testing against a mock server.
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// The BigML domain to connect to, such as `"example.bigml.io"` for a
+    /// private deployment. Defaults to `DEFAULT_BIGML_DOMAIN`.
+    pub fn domain<S: Into<String>>(mut self, domain: S) -> Self {
+        self.domain = domain.into();
+        self
+    }
+
+    /// Access resources belonging to the specified BigML organization, such
+    /// as `"organization/123abc"`. This is required for organization users.
+    pub fn organization<S: Into<String>>(mut self, organization: S) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    /// Access resources in the specified project, using project-scoped
+    /// permissions. Organization users will normally want to set this, too.
+    pub fn project(mut self, project: Id<Project>) -> Self {
+        self.project = Some(project);
+        self
+    }
+
+    /// The maximum time to allow for each HTTP request, including reading the
+    /// response body. Be careful when uploading or downloading large files.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send all requests through `proxy`.
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Text to append to our `User-Agent` header, to help identify your
+    /// application.
+    pub fn user_agent_suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+        self.user_agent_suffix = Some(suffix.into());
+        self
+    }
+
+    /// The maximum number of idle connections to keep open to BigML.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Close idle connections to BigML after `timeout`.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Send TCP keepalive probes on open connections every `interval`. This
+    /// can help keep connections alive through NAT gateways and firewalls
+    /// during long waits.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Send at most `max_requests_per_second` requests to BigML, spacing them
+    /// out evenly. This can be used to avoid hitting BigML's rate limits when
+    /// making lots of requests in parallel.
+    pub fn rate_limit(mut self, max_requests_per_second: f64) -> Self {
+        self.max_requests_per_second = Some(max_requests_per_second);
+        self
+    }
+
+    /// Retry individual HTTP requests which fail because of network problems
+    /// or server errors, as specified by `policy`. By default, we don't retry
+    /// these requests here, though higher-level operations like `wait` may
+    /// retry them anyway.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Report metrics about our API calls to `metrics`. See
+    /// [`bigml::metrics`](crate::metrics) for details.
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Record every request we make and BigML's response to a JSON
+    /// "cassette" at `path`, which can later be passed to `replay_from`.
+    /// Usernames and API keys are not recorded. Response bodies are read
+    /// into memory before being returned, so this isn't suitable for very
+    /// large downloads.
+    pub fn record_to<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.vcr = Some((VcrMode::Record, path.into()));
+        self
+    }
+
+    /// Answer requests using the responses recorded at `path` by
+    /// `record_to`, without talking to BigML. Requests which weren't
+    /// recorded will fail. If the same request was recorded more than once,
+    /// the recorded responses are returned in order.
+    ///
+    /// Requests with streaming bodies, such as source uploads, are matched
+    /// by method and URL only.
+    pub fn replay_from<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.vcr = Some((VcrMode::Replay, path.into()));
+        self
+    }
+
+    /// Remember the resources created by `Client::create_cached` in a JSON
+    /// file at `path`, so that they can be reused by later runs. The file
+    /// will be created if it doesn't exist.
+    pub fn cache_to<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.cache = Some(path.into());
+        self
+    }
+
+    /// Create a new `Client` using these options.
+    pub fn build(self) -> Result<Client> {
+        let url = match self.base_url {
+            Some(base_url) => base_url,
+            None => {
+                let url_str = format!("https://{}/", self.domain);
+                url_str.parse().map_err(|err| {
+                    Error::could_not_parse_url_with_domain(&self.domain, err)
+                })?
+            }
+        };
+
+        let mut user_agent =
+            concat!("bigml-rs/", env!("CARGO_PKG_VERSION")).to_owned();
+        if let Some(suffix) = &self.user_agent_suffix {
+            user_agent.push(' ');
+            user_agent.push_str(suffix);
+        }
+        let mut http = reqwest::Client::builder().user_agent(user_agent);
+        #[cfg(feature = "rustls-tls")]
+        {
+            http = http.use_rustls_tls();
+        }
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            http = http.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            http = http.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            http = http.tcp_keepalive(interval);
+        }
+
+        let mut scope = vec![];
+        if let Some(organization) = self.organization {
+            scope.push(("organization", organization));
+        }
+        if let Some(project) = self.project {
+            scope.push(("project", project.to_string()));
+        }
+
+        Ok(Client {
+            url,
+            username: self.username,
+            api_key: self.api_key,
+            scope,
+            http: http.build()?,
+            rate_limiter: self
+                .max_requests_per_second
+                .map(|rate| Arc::new(RateLimiter::new(rate))),
+            retry_policy: self.retry_policy,
+            metrics: self.metrics,
+            vcr: self
+                .vcr
+                .map(|(mode, path)| Vcr::new(mode, path).map(Arc::new))
+                .transpose()?,
+            cache: self
+                .cache
+                .map(|path| ResourceCache::open(path).map(Arc::new))
+                .transpose()?,
+        })
+    }
+}
+
+/// A listed resource, of which we only care about the ID.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct ListedId<R: Resource> {
+    /// The ID of the resource.
+    resource: Id<R>,
+}
+
+/// Create a `tracing` span describing `request`. The `status` and
+/// `elapsed_ms` fields are filled in by `Client::send` once we're done.
+#[cfg(feature = "tracing")]
+fn request_span(request: &reqwest::Request) -> tracing::Span {
+    let (resource_type, id) = endpoint_and_id(request.url());
+    tracing::debug_span!(
+        "bigml_request",
+        method = %request.method(),
+        resource_type = resource_type.as_str(),
+        id = id.as_deref().unwrap_or_default(),
+        status = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    )
+}
+
+/// Return an error if `supported` is false, explaining that BigML doesn't
+/// support `operation` for resources of type `R`.
+fn check_supported<R: Resource>(supported: bool, operation: &str) -> Result<()> {
+    if supported {
+        Ok(())
+    } else {
+        Err(format_err!(
+            "BigML does not support {} {} resources",
+            operation,
+            R::api_name(),
+        ))
+    }
+}
+
+/// Parse the `Retry-After` header of `res`, if it contains a number of
+/// seconds.
+fn retry_after(res: &reqwest::Response) -> Option<Duration> {
+    let value = res.headers().get(reqwest::header::RETRY_AFTER)?;
+    let secs = value.to_str().ok()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// A temporary file, which we delete when dropped.
+#[cfg(feature = "csv")]
+struct TempFile(PathBuf);
+
+#[cfg(feature = "csv")]
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.0) {
+            warn!("could not remove {}: {}", self.0.display(), err);
+        }
+    }
+}
+
+/// Build a multipart form for uploading a source, streaming `length` bytes
+/// from `reader`. We send `UploadProgress` updates to `progress_tx` as the
+/// body is read.
+fn source_upload_form<R>(
+    reader: R,
+    filename: &str,
+    content_type: &str,
+    length: u64,
+    progress_tx: mpsc::UnboundedSender<UploadProgress>,
+) -> Result<multipart::Form>
+where
+    R: AsyncRead + Send + Sync + 'static,
+{
+    let mut bytes_sent = 0;
+    let stream = codec::FramedRead::new(reader, codec::BytesCodec::new()).map_ok(
+        move |bytes| {
+            bytes_sent += bytes.len() as u64;
+            // If nobody is listening, we don't care.
+            let _ = progress_tx.unbounded_send(UploadProgress {
+                bytes_sent,
+                total_bytes: length,
+            });
+            bytes.freeze()
+        },
+    );
+    let data = multipart::Part::stream_with_length(
+        reqwest::Body::wrap_stream(stream),
+        length,
+    )
+    .file_name(filename.to_owned())
+    .mime_str(content_type)?;
+    Ok(multipart::Form::new().part("file", data))
+}
+
+/// Wait for `send` to finish, passing any progress updates from `progress_rx` to
+/// `report` until we get a response. The outer `Result` contains any error
+/// returned by `report`, and the inner one contains any error sending the
+/// request.
+async fn send_with_progress<S, F>(
+    send: S,
+    mut progress_rx: mpsc::UnboundedReceiver<UploadProgress>,
+    mut report: F,
+) -> Result<Result<reqwest::Response>>
+where
+    S: Future<Output = Result<reqwest::Response>>,
+    F: FnMut(&UploadProgress) -> Result<()>,
+{
+    tokio::pin!(send);
+    let res = loop {
+        tokio::select! {
+            res = &mut send => break res,
+            Some(progress) = progress_rx.next() => report(&progress)?,
+        }
+    };
+    // Report anything we haven't seen yet, so that callers see the final
+    // progress update.
+    while let Some(Some(progress)) = progress_rx.next().now_or_never() {
+        report(&progress)?;
+    }
+    Ok(res)
+}
+
 #[test]
 fn client_url_is_sanitizable() {
     let client = Client::new("example", "secret").unwrap();
@@ -441,3 +2009,292 @@ fn client_url_is_sanitizable() {
     println!("err_str = {:?}", err_str);
     assert!(!err_str.contains("secret"));
 }
+
+#[test]
+fn client_url_includes_organization_and_project() {
+    let project: Id<Project> = "project/5e5d87f6e476845bd8000000".parse().unwrap();
+    let client = Client::builder("example", "secret")
+        .organization("organization/5e5d87f6e476845bd8000001")
+        .project(project)
+        .build()
+        .unwrap();
+    let url = client.url("/source");
+    assert_eq!(
+        url.query(),
+        Some(
+            "username=example&api_key=secret\
+             &organization=organization%2F5e5d87f6e476845bd8000001\
+             &project=project%2F5e5d87f6e476845bd8000000"
+        ),
+    );
+}
+
+#[test]
+fn client_url_honors_base_url() {
+    let base_url = Url::parse("http://127.0.0.1:8080/bigml/").unwrap();
+    let client = Client::new_with_base_url(base_url, "example", "secret").unwrap();
+    let url = client.url("source/5e5d87f6e476845bd8000000");
+    assert_eq!(url.path(), "/bigml/source/5e5d87f6e476845bd8000000");
+    assert_eq!(url.host_str(), Some("127.0.0.1"));
+}
+
+#[test]
+fn clients_can_be_cloned() {
+    let client = Client::builder("example", "secret")
+        .pool_max_idle_per_host(8)
+        .pool_idle_timeout(Duration::from_secs(30))
+        .tcp_keepalive(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let clone = client.clone();
+    assert_eq!(clone.url("/source"), client.url("/source"));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn tags_can_be_added() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut source = fixtures::source(&id);
+    source["tags"] = serde_json::json!(["old"]);
+    server.mount_fetch(&id, source.clone()).await;
+    server
+        .mount_json("PUT", &format!("/{}", id), 202, source)
+        .await;
+    let client = server.client().unwrap();
+    client.add_tags(&id, &["old", "new"]).await.unwrap();
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let put = requests
+        .iter()
+        .find(|req| req.method == wiremock::http::Method::Put)
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&put.body).unwrap();
+    assert_eq!(body, serde_json::json!({ "tags": ["old", "new"] }));
+}
+
+#[tokio::test]
+async fn idempotent_creation_requires_tag() {
+    use crate::resource::sample;
+
+    let client = Client::new("example", "secret").unwrap();
+    let dataset = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    let args = sample::Args::new(dataset);
+    let result = client
+        .create_opt_idempotent(&args, "key", &WaitOptions::default())
+        .await;
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn idempotent_creation_adopts_existing_resources() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Execution> = "execution/5e5d87f6e476845bd8000000".parse().unwrap();
+    server
+        .mount_json("POST", "/execution", 503, serde_json::json!({}))
+        .await;
+    // Listings may omit required fields, so only include the ID.
+    server
+        .mount_json(
+            "GET",
+            "/execution",
+            200,
+            serde_json::json!({
+                "meta": {
+                    "limit": 1,
+                    "next": null,
+                    "offset": 0,
+                    "previous": null,
+                    "total_count": 1,
+                },
+                "objects": [{ "resource": id.as_str() }],
+            }),
+        )
+        .await;
+    server.mount_fetch(&id, fixtures::execution(&id, &[])).await;
+    let client = server.client().unwrap();
+
+    let mut args = resource::execution::Args::default();
+    args.set_script("script/5e5d87f6e476845bd8000001".parse().unwrap());
+    args.tags.push("key".to_owned());
+    let options = WaitOptions::default()
+        .retry_interval(Duration::from_secs(0))
+        .allowed_errors(1);
+    let execution = client
+        .create_opt_idempotent(&args, "key", &options)
+        .await
+        .unwrap();
+    assert_eq!(execution.resource, id);
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let list = requests
+        .iter()
+        .find(|req| {
+            req.url.path() == "/execution" && req.method == wiremock::http::Method::Get
+        })
+        .unwrap();
+    assert!(list.url.query().unwrap().contains("tags__in=key"));
+}
+
+#[cfg(all(feature = "csv", feature = "mock"))]
+#[tokio::test]
+async fn sources_can_be_created_from_rows() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    server
+        .mount_json("POST", "/source", 201, fixtures::source(&id))
+        .await;
+    let client = server.client().unwrap();
+    let rows = stream::iter(vec![
+        Ok(vec!["name", "comment"]),
+        Ok(vec!["alice", "says \"hi\", twice"]),
+    ]);
+    let source = client
+        .create_source_from_rows("people.csv", rows)
+        .await
+        .unwrap();
+    assert_eq!(source.resource, id);
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains("filename=\"people.csv\""));
+    assert!(body.contains("name,comment\nalice,\"says \"\"hi\"\", twice\"\n"));
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn temp_files_are_removed_when_dropped() {
+    let path =
+        env::temp_dir().join(format!("bigml-temp-{:016x}.csv", rand::random::<u64>()));
+    std::fs::write(&path, "name\n").unwrap();
+    drop(TempFile(path.clone()));
+    assert!(!path.exists());
+}
+
+#[cfg(all(feature = "csv", feature = "mock"))]
+#[tokio::test]
+async fn row_upload_filenames_are_not_used_as_paths() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    server
+        .mount_json("POST", "/source", 201, fixtures::source(&id))
+        .await;
+    let client = server.client().unwrap();
+    let rows = stream::iter(vec![Ok(vec!["name"]), Ok(vec!["alice"])]);
+    let source = client
+        .create_source_from_rows("../missing/people.csv", rows)
+        .await
+        .unwrap();
+    assert_eq!(source.resource, id);
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains("filename=\"../missing/people.csv\""));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn execution_logs_can_be_streamed() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Execution> = "execution/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut execution = fixtures::execution(&id, &[]);
+    execution["execution"]["logs"] = serde_json::json!([
+        [
+            "info",
+            "2016-04-17T01:13:30.713Z",
+            0,
+            30,
+            "creating model 1"
+        ],
+        ["error", "2016-04-17T01:13:31.713Z", 0, 31, "model 1 failed"],
+    ]);
+    server.mount_fetch(&id, execution).await;
+    let client = server.client().unwrap();
+    let messages = client
+        .stream_logs(&id, Duration::from_millis(10))
+        .map_ok(|entry| entry.message)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(messages, &["creating model 1", "model 1 failed"]);
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn cached_resources_are_reused() {
+    use crate::mock::{fixtures, MockServer};
+    use crate::resource::dataset;
+
+    let server = MockServer::start().await;
+    let id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000001".parse().unwrap();
+    server.mount_create::<Dataset>(fixtures::dataset(&id)).await;
+    server.mount_fetch(&id, fixtures::dataset(&id)).await;
+    let path =
+        env::temp_dir().join(format!("bigml-cache-{}.json", rand::random::<u64>()));
+    let client = Client::builder("mock", "mock-api-key")
+        .base_url(server.inner().uri().parse().unwrap())
+        .cache_to(&path)
+        .build()
+        .unwrap();
+
+    let source = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    let args = dataset::Args::from_source(source);
+    assert_eq!(client.create_cached(&args).await.unwrap().resource, id);
+    assert_eq!(client.create_cached(&args).await.unwrap().resource, id);
+    let cached = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(cached.contains(id.as_str()));
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let creates = requests
+        .iter()
+        .filter(|req| req.method == wiremock::http::Method::Post)
+        .count();
+    assert_eq!(creates, 1);
+}
+
+#[test]
+fn dashboard_urls_use_client_domain() {
+    let client = Client::new_with_domain("au.bigml.io", "example", "secret").unwrap();
+    let id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    assert_eq!(
+        client.dashboard_url(&id).as_str(),
+        "https://au.bigml.com/dashboard/dataset/5e5d87f6e476845bd8000000",
+    );
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn wait_failures_link_to_client_dashboard() {
+    use crate::mock::{fixtures, MockServer};
+
+    let server = MockServer::start().await;
+    let id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut dataset = fixtures::dataset(&id);
+    dataset["status"] = serde_json::json!({ "code": -1, "message": "bad data" });
+    server.mount_fetch(&id, dataset).await;
+    let client = server.client().unwrap();
+
+    let err = client.wait(&id).await.unwrap_err();
+    let dashboard_url = client.dashboard_url(&id);
+    match err.original_bigml_error() {
+        Error::WaitFailed {
+            dashboard_url: url, ..
+        } => assert_eq!(**url, dashboard_url),
+        other => panic!("unexpected error: {}", other),
+    }
+    let message = err.original_bigml_error().to_string();
+    assert!(message.starts_with(dashboard_url.as_str()));
+    assert!(message.ends_with("failed (bad data)"));
+}
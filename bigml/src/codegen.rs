@@ -0,0 +1,378 @@
+//! Generating standalone Rust code from BigML models, similar to the
+//! "actionable models" which BigML can generate for other languages.
+//!
+//! The generated code has no dependencies, so it can be pasted into any
+//! Rust program which needs to make predictions without talking to BigML.
+//!
+//! ```
+//! # fn example(model: &bigml::resource::Model) -> bigml::Result<()> {
+//! let code = bigml::codegen::rust_predict_fn(model, "predict_species")?;
+//! std::fs::write("src/species.rs", code)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::errors::*;
+use crate::resource::model::{Condition, Model, Output, Predicate, TreeNode};
+use crate::resource::source::{Field, Optype};
+
+/// Generate a Rust function named `fn_name` which implements the decision
+/// tree in `model`.
+///
+/// The function takes one argument for each field used by the tree, in the
+/// order of their field IDs. Numeric fields are passed as `Option<f64>`, and
+/// all other fields as `Option<&str>`, with `None` for missing values. It
+/// returns `f64` for regression models, or `&'static str` for
+/// classification models.
+///
+/// Fails if the tree tests for terms in a text or items field, because the
+/// generated code can't split text into terms the way BigML does.
+pub fn rust_predict_fn(model: &Model, fn_name: &str) -> Result<String> {
+    let info = model
+        .model
+        .as_ref()
+        .ok_or_else(|| format_err!("{} has not finished training", model.resource))?;
+    let root = info
+        .root
+        .as_ref()
+        .ok_or_else(|| format_err!("{} has no decision tree", model.resource))?;
+    if root.uses_terms() {
+        return Err(format_err!(
+            "{} tests for terms in text or items fields, which generated code \
+             does not support",
+            model.resource,
+        ));
+    }
+
+    // Collect the fields used by our tree, and give each one a unique Rust
+    // identifier.
+    let mut field_ids = BTreeSet::new();
+    collect_fields(root, &mut field_ids);
+    let mut params = BTreeMap::new();
+    let mut used_names = HashSet::new();
+    for field_id in &field_ids {
+        let field = info.fields.get(field_id).ok_or_else(|| {
+            format_err!("{} has no metadata for field {}", model.resource, field_id)
+        })?;
+        let mut name = identifier(&field.name);
+        if !used_names.insert(name.clone()) {
+            name = format!("{}_{}", name, field_id);
+            used_names.insert(name.clone());
+        }
+        params.insert(field_id.to_owned(), Param { name, field });
+    }
+
+    let return_type = match root.output() {
+        Output::Numeric(_) => "f64",
+        Output::Categorical(_) => "&'static str",
+    };
+    let objective = model
+        .objective_field
+        .as_ref()
+        .and_then(|id| info.fields.get(id))
+        .map(|field| field.name.as_str())
+        .unwrap_or("the objective field");
+    let args = params
+        .values()
+        .map(|param| format!("{}: {}", param.name, param.rust_type()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "/// Predict {} using BigML model {}.\n",
+        objective, model.resource,
+    ));
+    code.push_str("///\n/// Generated by the `bigml` crate.\n");
+    code.push_str(&format!(
+        "pub fn {}({}) -> {} {{\n",
+        fn_name, args, return_type,
+    ));
+    node_expr(root, &params, 1, &mut code)?;
+    code.push_str("}\n");
+    Ok(code)
+}
+
+/// A parameter of our generated function.
+struct Param<'a> {
+    /// The Rust name of this parameter.
+    name: String,
+    /// The field this parameter represents.
+    field: &'a Field,
+}
+
+impl<'a> Param<'a> {
+    /// Is this a numeric parameter?
+    fn is_numeric(&self) -> bool {
+        self.field.optype == Optype::Numeric
+    }
+
+    /// The Rust type of this parameter.
+    fn rust_type(&self) -> &'static str {
+        if self.is_numeric() {
+            "Option<f64>"
+        } else {
+            "Option<&str>"
+        }
+    }
+}
+
+/// Record the ID of every field tested by `node` or its descendants.
+fn collect_fields(node: &TreeNode, field_ids: &mut BTreeSet<String>) {
+    if let Predicate::Condition(cond) = node.predicate() {
+        field_ids.insert(cond.field.clone());
+    }
+    for child in node.children() {
+        collect_fields(child, field_ids);
+    }
+}
+
+/// Write an expression which evaluates to the prediction made by `node`.
+///
+/// This matches `TreeNode::predict`: we follow the first child whose
+/// predicate matches, and we use the output of `node` if none do.
+fn node_expr(
+    node: &TreeNode,
+    params: &BTreeMap<String, Param<'_>>,
+    depth: usize,
+    code: &mut String,
+) -> Result<()> {
+    let indent = "    ".repeat(depth);
+    let mut conditions = 0;
+    for child in node.children() {
+        let test = match child.predicate() {
+            Predicate::True => "true".to_owned(),
+            Predicate::Condition(cond) => condition_expr(cond, params)?,
+        };
+        if conditions == 0 {
+            code.push_str(&format!("{}if {} {{\n", indent, test));
+        } else {
+            code.push_str(&format!("{}}} else if {} {{\n", indent, test));
+        }
+        node_expr(child, params, depth + 1, code)?;
+        conditions += 1;
+    }
+    let output = output_literal(node.output());
+    if conditions == 0 {
+        code.push_str(&format!("{}{}\n", indent, output));
+    } else {
+        code.push_str(&format!("{}}} else {{\n", indent));
+        code.push_str(&format!("{}    {}\n", indent, output));
+        code.push_str(&format!("{}}}\n", indent));
+    }
+    Ok(())
+}
+
+/// Return a Rust boolean expression equivalent to `cond`.
+fn condition_expr(
+    cond: &Condition,
+    params: &BTreeMap<String, Param<'_>>,
+) -> Result<String> {
+    use std::cmp::Ordering;
+
+    let param = params
+        .get(&cond.field)
+        .ok_or_else(|| format_err!("unknown field {} in decision tree", cond.field))?;
+    let name = &param.name;
+    let missing = cond.operator.matches_missing();
+
+    // Comparisons against `null` only test whether a value is present.
+    if cond.value.is_null() {
+        let when_missing = missing || cond.operator.compare(Ordering::Equal);
+        let when_present = !cond.operator.compare(Ordering::Equal);
+        return Ok(match (when_missing, when_present) {
+            (true, true) => "true".to_owned(),
+            (true, false) => format!("{}.is_none()", name),
+            (false, true) => format!("{}.is_some()", name),
+            (false, false) => "false".to_owned(),
+        });
+    }
+
+    if cond.term.is_some() {
+        return Err(format_err!("cannot generate code for term in {:?}", cond));
+    }
+
+    let op = operator_str(cond);
+    let test = if param.is_numeric() {
+        let value = cond
+            .value
+            .as_f64()
+            .ok_or_else(|| format_err!("expected number in {:?}", cond))?;
+        format!("v {} {:?}", op, value)
+    } else {
+        let value = match &cond.value {
+            serde_json::Value::String(s) => s.to_owned(),
+            other => other.to_string(),
+        };
+        format!("v {} {:?}", op, value)
+    };
+    Ok(format!("{}.map_or({}, |v| {})", name, missing, test))
+}
+
+/// The Rust comparison operator corresponding to the operator in `cond`.
+fn operator_str(cond: &Condition) -> &'static str {
+    use crate::resource::model::Operator::*;
+    match cond.operator {
+        LessThan | LessThanOrMissing => "<",
+        LessThanOrEqual | LessThanOrEqualOrMissing => "<=",
+        Equal | EqualOrMissing => "==",
+        NotEqual | NotEqualOrMissing => "!=",
+        GreaterThan | GreaterThanOrMissing => ">",
+        GreaterThanOrEqual | GreaterThanOrEqualOrMissing => ">=",
+    }
+}
+
+/// A Rust literal for `output`.
+fn output_literal(output: &Output) -> String {
+    match output {
+        Output::Numeric(n) => format!("{:?}", n),
+        Output::Categorical(s) => format!("{:?}", s),
+    }
+}
+
+/// Convert a field name into a valid Rust identifier.
+fn identifier(name: &str) -> String {
+    let mut ident = String::new();
+    for c in name.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_ascii_alphanumeric() {
+            ident.push(c);
+        } else if !ident.ends_with('_') {
+            ident.push('_');
+        }
+    }
+    let ident = ident.trim_matches('_').to_owned();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{}", ident)
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        format!("{}_", ident)
+    } else {
+        ident
+    }
+}
+
+/// Rust keywords which we can't use as identifiers.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn",
+    "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+    "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct",
+    "super", "trait", "true", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+#[test]
+fn field_names_become_identifiers() {
+    assert_eq!(identifier("Petal Length (cm)"), "petal_length_cm");
+    assert_eq!(identifier("2nd"), "field_2nd");
+    assert_eq!(identifier("type"), "type_");
+    assert_eq!(identifier("???"), "field_");
+}
+
+#[test]
+fn generate_decision_tree_fn() {
+    let model: Model = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "model/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The model has been created" },
+        "objective_field": "000004",
+        "model": {
+            "fields": {
+                "000002": { "name": "petal length", "optype": "numeric" },
+                "000004": { "name": "species", "optype": "categorical" },
+            },
+            "root": {
+                "id": 0,
+                "count": 150,
+                "output": "Iris-setosa",
+                "predicate": true,
+                "children": [
+                    {
+                        "id": 1,
+                        "count": 50,
+                        "output": "Iris-setosa",
+                        "predicate": {
+                            "field": "000002",
+                            "operator": "<=",
+                            "value": 2.45,
+                        },
+                    },
+                    {
+                        "id": 2,
+                        "count": 100,
+                        "output": "Iris-versicolor",
+                        "predicate": {
+                            "field": "000002",
+                            "operator": ">*",
+                            "value": 2.45,
+                        },
+                    },
+                ],
+            },
+        },
+    }))
+    .unwrap();
+    let code = rust_predict_fn(&model, "predict_species").unwrap();
+    assert_eq!(
+        code,
+        r#"/// Predict species using BigML model model/5e5d87f6e476845bd8000000.
+///
+/// Generated by the `bigml` crate.
+pub fn predict_species(petal_length: Option<f64>) -> &'static str {
+    if petal_length.map_or(false, |v| v <= 2.45) {
+        "Iris-setosa"
+    } else if petal_length.map_or(true, |v| v > 2.45) {
+        "Iris-versicolor"
+    } else {
+        "Iris-setosa"
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn term_conditions_are_rejected() {
+    let model: Model = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "reviews",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "model/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The model has been created" },
+        "objective_field": "000001",
+        "model": {
+            "fields": {
+                "000000": { "name": "review", "optype": "text" },
+                "000001": { "name": "sentiment", "optype": "categorical" },
+            },
+            "root": {
+                "id": 0,
+                "count": 2,
+                "output": "good",
+                "predicate": true,
+                "children": [{
+                    "id": 1,
+                    "count": 1,
+                    "output": "bad",
+                    "predicate": {
+                        "field": "000000",
+                        "operator": ">",
+                        "value": 0,
+                        "term": "cat",
+                    },
+                }],
+            },
+        },
+    }))
+    .unwrap();
+    assert!(rust_predict_fn(&model, "predict_sentiment").is_err());
+}
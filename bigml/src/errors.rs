@@ -1,11 +1,13 @@
-//! Declare our error types using error-chain.
+//! Our error types.
 
-// Unforunately, error_chain does not generate docs for all the types it
-// defines.
-#![allow(missing_docs, unused_doc_comments)]
+// We don't document every field of every error variant.
+#![allow(missing_docs)]
 
 use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
 use std::io;
 use std::path::PathBuf;
 use std::result;
@@ -14,26 +16,51 @@ use url::Url;
 /// A custom `Result`, for convenience.
 pub type Result<T, E = Error> = result::Result<T, E>;
 
+/// Create an [`Error::Other`] using a format string, like `format!`.
+macro_rules! format_err {
+    ($($arg:tt)*) => {
+        $crate::Error::other(format!($($arg)*))
+    };
+}
+
 /// A BigML-related error.
-#[derive(Debug, Fail)]
+///
+/// Errors which wrap another error include it in their message, and also
+/// return it from [`std::error::Error::source`].
+#[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum Error {
+    /// BigML's API returned an error, which we were able to parse.
+    ///
+    /// **WARNING:** Do not construct this directly, but use
+    /// `Error::api_error` to handle various URL sanitization and security
+    /// issues.
+    #[error("{status} for {url}: {kind} ({api_status})")]
+    Api {
+        url: Url,
+        status: StatusCode,
+        kind: BigMlApiError,
+        api_status: Box<ApiErrorStatus>,
+    },
+
     /// We could not access the specified URL.
     ///
     /// **WARNING:** Do not construct this directly, but use
     /// `Error::could_not_access_url` to handle various URL sanitization and
     /// security issues.
-    #[fail(display = "error accessing '{}': {}", url, error)]
+    #[error("error accessing '{url}': {error}")]
     CouldNotAccessUrl {
         url: Url,
-        /*#[cause]*/ error: Box<Error>,
+        #[source]
+        error: Box<Error>,
     },
 
     /// We could not get an output value from a WhizzML script.
-    #[fail(display = "could not get WhizzML output '{}': {}", name, error)]
+    #[error("could not get WhizzML output '{name}': {error}")]
     CouldNotGetOutput {
         name: String,
-        /*#[cause]*/ error: Box<Error>,
+        #[source]
+        error: Box<Error>,
     },
 
     /// We could not parse the specified URL.
@@ -41,37 +68,56 @@ pub enum Error {
     /// **WARNING:** This takes a domain, not the full URL that we couldn't
     /// parse, because we want to be careful to exclude credentials from error
     /// messages, and we can't remove credentials from a URL we can't parse.
-    #[fail(
-        display = "could not parse a URL with the domain '{}': {}",
-        domain, error
-    )]
+    #[error("could not parse a URL with the domain '{domain}': {error}")]
     CouldNotParseUrlWithDomain {
         domain: String,
-        /*#[cause]*/ error: Box<url::ParseError>,
+        #[source]
+        error: Box<url::ParseError>,
     },
 
     /// We could not read a file.
-    #[fail(display = "could not read file {:?}: {}", path, error)]
+    #[error("could not read file {path:?}: {error}")]
     CouldNotReadFile {
         path: PathBuf,
-        /*#[cause]*/ error: Box<Error>,
+        #[source]
+        error: Box<Error>,
+    },
+
+    /// We could not write a file.
+    #[error("could not write file {path:?}: {error}")]
+    CouldNotWriteFile {
+        path: PathBuf,
+        #[source]
+        error: Box<Error>,
     },
 
+    /// An error occurred while making an HTTP request.
+    #[error("{0}")]
+    Http(#[source] reqwest::Error),
+
+    /// An I/O error occurred.
+    #[error("{0}")]
+    Io(#[source] io::Error),
+
+    /// We could not serialize or deserialize JSON.
+    #[error("{0}")]
+    Json(#[source] serde_json::Error),
+
     /// We could not access an output value of a WhizzML script.
-    #[fail(display = "WhizzML output is not (yet?) available")]
+    #[error("WhizzML output is not (yet?) available")]
     OutputNotAvailable,
 
     /// BigML says that payment is required for this request, perhaps because
     /// we have hit plan limits.
-    #[fail(display = "BigML payment required for {} ({})", url, body)]
+    #[error("BigML payment required for {url} ({body})")]
     PaymentRequired { url: Url, body: String },
 
     /// A request timed out.
-    #[fail(display = "The operation timed out")]
+    #[error("The operation timed out")]
     Timeout,
 
     /// We received an unexpected HTTP status code.
-    #[fail(display = "{} for {} ({})", status, url, body)]
+    #[error("{status} for {url} ({body})")]
     UnexpectedHttpStatus {
         url: Url,
         status: StatusCode,
@@ -80,35 +126,58 @@ pub enum Error {
 
     /// We tried to create a BigML resource, but we failed. Display a dashboard
     /// URL to make it easy to look up the actual error.
-    #[fail(display = "https://bigml.com/dashboard/{} failed ({})", id, message)]
+    #[error("{dashboard_url} failed ({message})")]
     WaitFailed {
         /// The ID of the resource that we were waiting on.
         id: String,
+        /// The dashboard URL for this resource, on the BigML domain used by
+        /// the client which was waiting.
+        dashboard_url: Box<Url>,
         /// The message that was returned.
         message: String,
     },
 
     /// We found a type mismatch deserializing a BigML resource ID.
-    #[fail(
-        display = "Expected BigML resource ID starting with '{}', found '{}'",
-        expected, found
-    )]
+    #[error("Expected BigML resource ID starting with '{expected}', found '{found}'")]
     WrongResourceType {
         expected: &'static str,
         found: String,
     },
 
     /// Another kind of error occurred.
-    #[fail(display = "{}", error)]
-    Other { /*#[cause]*/ error: failure::Error, },
-
-    /// Add a hidden member for future API extensibility.
-    #[doc(none)]
-    #[fail(display = "This error should never have occurred")]
-    __Nonexclusive,
+    #[error("{error}")]
+    Other {
+        #[source]
+        error: Box<dyn error::Error + Send + Sync>,
+    },
 }
 
 impl Error {
+    /// Construct an `Error::Other` value from any other error, or from a
+    /// string.
+    pub fn other<E>(error: E) -> Error
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        Error::Other {
+            error: error.into(),
+        }
+    }
+
+    /// Construct an `Error::Api` value, taking care to sanitize the URL query.
+    pub(crate) fn api_error(
+        url: &Url,
+        status: StatusCode,
+        api_status: ApiErrorStatus,
+    ) -> Error {
+        Error::Api {
+            url: url_without_api_key(url),
+            status,
+            kind: BigMlApiError::from_codes(status, api_status.code),
+            api_status: Box::new(api_status),
+        }
+    }
+
     /// Construct an `Error::CouldNotAccessUrl` value, taking care to
     /// sanitize the URL query.
     pub(crate) fn could_not_access_url<E>(url: &Url, error: E) -> Error
@@ -156,18 +225,35 @@ impl Error {
         }
     }
 
-    /// Is this error likely to be temporary?
-    pub fn might_be_temporary(&self) -> bool {
+    pub(crate) fn could_not_write_file<P, E>(path: P, error: E) -> Error
+    where
+        P: Into<PathBuf>,
+        E: Into<Error>,
+    {
+        Error::CouldNotWriteFile {
+            path: path.into(),
+            error: Box::new(error.into()),
+        }
+    }
+
+    /// Is this error likely to be temporary? This is used by
+    /// [`try_wait!`](crate::try_wait) to decide whether to retry.
+    pub fn is_transient(&self) -> bool {
         match self {
-            Error::CouldNotAccessUrl { error, .. } => error.might_be_temporary(),
-            Error::CouldNotGetOutput { error, .. } => error.might_be_temporary(),
-            Error::CouldNotReadFile { error, .. } => error.might_be_temporary(),
+            Error::Api { kind, .. } => kind.might_be_temporary(),
+            Error::CouldNotAccessUrl { error, .. } => error.is_transient(),
+            Error::CouldNotGetOutput { error, .. } => error.is_transient(),
+            Error::CouldNotReadFile { error, .. } => error.is_transient(),
+            Error::CouldNotWriteFile { error, .. } => error.is_transient(),
+            // Network problems often go away if we try again.
+            Error::Http(error) => error.is_timeout() || error.is_connect(),
             // This error occurs when all your BigML "slots" are used and
             // they're suggesting you upgrade. Backing off may free up slots.
             Error::PaymentRequired { .. } => true,
             // Some HTTP status codes also tend to correspond to temporary errors.
             Error::UnexpectedHttpStatus { status, .. } => match *status {
                 StatusCode::INTERNAL_SERVER_ERROR // I'm not so sure about this one.
+                | StatusCode::TOO_MANY_REQUESTS
                 | StatusCode::SERVICE_UNAVAILABLE
                 | StatusCode::GATEWAY_TIMEOUT => true,
                 _ => false,
@@ -176,6 +262,12 @@ impl Error {
         }
     }
 
+    /// Is this error likely to be temporary?
+    #[deprecated(since = "0.7.1", note = "use `Error::is_transient` instead")]
+    pub fn might_be_temporary(&self) -> bool {
+        self.is_transient()
+    }
+
     /// Return the original `bigml::Error` that caused this error, without any
     /// wrapper errors.
     pub fn original_bigml_error(&self) -> &Error {
@@ -183,8 +275,13 @@ impl Error {
             Error::CouldNotAccessUrl { error, .. } => error.original_bigml_error(),
             Error::CouldNotGetOutput { error, .. } => error.original_bigml_error(),
             Error::CouldNotReadFile { error, .. } => error.original_bigml_error(),
+            Error::CouldNotWriteFile { error, .. } => error.original_bigml_error(),
 
-            Error::CouldNotParseUrlWithDomain { .. }
+            Error::Api { .. }
+            | Error::CouldNotParseUrlWithDomain { .. }
+            | Error::Http(_)
+            | Error::Io(_)
+            | Error::Json(_)
             | Error::Other { .. }
             | Error::OutputNotAvailable
             | Error::PaymentRequired { .. }
@@ -192,43 +289,143 @@ impl Error {
             | Error::UnexpectedHttpStatus { .. }
             | Error::WaitFailed { .. }
             | Error::WrongResourceType { .. } => self,
+        }
+    }
 
-            Error::__Nonexclusive => {
-                panic!("should never create Error::__Nonexclusive")
-            }
+    /// If this error was ultimately caused by a BigML API error, return what
+    /// kind of error it was.
+    pub fn api_error_kind(&self) -> Option<BigMlApiError> {
+        match self.original_bigml_error() {
+            Error::Api { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+}
+
+/// The kinds of errors reported by BigML's API. We classify errors using both
+/// the HTTP status and BigML's own error code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BigMlApiError {
+    /// Our request was malformed or failed validation.
+    BadRequest,
+    /// Our credentials were rejected.
+    Unauthorized,
+    /// All of our BigML "slots" are in use, or we have hit some other plan
+    /// limit.
+    PaymentRequired,
+    /// Our account does not have enough credits to perform this request.
+    InsufficientCredits,
+    /// We are not allowed to access this resource.
+    Forbidden,
+    /// The requested resource does not exist.
+    NotFound,
+    /// We are making too many requests.
+    RateLimited,
+    /// BigML had an internal problem.
+    ServerError,
+    /// Some other kind of error.
+    Other,
+}
+
+impl BigMlApiError {
+    /// Classify an error using an HTTP status and a BigML error code.
+    pub fn from_codes(status: StatusCode, bigml_code: i64) -> Self {
+        match bigml_code {
+            -1204 => return BigMlApiError::RateLimited,
+            -1206 => return BigMlApiError::InsufficientCredits,
+            _ => {}
+        }
+        match status {
+            StatusCode::BAD_REQUEST => BigMlApiError::BadRequest,
+            StatusCode::UNAUTHORIZED => BigMlApiError::Unauthorized,
+            StatusCode::PAYMENT_REQUIRED => BigMlApiError::PaymentRequired,
+            StatusCode::FORBIDDEN => BigMlApiError::Forbidden,
+            StatusCode::NOT_FOUND => BigMlApiError::NotFound,
+            StatusCode::TOO_MANY_REQUESTS => BigMlApiError::RateLimited,
+            status if status.is_server_error() => BigMlApiError::ServerError,
+            _ => BigMlApiError::Other,
+        }
+    }
+
+    /// Is this kind of error likely to be temporary?
+    pub fn might_be_temporary(self) -> bool {
+        match self {
+            // Backing off may free up slots.
+            BigMlApiError::PaymentRequired
+            | BigMlApiError::RateLimited
+            | BigMlApiError::ServerError => true,
+            _ => false,
         }
     }
 }
 
-impl From<failure::Error> for Error {
-    fn from(error: failure::Error) -> Error {
-        Error::Other { error }
+impl fmt::Display for BigMlApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            BigMlApiError::BadRequest => "bad request",
+            BigMlApiError::Unauthorized => "unauthorized",
+            BigMlApiError::PaymentRequired => "payment required",
+            BigMlApiError::InsufficientCredits => "insufficient credits",
+            BigMlApiError::Forbidden => "forbidden",
+            BigMlApiError::NotFound => "not found",
+            BigMlApiError::RateLimited => "rate limited",
+            BigMlApiError::ServerError => "server error",
+            BigMlApiError::Other => "other error",
+        };
+        description.fmt(f)
     }
 }
 
+/// The `status` section of an error response from BigML.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ApiErrorStatus {
+    /// BigML's error code, such as `-1204`.
+    pub code: i64,
+
+    /// Additional details about the error, such as which fields failed
+    /// validation. The format varies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<serde_json::Value>,
+
+    /// A human-readable description of the error.
+    #[serde(default)]
+    pub message: String,
+}
+
+impl fmt::Display for ApiErrorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)?;
+        if let Some(extra) = &self.extra {
+            write!(f, " {}", extra)?;
+        }
+        Ok(())
+    }
+}
+
+/// The body of an error response from BigML.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ApiErrorBody {
+    /// Details about the error.
+    pub(crate) status: ApiErrorStatus,
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Error {
-        Error::Other {
-            error: error.into(),
-        }
+        Error::Io(error)
     }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(error: reqwest::Error) -> Error {
-        // TODO: We might be able to classify more `serde` errors as temporary
-        // now.
-        Error::Other {
-            error: error.into(),
-        }
+        Error::Http(error)
     }
 }
 
 impl From<serde_json::Error> for Error {
     fn from(error: serde_json::Error) -> Error {
-        Error::Other {
-            error: error.into(),
-        }
+        Error::Json(error)
     }
 }
 
@@ -268,3 +465,29 @@ fn url_without_api_key_is_sanitized() {
         "https://www.example.com/foo?a=b&api_key=*****"
     );
 }
+
+#[test]
+fn api_errors_are_classified() {
+    let body: ApiErrorBody = serde_json::from_str(
+        r#"{"code": 429, "status": {"code": -1204, "message": "Too many requests"}}"#,
+    )
+    .unwrap();
+    let url = Url::parse("https://bigml.io/source?api_key=secret").unwrap();
+    let err = Error::api_error(&url, StatusCode::TOO_MANY_REQUESTS, body.status);
+    assert_eq!(err.api_error_kind(), Some(BigMlApiError::RateLimited));
+    assert!(err.is_transient());
+    assert!(!err.to_string().contains("secret"));
+
+    let wrapped = Error::could_not_access_url(&url, err);
+    let source = error::Error::source(&wrapped).unwrap();
+    assert!(source.to_string().starts_with("429 Too Many Requests"));
+
+    assert_eq!(
+        BigMlApiError::from_codes(StatusCode::PAYMENT_REQUIRED, -1206),
+        BigMlApiError::InsufficientCredits,
+    );
+    assert_eq!(
+        BigMlApiError::from_codes(StatusCode::BAD_REQUEST, -1200),
+        BigMlApiError::BadRequest,
+    );
+}
@@ -0,0 +1,45 @@
+//! Exporting models from BigML, so that they can be archived or used by
+//! other scoring systems. See `Client::export_model`.
+
+/// The format to use when exporting a model.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ExportFormat {
+    /// BigML's own JSON representation of the model. This is supported for
+    /// all resource types.
+    Json,
+    /// Predictive Model Markup Language. BigML only supports this for some
+    /// kinds of models, such as decision trees and logistic regressions.
+    Pmml,
+}
+
+impl ExportFormat {
+    /// The extra query parameters used to request this format.
+    pub(crate) fn query_pairs(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            ExportFormat::Json => &[],
+            ExportFormat::Pmml => &[("pmml", "yes")],
+        }
+    }
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn models_can_be_exported() {
+    use crate::{mock::MockServer, resource::Id, resource::Model};
+
+    let server = MockServer::start().await;
+    let id: Id<Model> = "model/5e5d87f6e476845bd8000000".parse().unwrap();
+    let json = serde_json::json!({ "resource": id.as_str() });
+    server
+        .mount_json("GET", &format!("/{}", id), 200, json.clone())
+        .await;
+    let client = server.client().unwrap();
+    let mut exported = vec![];
+    client
+        .export_model(&id, ExportFormat::Json, &mut exported)
+        .await
+        .unwrap();
+    let exported: serde_json::Value = serde_json::from_slice(&exported).unwrap();
+    assert_eq!(exported, json);
+}
@@ -22,7 +22,7 @@
 //! // Create a BigML client.
 //! let client = bigml::Client::new(username, api_key)?;
 //!
-//! // Create a source (actually, you should do this via S3 for now).
+//! // Create a source by uploading a local file.
 //! let source =
 //!     block_on(client.create_source_from_path_and_wait(path.to_owned()))?;
 //! println!("{:?}", source);
@@ -50,19 +50,44 @@
 #[macro_use]
 extern crate bigml_derive;
 
-#[macro_use]
-extern crate failure;
+// We log using `log` by default. If the `tracing` feature is enabled, the same
+// macros produce `tracing` events instead, which still fall back to `log` if
+// no `tracing` subscriber has been installed.
+#[cfg(not(feature = "tracing"))]
 #[macro_use]
 extern crate log;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 
-pub use client::{Client, DEFAULT_BIGML_DOMAIN};
+pub use client::{Client, ClientBuilder, DEFAULT_BIGML_DOMAIN};
 pub use errors::*;
-pub use progress::{ProgressCallback, ProgressOptions};
-pub use wait::WaitOptions;
+pub use export::ExportFormat;
+pub use list::ListOptions;
+pub use progress::{ProgressCallback, ProgressOptions, UploadProgress};
+pub use wait::{RetryPolicy, WaitOptions};
 
+#[macro_use]
+mod errors;
 #[macro_use]
 pub mod wait;
+pub mod account;
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
+pub mod cleanup;
 mod client;
-mod errors;
+pub mod codegen;
+pub mod export;
+pub mod list;
+pub mod metrics;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod pipeline;
 mod progress;
 pub mod resource;
+#[cfg(feature = "csv")]
+mod rows;
+pub mod validate;
+mod vcr;
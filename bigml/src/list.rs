@@ -0,0 +1,278 @@
+//! Support for listing existing BigML resources.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use url::Url;
+
+/// Options controlling which resources we list. This uses a "builder"
+/// pattern, so you can write:
+///
+/// ```
+/// use bigml::list::{ListOptions, ListQuery};
+///
+/// let options = ListOptions::default()
+///     .limit(100)
+///     .offset(200)
+///     .query(ListQuery::new().tag("nightly").order_by_desc("created"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ListOptions {
+    /// The maximum number of resources to return at once.
+    limit: Option<u64>,
+
+    /// The number of resources to skip.
+    offset: u64,
+
+    /// Filters and sort order to apply.
+    query: ListQuery,
+}
+
+impl ListOptions {
+    /// The maximum number of resources BigML should return in a single
+    /// response. BigML's default is 20. When using `Client::list_stream`, this
+    /// is the page size.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// How many resources should we skip before returning results?
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Only list resources matching `query`, in the order it specifies.
+    pub fn query(mut self, query: ListQuery) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Add our query parameters to `url`, starting at `offset`.
+    pub(crate) fn add_to_url(&self, url: &mut Url, offset: u64) {
+        let mut query = url.query_pairs_mut();
+        if !self.query.tags.is_empty() {
+            query.append_pair("tags__in", &self.query.tags.join(","));
+        }
+        for (key, value) in &self.query.filters {
+            query.append_pair(key, value);
+        }
+        if !self.query.order_by.is_empty() {
+            query.append_pair("order_by", &self.query.order_by.join(","));
+        }
+        if let Some(limit) = self.limit {
+            query.append_pair("limit", &limit.to_string());
+        }
+        query.append_pair("offset", &offset.to_string());
+    }
+
+    /// The offset of the first resource to return.
+    pub(crate) fn first_offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+/// Filters and sort order used when listing resources. This uses a "builder"
+/// pattern, so you can write:
+///
+/// ```
+/// use bigml::list::ListQuery;
+/// use chrono::{Duration, Utc};
+///
+/// let query = ListQuery::new()
+///     .name_contains("test")
+///     .created_after(Utc::now() - Duration::days(7))
+///     .order_by_desc("created");
+/// ```
+///
+/// See the [BigML docs][docs] for the available filters.
+///
+/// [docs]: https://bigml.com/api/requests#rq_filtering_and_paginating_resources
+#[derive(Clone, Debug, Default)]
+pub struct ListQuery {
+    /// Match resources with any of these tags. BigML only looks at the first
+    /// `tags__in` parameter, so we send them all in one list.
+    tags: Vec<String>,
+
+    /// Filters to apply, as `(field__lookup, value)` pairs.
+    filters: Vec<(String, String)>,
+
+    /// Fields to sort by, with a leading `-` for descending order.
+    order_by: Vec<String>,
+}
+
+impl ListQuery {
+    /// Create a new, empty query which matches all resources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an arbitrary BigML filter, such as `("size__gt", "1000")`.
+    pub fn filter<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.filters.push((key.into(), value.into()));
+        self
+    }
+
+    /// Only match resources with exactly this name.
+    pub fn name<S: Into<String>>(self, name: S) -> Self {
+        self.filter("name", name)
+    }
+
+    /// Only match resources whose name contains `text`.
+    pub fn name_contains<S: Into<String>>(self, text: S) -> Self {
+        self.filter("name__contains", text)
+    }
+
+    /// Only match resources with the specified tag. If called more than
+    /// once, resources with any of the specified tags will match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` contains a comma, which BigML would treat as a
+    /// separator between tags.
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        let tag = tag.into();
+        assert!(!tag.contains(','), "tag {:?} contains a comma", tag);
+        self.tags.push(tag);
+        self
+    }
+
+    /// Only match resources with at least one of the specified tags.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any tag contains a comma.
+    pub fn tags_in<S: AsRef<str>>(self, tags: &[S]) -> Self {
+        tags.iter().fold(self, |query, tag| query.tag(tag.as_ref()))
+    }
+
+    /// Only match resources created after `time`.
+    pub fn created_after(self, time: DateTime<Utc>) -> Self {
+        self.filter("created__gt", format_time(time))
+    }
+
+    /// Only match resources created before `time`.
+    pub fn created_before(self, time: DateTime<Utc>) -> Self {
+        self.filter("created__lt", format_time(time))
+    }
+
+    /// Sort results by `field`, in ascending order. May be called more than
+    /// once to sort by multiple fields.
+    pub fn order_by<S: Into<String>>(mut self, field: S) -> Self {
+        self.order_by.push(field.into());
+        self
+    }
+
+    /// Sort results by `field`, in descending order. May be called more than
+    /// once to sort by multiple fields.
+    pub fn order_by_desc<S: AsRef<str>>(mut self, field: S) -> Self {
+        self.order_by.push(format!("-{}", field.as_ref()));
+        self
+    }
+}
+
+/// Format a timestamp the way BigML expects it in filters (without a
+/// trailing `Z`).
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+/// A single page of resources returned by BigML.
+#[derive(Debug, Deserialize)]
+#[non_exhaustive]
+pub struct ListPage<R> {
+    /// Information about this page and the full result set.
+    pub meta: ListMeta,
+
+    /// The resources on this page.
+    pub objects: Vec<R>,
+}
+
+/// Pagination information for a list of resources.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct ListMeta {
+    /// The maximum number of resources on this page.
+    pub limit: u64,
+
+    /// A relative URL pointing to the next page, if any.
+    pub next: Option<String>,
+
+    /// The number of resources skipped before this page.
+    pub offset: u64,
+
+    /// A relative URL pointing to the previous page, if any.
+    pub previous: Option<String>,
+
+    /// The total number of resources matching our request.
+    pub total_count: u64,
+}
+
+impl ListMeta {
+    /// The offset of the next page, or `None` if this is the last page.
+    pub(crate) fn next_offset(&self, page_len: usize) -> Option<u64> {
+        let next_offset = self.offset + page_len as u64;
+        if page_len == 0 || next_offset >= self.total_count {
+            None
+        } else {
+            Some(next_offset)
+        }
+    }
+}
+
+#[test]
+fn next_offset_stops_at_end() {
+    let meta: ListMeta = serde_json::from_str(
+        r#"{"limit": 2, "next": null, "offset": 2, "previous": null, "total_count": 5}"#,
+    )
+    .unwrap();
+    assert_eq!(meta.next_offset(2), Some(4));
+    assert_eq!(meta.next_offset(0), None);
+    let meta = ListMeta { offset: 4, ..meta };
+    assert_eq!(meta.next_offset(1), None);
+}
+
+#[test]
+fn tags_are_sent_as_one_list() {
+    let query = ListQuery::new()
+        .tag("nightly")
+        .tags_in(&["weekly", "monthly"])
+        .name("report");
+    let options = ListOptions::default().query(query);
+    let mut url = Url::parse("https://bigml.io/source").unwrap();
+    options.add_to_url(&mut url, 0);
+    assert_eq!(
+        url.query(),
+        Some("tags__in=nightly%2Cweekly%2Cmonthly&name=report&offset=0"),
+    );
+}
+
+#[test]
+#[should_panic(expected = "contains a comma")]
+fn tags_cannot_contain_commas() {
+    ListQuery::new().tag("a,b");
+}
+
+#[test]
+fn query_parameters_added_to_url() {
+    let time = "2020-01-02T03:04:05Z".parse::<DateTime<Utc>>().unwrap();
+    let query = ListQuery::new()
+        .tag("nightly")
+        .created_after(time)
+        .order_by_desc("created")
+        .order_by("name");
+    let options = ListOptions::default().limit(10).query(query);
+    let mut url = Url::parse("https://bigml.io/source").unwrap();
+    options.add_to_url(&mut url, 20);
+    assert_eq!(
+        url.query(),
+        Some(
+            "tags__in=nightly&created__gt=2020-01-02T03%3A04%3A05\
+             &order_by=-created%2Cname&limit=10&offset=20"
+        ),
+    );
+}
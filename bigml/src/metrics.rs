@@ -0,0 +1,137 @@
+//! Hooks for collecting metrics about how a `Client` talks to BigML.
+//!
+//! To collect metrics, implement [`Metrics`] and pass it to
+//! [`ClientBuilder::metrics`](crate::ClientBuilder::metrics). Every method
+//! has a default implementation which does nothing, so you only need to
+//! implement the ones you care about.
+//!
+//! ```
+//! use bigml::{metrics::{ApiCall, Metrics}, ClientBuilder};
+//! use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+//!
+//! #[derive(Debug, Default)]
+//! struct CountRequests(AtomicU64);
+//!
+//! impl Metrics for CountRequests {
+//!     fn api_call(&self, _call: &ApiCall) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let client = ClientBuilder::new("username", "api_key")
+//!     .metrics(Arc::new(CountRequests::default()))
+//!     .build()?;
+//! # Ok::<(), bigml::Error>(())
+//! ```
+
+use reqwest::{Method, StatusCode};
+use std::{fmt, time::Duration};
+use url::Url;
+
+/// Receives metrics from a `Client`. These methods are called from inside
+/// API calls, so they should return quickly.
+pub trait Metrics: fmt::Debug + Send + Sync + 'static {
+    /// Called once we've finished an HTTP request to BigML, including any
+    /// retries made by `send`.
+    fn api_call(&self, _call: &ApiCall) {}
+
+    /// Called each time we retry an HTTP request, either because BigML asked
+    /// us to slow down, or because of our `RetryPolicy`.
+    fn api_call_retried(&self, _method: &Method, _endpoint: &str) {}
+
+    /// Called when `Client::wait_opt` or `Client::wait_until_opt` finishes
+    /// waiting for a resource of type `resource_type`, successfully or not.
+    fn wait_finished(
+        &self,
+        _resource_type: &str,
+        _elapsed: Duration,
+        _succeeded: bool,
+    ) {
+    }
+
+    /// Called when we've uploaded `bytes` bytes of request bodies.
+    fn bytes_uploaded(&self, _bytes: u64) {}
+
+    /// Called when we've downloaded `bytes` bytes of response bodies.
+    fn bytes_downloaded(&self, _bytes: u64) {}
+}
+
+/// Information about a single HTTP request to BigML.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ApiCall {
+    /// The HTTP method we used.
+    pub method: Method,
+
+    /// The endpoint we called, which is normally a resource type like
+    /// `"source"`.
+    pub endpoint: String,
+
+    /// The HTTP status of the response, or `None` if the request failed
+    /// without a response.
+    pub status: Option<StatusCode>,
+
+    /// How long the request took, including any retries.
+    pub elapsed: Duration,
+}
+
+/// Get the endpoint (normally a resource type) and resource ID, if any, from
+/// an API URL.
+pub(crate) fn endpoint_and_id(url: &Url) -> (String, Option<String>) {
+    let mut segments = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty());
+    let endpoint = segments.next().unwrap_or_default().to_owned();
+    let id = segments.next().map(|id| format!("{}/{}", endpoint, id));
+    (endpoint, id)
+}
+
+#[test]
+fn endpoints_are_extracted_from_urls() {
+    let url = Url::parse("https://bigml.io/dataset/1/download?api_key=x").unwrap();
+    assert_eq!(
+        endpoint_and_id(&url),
+        ("dataset".to_owned(), Some("dataset/1".to_owned())),
+    );
+    let url = Url::parse("https://bigml.io/source").unwrap();
+    assert_eq!(endpoint_and_id(&url), ("source".to_owned(), None));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn api_calls_are_reported() {
+    use crate::{
+        mock::{fixtures, MockServer},
+        resource::{Id, Source},
+        ClientBuilder,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<ApiCall>>);
+
+    impl Metrics for Recorder {
+        fn api_call(&self, call: &ApiCall) {
+            self.0.lock().unwrap().push(call.to_owned());
+        }
+    }
+
+    let server = MockServer::start().await;
+    let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    server.mount_fetch(&id, fixtures::source(&id)).await;
+    let recorder = Arc::new(Recorder::default());
+    let client = ClientBuilder::new("example", "secret")
+        .base_url(server.inner().uri().parse().unwrap())
+        .metrics(recorder.clone())
+        .build()
+        .unwrap();
+    client.fetch(&id).await.unwrap();
+
+    let calls = recorder.0.lock().unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].method, Method::GET);
+    assert_eq!(calls[0].endpoint, "source");
+    assert_eq!(calls[0].status, Some(StatusCode::OK));
+}
@@ -0,0 +1,110 @@
+//! Canned JSON for finished BigML resources, for use with
+//! [`MockServer`](super::MockServer).
+//!
+//! These contain just enough fields to be deserialized. You can add or
+//! replace fields using `serde_json::Value`'s indexing operators.
+
+use serde_json::{json, Value};
+
+use crate::resource::{Dataset, Execution, Id, Resource, Script, Source};
+
+/// Fields shared by all resources, for a resource which has finished being
+/// created.
+fn common<R: Resource>(id: &Id<R>) -> Value {
+    json!({
+        "resource": id.as_str(),
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "mock",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "status": {
+            "code": 5,
+            "message": "The resource has been created",
+        },
+    })
+}
+
+/// Merge `fields` into `value`, which must be a JSON object.
+fn with_fields(mut value: Value, fields: Value) -> Value {
+    if let (Some(value), Value::Object(fields)) = (value.as_object_mut(), fields) {
+        value.extend(fields);
+    }
+    value
+}
+
+/// A finished source.
+pub fn source(id: &Id<Source>) -> Value {
+    with_fields(
+        common(id),
+        json!({
+            "file_name": "mock.csv",
+            "md5": "d41d8cd98f00b204e9800998ecf8427e",
+            "size": 0,
+        }),
+    )
+}
+
+/// A finished dataset.
+pub fn dataset(id: &Id<Dataset>) -> Value {
+    with_fields(
+        common(id),
+        json!({
+            "columns": 0,
+            "excluded_fields": [],
+            "field_types": {},
+            "fields": {},
+            "input_fields": [],
+            "rows": 0,
+        }),
+    )
+}
+
+/// A finished WhizzML script.
+pub fn script(id: &Id<Script>) -> Value {
+    with_fields(
+        common(id),
+        json!({
+            "source_code": "",
+            "inputs": [],
+            "outputs": [],
+        }),
+    )
+}
+
+/// A finished execution, with the specified output values.
+pub fn execution(id: &Id<Execution>, outputs: &[(&str, Value)]) -> Value {
+    let outputs = outputs
+        .iter()
+        .map(|(name, value)| json!([name, value, ""]))
+        .collect::<Vec<_>>();
+    with_fields(
+        common(id),
+        json!({
+            "execution": {
+                "outputs": outputs,
+            },
+        }),
+    )
+}
+
+#[test]
+fn fixtures_can_be_deserialized() {
+    let source_id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    let source: Source = serde_json::from_value(source(&source_id)).unwrap();
+    assert_eq!(source.resource.as_str(), source_id.as_str());
+
+    let dataset_id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    let _: Dataset = serde_json::from_value(dataset(&dataset_id)).unwrap();
+
+    let script_id: Id<Script> = "script/5e5d87f6e476845bd8000000".parse().unwrap();
+    let _: Script = serde_json::from_value(script(&script_id)).unwrap();
+
+    let execution_id: Id<Execution> =
+        "execution/5e5d87f6e476845bd8000000".parse().unwrap();
+    let json = execution(&execution_id, &[("answer", json!(42))]);
+    let execution: Execution = serde_json::from_value(json).unwrap();
+    assert_eq!(execution.execution.get::<u64>("answer").unwrap(), 42);
+}
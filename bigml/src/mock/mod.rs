@@ -0,0 +1,95 @@
+//! A fake BigML server, for testing code which uses [`Client`] without
+//! talking to BigML or needing credentials.
+//!
+//! This is only available with the `mock` feature. It's built on
+//! [`wiremock`], which you can use directly for anything we don't support.
+//!
+//! ```
+//! use bigml::{mock::{fixtures, MockServer}, resource::{Id, Source}};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> bigml::Result<()> {
+//! let server = MockServer::start().await;
+//! let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse()?;
+//! server.mount_fetch(&id, fixtures::source(&id)).await;
+//!
+//! let client = server.client()?;
+//! let source = client.wait(&id).await?;
+//! assert_eq!(source.resource.as_str(), id.as_str());
+//! #   Ok(())
+//! # }
+//! ```
+
+use serde_json::Value;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::errors::*;
+use crate::resource::{Id, Resource};
+use crate::Client;
+
+pub mod fixtures;
+
+/// A fake BigML server running on a local port.
+pub struct MockServer {
+    /// The underlying `wiremock` server.
+    server: wiremock::MockServer,
+}
+
+impl MockServer {
+    /// Start a new server on a random local port.
+    pub async fn start() -> MockServer {
+        MockServer {
+            server: wiremock::MockServer::start().await,
+        }
+    }
+
+    /// The underlying `wiremock` server, which can be used to mount custom
+    /// mocks or to inspect the requests we've received.
+    pub fn inner(&self) -> &wiremock::MockServer {
+        &self.server
+    }
+
+    /// Create a `Client` which talks to this server, using fake credentials.
+    pub fn client(&self) -> Result<Client> {
+        let base_url = self.server.uri().parse().map_err(Error::other)?;
+        Client::new_with_base_url(base_url, "mock", "mock-api-key")
+    }
+
+    /// Respond to HTTP `method` requests for `path` with `status` and a
+    /// JSON `body`.
+    pub async fn mount_json(
+        &self,
+        method_name: &str,
+        path_str: &str,
+        status: u16,
+        body: Value,
+    ) {
+        Mock::given(method(method_name))
+            .and(path(format!("/{}", path_str.trim_start_matches('/'))))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Respond to requests to create a resource of type `R` with `body`.
+    pub async fn mount_create<R: Resource>(&self, body: Value) {
+        self.mount_json("POST", R::create_path(), 201, body).await;
+    }
+
+    /// Respond to requests to fetch `id` with `body`.
+    pub async fn mount_fetch<R: Resource>(&self, id: &Id<R>, body: Value) {
+        self.mount_json("GET", id.as_str(), 200, body).await;
+    }
+
+    /// Respond successfully to requests to delete `id`.
+    pub async fn mount_delete<R: Resource>(&self, id: &Id<R>) {
+        Mock::given(method("DELETE"))
+            .and(path(format!("/{}", id.as_str())))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&self.server)
+            .await;
+    }
+}
@@ -0,0 +1,346 @@
+//! Creating several dependent resources, such as a source, a dataset and a
+//! model.
+//!
+//! Each step of a `Pipeline` creates one resource, using arguments built
+//! from the resources created by the steps it depends on. Steps whose
+//! dependencies have finished run concurrently.
+//!
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> bigml::Result<()> {
+//! use bigml::{
+//!     pipeline::Pipeline,
+//!     resource::{dataset, model, source, Dataset, Model, Source},
+//!     Client,
+//! };
+//!
+//! let client = Client::new_from_env()?;
+//! let mut pipeline = Pipeline::new();
+//! pipeline
+//!     .step("source", &[], |_| {
+//!         Ok(source::Args::remote("https://static.bigml.com/csv/iris.csv"))
+//!     })
+//!     .step("dataset", &["source"], |outputs| {
+//!         Ok(dataset::Args::from_source(outputs.get::<Source>("source")?))
+//!     })
+//!     .step("model", &["dataset"], |outputs| {
+//!         Ok(model::Args::from_dataset(outputs.get::<Dataset>("dataset")?))
+//!     });
+//! let outputs = pipeline.run(&client).await?;
+//! println!("created {}", outputs.get::<Model>("model")?);
+//! # Ok(())
+//! # }
+//! ```
+
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::client::Client;
+use crate::errors::*;
+use crate::progress::ProgressOptions;
+use crate::resource::{self, Id, Resource};
+
+/// A type-erased function which starts creating the resource for a step.
+type StepFn =
+    dyn for<'a> Fn(&'a Client, &PipelineOutputs) -> StepFuture<'a> + Send + Sync;
+
+/// A future which creates the resource for a step, and returns its ID.
+type StepFuture<'a> = BoxFuture<'a, Result<String>>;
+
+/// A set of resource-creation steps, some of which may depend on others.
+#[derive(Default)]
+pub struct Pipeline {
+    /// Our steps, in the order they were declared.
+    steps: Vec<Step>,
+}
+
+/// A single step in a `Pipeline`.
+struct Step {
+    /// The name of this step.
+    name: String,
+    /// The names of the steps which must finish before this one starts.
+    deps: Vec<String>,
+    /// Start creating our resource.
+    run: Box<StepFn>,
+}
+
+impl Pipeline {
+    /// Create an empty pipeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step named `name`, which runs once all the steps in `deps`
+    /// have finished. It calls `make_args` with the resources created so
+    /// far, and creates a resource using the returned arguments.
+    pub fn step<A, F>(&mut self, name: &str, deps: &[&str], make_args: F) -> &mut Self
+    where
+        A: resource::Args + Send + Sync + 'static,
+        F: Fn(&PipelineOutputs) -> Result<A> + Send + Sync + 'static,
+    {
+        let run: Box<StepFn> = Box::new(move |client, outputs| {
+            let args = make_args(outputs);
+            async move {
+                let resource = client.create_and_wait(&args?).await?;
+                Ok(resource.id().to_string())
+            }
+            .boxed()
+        });
+        self.steps.push(Step {
+            name: name.to_owned(),
+            deps: deps.iter().map(|&dep| dep.to_owned()).collect(),
+            run,
+        });
+        self
+    }
+
+    /// Run all our steps, and return the resources they created.
+    pub async fn run(&self, client: &Client) -> Result<PipelineOutputs> {
+        self.run_opt(client, &mut ProgressOptions::default()).await
+    }
+
+    /// Run all our steps, calling the callback in `progress_options` each
+    /// time a step starts, finishes or fails.
+    ///
+    /// If any step fails, we return its error without waiting for other
+    /// running steps to finish.
+    pub async fn run_opt(
+        &self,
+        client: &Client,
+        progress_options: &mut ProgressOptions<'_, PipelineEvent>,
+    ) -> Result<PipelineOutputs> {
+        self.validate()?;
+        let mut outputs = PipelineOutputs::default();
+        let mut started = HashSet::new();
+        let mut running = FuturesUnordered::new();
+        loop {
+            // Start every step whose dependencies have all finished.
+            for step in &self.steps {
+                if started.contains(step.name.as_str())
+                    || !step.deps.iter().all(|dep| outputs.ids.contains_key(dep))
+                {
+                    continue;
+                }
+                started.insert(step.name.as_str());
+                debug!("starting pipeline step {:?}", step.name);
+                progress_options.report(&PipelineEvent::Started {
+                    step: step.name.clone(),
+                })?;
+                let name = step.name.as_str();
+                let fut = (step.run)(client, &outputs);
+                running.push(fut.map(move |result| (name, result)));
+            }
+
+            // Wait for the next step to finish. `validate` guarantees that
+            // we'll eventually run every step.
+            match running.next().await {
+                Some((name, Ok(id))) => {
+                    progress_options.report(&PipelineEvent::Finished {
+                        step: name.to_owned(),
+                        id: id.clone(),
+                    })?;
+                    outputs.ids.insert(name.to_owned(), id);
+                }
+                Some((name, Err(err))) => {
+                    error!("pipeline step {:?} failed: {}", name, err);
+                    progress_options.report(&PipelineEvent::Failed {
+                        step: name.to_owned(),
+                        error: err.to_string(),
+                    })?;
+                    return Err(err);
+                }
+                None => return Ok(outputs),
+            }
+        }
+    }
+
+    /// Make sure our step names are unique, that all dependencies exist,
+    /// and that there are no cycles.
+    fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for step in &self.steps {
+            if !names.insert(step.name.as_str()) {
+                return Err(format_err!("duplicate pipeline step {:?}", step.name));
+            }
+        }
+        for step in &self.steps {
+            for dep in &step.deps {
+                if !names.contains(dep.as_str()) {
+                    return Err(format_err!(
+                        "pipeline step {:?} depends on unknown step {:?}",
+                        step.name,
+                        dep,
+                    ));
+                }
+            }
+        }
+
+        // Repeatedly remove steps whose dependencies have been removed. If we
+        // get stuck, the remaining steps contain a cycle.
+        let mut done = HashSet::new();
+        while done.len() < self.steps.len() {
+            let ready = self
+                .steps
+                .iter()
+                .filter(|step| !done.contains(step.name.as_str()))
+                .filter(|step| step.deps.iter().all(|dep| done.contains(dep.as_str())))
+                .map(|step| step.name.as_str())
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                let mut stuck = self
+                    .steps
+                    .iter()
+                    .map(|step| step.name.as_str())
+                    .filter(|name| !done.contains(name))
+                    .collect::<Vec<_>>();
+                stuck.sort_unstable();
+                return Err(format_err!(
+                    "pipeline steps contain a cycle: {}",
+                    stuck.join(", "),
+                ));
+            }
+            done.extend(ready);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| (&step.name, &step.deps))
+            .collect::<Vec<_>>();
+        f.debug_struct("Pipeline").field("steps", &steps).finish()
+    }
+}
+
+/// The resources created by a `Pipeline`, keyed by step name.
+#[derive(Clone, Debug, Default)]
+pub struct PipelineOutputs {
+    /// The ID of the resource created by each finished step.
+    ids: HashMap<String, String>,
+}
+
+impl PipelineOutputs {
+    /// Get the ID of the resource created by `step`.
+    pub fn get<R: Resource>(&self, step: &str) -> Result<Id<R>> {
+        self.ids
+            .get(step)
+            .ok_or_else(|| format_err!("pipeline step {:?} has not finished", step))?
+            .parse()
+    }
+
+    /// Iterate over the finished steps and the IDs of the resources they
+    /// created.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.ids
+            .iter()
+            .map(|(step, id)| (step.as_str(), id.as_str()))
+    }
+}
+
+/// Something which happened while running a `Pipeline`. These are passed to
+/// the callback in `ProgressOptions`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum PipelineEvent {
+    /// We started creating the resource for `step`.
+    Started {
+        /// The name of the step.
+        step: String,
+    },
+
+    /// `step` created the resource `id`, which is ready to use.
+    Finished {
+        /// The name of the step.
+        step: String,
+        /// The ID of the resource we created.
+        id: String,
+    },
+
+    /// `step` failed, so we're stopping the pipeline.
+    Failed {
+        /// The name of the step.
+        step: String,
+        /// A description of the error.
+        error: String,
+    },
+}
+
+#[test]
+fn pipelines_reject_cycles_and_unknown_steps() {
+    use crate::resource::{dataset, Dataset};
+
+    let derived = |outputs: &PipelineOutputs| {
+        Ok(dataset::Args::from_dataset(outputs.get::<Dataset>("a")?))
+    };
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .step("a", &["b"], derived)
+        .step("b", &["a"], derived);
+    let err = pipeline.validate().unwrap_err();
+    assert_eq!(err.to_string(), "pipeline steps contain a cycle: a, b");
+
+    let mut pipeline = Pipeline::new();
+    pipeline.step("a", &["missing"], derived);
+    assert!(pipeline.validate().is_err());
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn pipelines_pass_outputs_to_later_steps() {
+    use crate::mock::{fixtures, MockServer};
+    use crate::resource::{dataset, source, Dataset, Source};
+
+    let server = MockServer::start().await;
+    let source_id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    let dataset_id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000001".parse().unwrap();
+    server
+        .mount_create::<Source>(fixtures::source(&source_id))
+        .await;
+    server
+        .mount_fetch(&source_id, fixtures::source(&source_id))
+        .await;
+    server
+        .mount_create::<Dataset>(fixtures::dataset(&dataset_id))
+        .await;
+    server
+        .mount_fetch(&dataset_id, fixtures::dataset(&dataset_id))
+        .await;
+    let client = server.client().unwrap();
+
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .step("dataset", &["source"], |outputs| {
+            Ok(dataset::Args::from_source(outputs.get::<Source>("source")?))
+        })
+        .step("source", &[], |_| {
+            Ok(source::Args::remote("https://example.com/data.csv"))
+        });
+    let mut events = vec![];
+    let mut callback = |event: &PipelineEvent| {
+        if let PipelineEvent::Finished { step, .. } = event {
+            events.push(step.clone());
+        }
+        Ok(())
+    };
+    let mut progress_options = ProgressOptions::default().callback(&mut callback);
+    let outputs = pipeline
+        .run_opt(&client, &mut progress_options)
+        .await
+        .unwrap();
+    assert_eq!(outputs.get::<Dataset>("dataset").unwrap(), dataset_id);
+    assert_eq!(events, &["source", "dataset"]);
+
+    let requests = server.inner().received_requests().await.unwrap();
+    let create_dataset = requests
+        .iter()
+        .find(|req| req.url.path() == "/dataset")
+        .unwrap();
+    let body: serde_json::Value =
+        serde_json::from_slice(&create_dataset.body).unwrap();
+    assert_eq!(body["source"], source_id.as_str());
+}
@@ -19,8 +19,30 @@ impl<'a, T: 'static> ProgressOptions<'a, T> {
     }
 }
 
+impl<'a, T: 'static> ProgressOptions<'a, T> {
+    /// Pass `value` to our callback, if we have one.
+    pub(crate) fn report(&mut self, value: &T) -> Result<()> {
+        match self.callback {
+            Some(ref mut callback) => callback(value),
+            None => Ok(()),
+        }
+    }
+}
+
 impl<'a, T: 'static> Default for ProgressOptions<'a, T> {
     fn default() -> Self {
         ProgressOptions { callback: None }
     }
 }
+
+/// How much of an upload has been sent so far.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct UploadProgress {
+    /// The number of bytes sent so far. This is approximate, because it
+    /// counts bytes as they're handed to the network layer.
+    pub bytes_sent: u64,
+
+    /// The total number of bytes we plan to send.
+    pub total_bytes: u64,
+}
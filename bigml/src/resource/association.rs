@@ -0,0 +1,216 @@
+//! https://bigml.com/api/associations
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::source::Field;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+
+/// A set of association rules discovered in a dataset.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "association"]
+#[non_exhaustive]
+pub struct Association {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Association>,
+
+    /// The current status of this association.
+    pub status: GenericStatus,
+
+    /// The dataset used to create this association.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The rules that BigML found. Will be missing while the association is
+    /// being created.
+    pub associations: Option<AssociationInfo>,
+}
+
+/// The rules discovered by an association.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AssociationInfo {
+    /// Metadata describing each field used by this association, keyed by
+    /// BigML field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+
+    /// The items which appear in `rules`. Rules refer to items by their index
+    /// in this list.
+    #[serde(default)]
+    pub items: Vec<Item>,
+
+    /// The maximum number of rules requested.
+    pub max_k: Option<u64>,
+
+    /// The rules that were found.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+
+    /// The measure used to rank rules.
+    pub search_strategy: Option<SearchStrategy>,
+}
+
+impl AssociationInfo {
+    /// The items on the left-hand side of `rule`.
+    pub fn antecedent(&self, rule: &Rule) -> Vec<&Item> {
+        self.lookup_items(&rule.lhs)
+    }
+
+    /// The items on the right-hand side of `rule`.
+    pub fn consequent(&self, rule: &Rule) -> Vec<&Item> {
+        self.lookup_items(&rule.rhs)
+    }
+
+    /// Look up items by index, skipping any which are out of range.
+    fn lookup_items(&self, indices: &[usize]) -> Vec<&Item> {
+        indices.iter().filter_map(|&i| self.items.get(i)).collect()
+    }
+}
+
+/// A single item, typically a field having a specific value or range of
+/// values.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Item {
+    /// The number of instances containing this item.
+    pub count: u64,
+
+    /// A description of this item.
+    pub description: Option<String>,
+
+    /// The BigML ID of the field this item refers to.
+    pub field_id: String,
+
+    /// The name of this item, such as a category or a numeric range.
+    pub name: String,
+}
+
+/// An association rule of the form "`lhs` implies `rhs`".
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Rule {
+    /// The ID of this rule.
+    pub id: String,
+
+    /// How often `rhs` appears in instances containing `lhs`.
+    pub confidence: f64,
+
+    /// The difference between the observed support and the support expected
+    /// if `lhs` and `rhs` were independent.
+    pub leverage: f64,
+
+    /// Indices into `AssociationInfo::items` of the antecedent.
+    pub lhs: Vec<usize>,
+
+    /// How many times more often `lhs` and `rhs` occur together than we would
+    /// expect if they were independent.
+    pub lift: f64,
+
+    /// The probability of seeing this rule by chance.
+    pub p_value: Option<f64>,
+
+    /// Indices into `AssociationInfo::items` of the consequent.
+    pub rhs: Vec<usize>,
+
+    /// The fraction and number of instances containing both `lhs` and `rhs`.
+    pub support: (f64, u64),
+}
+
+/// The measure used to rank association rules.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SearchStrategy {
+    /// Rank rules by confidence.
+    Confidence,
+    /// Rank rules by coverage (the support of the antecedent).
+    Coverage,
+    /// Rank rules by leverage. This is BigML's default.
+    Leverage,
+    /// Rank rules by lift.
+    Lift,
+    /// Rank rules by support.
+    Support,
+}
+
+/// Arguments used to create an association.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Association")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to search for rules.
+    pub dataset: Id<Dataset>,
+
+    /// The maximum number of rules to return.
+    pub max_k: Option<u64>,
+
+    /// The name of this association.
+    pub name: Option<String>,
+
+    /// The measure used to rank rules.
+    pub search_strategy: Option<SearchStrategy>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            max_k: None,
+            name: None,
+            search_strategy: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_rules() {
+    let json = r#"{
+        "fields": {},
+        "items": [
+            { "count": 70, "field_id": "000000", "name": "bread" },
+            { "count": 40, "field_id": "000000", "name": "butter" }
+        ],
+        "max_k": 100,
+        "rules": [
+            {
+                "confidence": 0.5,
+                "id": "000000",
+                "leverage": 0.05,
+                "lhs": [0],
+                "lhs_cover": [0.7, 70],
+                "lift": 1.25,
+                "p_value": 0.001,
+                "rhs": [1],
+                "rhs_cover": [0.4, 40],
+                "support": [0.35, 35]
+            }
+        ],
+        "search_strategy": "leverage"
+    }"#;
+    let info: AssociationInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(info.search_strategy, Some(SearchStrategy::Leverage));
+    let rule = &info.rules[0];
+    assert_eq!(rule.support, (0.35, 35));
+    assert_eq!(info.antecedent(rule)[0].name, "bread");
+    assert_eq!(info.consequent(rule)[0].name, "butter");
+}
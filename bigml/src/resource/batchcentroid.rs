@@ -4,18 +4,19 @@ use serde::{Deserialize, Serialize};
 
 use super::id::*;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Cluster, Dataset, Project, Resource, ResourceCommon, Updatable};
 
 /// A batch centroid generated by BigML.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "batchcentroid"]
 #[non_exhaustive]
 pub struct BatchCentroid {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -27,8 +28,61 @@ pub struct BatchCentroid {
     /// Does this centroid include all the fields in the input?
     pub all_fields: bool,
 
-    // Our output dataset.
-    //pub output_dataset_resource: Option<Id<Dataset>>,
+    /// Our output dataset, if we asked for one.
+    pub output_dataset_resource: Option<Id<Dataset>>,
+
     /// Is our output dataset currently available?
     pub output_dataset_status: bool,
 }
+
+/// Arguments used to create a batch centroid.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "BatchCentroid")]
+#[non_exhaustive]
+pub struct Args {
+    /// The cluster to use.
+    pub cluster: Id<Cluster>,
+
+    /// The dataset containing the rows we want to assign to clusters.
+    pub dataset: Id<Dataset>,
+
+    /// Should the output include all the fields in the input? Combined with
+    /// `output_dataset`, this appends a cluster label to each row.
+    pub all_fields: Option<bool>,
+
+    /// Should the output include the distance to each centroid?
+    pub distance: Option<bool>,
+
+    /// Should the output CSV file include a header row?
+    pub header: Option<bool>,
+
+    /// The name of this batch centroid.
+    pub name: Option<String>,
+
+    /// Should BigML also create a dataset containing our output?
+    pub output_dataset: Option<bool>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new(cluster: Id<Cluster>, dataset: Id<Dataset>) -> Args {
+        Args {
+            cluster,
+            dataset,
+            all_fields: None,
+            distance: None,
+            header: None,
+            name: None,
+            output_dataset: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
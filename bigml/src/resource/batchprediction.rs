@@ -3,19 +3,21 @@
 use serde::{Deserialize, Serialize};
 
 use super::id::*;
+use super::prediction::Predictor;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
 
 /// A batch prediction generated by BigML.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "batchprediction"]
 #[non_exhaustive]
 pub struct BatchPrediction {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -27,8 +29,61 @@ pub struct BatchPrediction {
     /// Does this prediction include all the fields in the input?
     pub all_fields: bool,
 
-    // Our output dataset.
-    //pub output_dataset_resource: Option<Id<Dataset>>,
+    /// Our output dataset, if we asked for one.
+    pub output_dataset_resource: Option<Id<Dataset>>,
+
     /// Is our output dataset currently available?
     pub output_dataset_status: bool,
 }
+
+/// Arguments used to create a batch prediction.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "BatchPrediction")]
+#[non_exhaustive]
+pub struct Args {
+    /// The model, ensemble or fusion to use.
+    #[serde(flatten)]
+    pub predictor: Predictor,
+
+    /// The dataset containing the rows we want to make predictions for.
+    pub dataset: Id<Dataset>,
+
+    /// Should the output include all the fields in the input?
+    pub all_fields: Option<bool>,
+
+    /// Should the output include the confidence of each prediction?
+    pub confidence: Option<bool>,
+
+    /// Should the output CSV file include a header row?
+    pub header: Option<bool>,
+
+    /// The name of this batch prediction.
+    pub name: Option<String>,
+
+    /// Should BigML also create a dataset containing our output?
+    pub output_dataset: Option<bool>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new<P: Into<Predictor>>(predictor: P, dataset: Id<Dataset>) -> Args {
+        Args {
+            predictor: predictor.into(),
+            dataset,
+            all_fields: None,
+            confidence: None,
+            header: None,
+            name: None,
+            output_dataset: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! https://bigml.com/api/batchprojections
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::status::*;
+use super::{Dataset, Pca, Project, Resource, ResourceCommon, Updatable};
+
+/// A batch projection of every row in a dataset onto the principal
+/// components of a `Pca`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "batchprojection"]
+#[non_exhaustive]
+pub struct BatchProjection {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<BatchProjection>,
+
+    /// The status of this batch projection.
+    pub status: GenericStatus,
+
+    /// Does this projection include all the fields in the input?
+    #[serde(default)]
+    pub all_fields: bool,
+
+    /// Our output dataset, if we asked for one.
+    pub output_dataset_resource: Option<Id<Dataset>>,
+
+    /// Is our output dataset currently available?
+    #[serde(default)]
+    pub output_dataset_status: bool,
+}
+
+/// Arguments used to create a batch projection.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "BatchProjection")]
+#[non_exhaustive]
+pub struct Args {
+    /// The PCA to use.
+    pub pca: Id<Pca>,
+
+    /// The dataset containing the rows we want to project.
+    pub dataset: Id<Dataset>,
+
+    /// Should the output include all the fields in the input?
+    pub all_fields: Option<bool>,
+
+    /// Should the output CSV file include a header row?
+    pub header: Option<bool>,
+
+    /// The name of this batch projection.
+    pub name: Option<String>,
+
+    /// Should BigML also create a dataset containing our output?
+    pub output_dataset: Option<bool>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new(pca: Id<Pca>, dataset: Id<Dataset>) -> Args {
+        Args {
+            pca,
+            dataset,
+            all_fields: None,
+            header: None,
+            name: None,
+            output_dataset: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! https://bigml.com/api/centroids
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::status::*;
+use super::{Cluster, Project, Resource, ResourceCommon, Updatable};
+
+/// The assignment of a single row of data to the nearest cluster of a
+/// `Cluster`. (Not to be confused with `cluster::Centroid`, which describes
+/// the center of a cluster.)
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "centroid"]
+#[non_exhaustive]
+pub struct Centroid {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Centroid>,
+
+    /// The current status of this centroid.
+    pub status: GenericStatus,
+
+    /// The cluster used to assign our input.
+    pub cluster: Option<Id<Cluster>>,
+
+    /// The input values we assigned to a cluster, keyed by field ID or name.
+    #[serde(default)]
+    pub input_data: HashMap<String, serde_json::Value>,
+
+    /// The ID of the nearest cluster, such as `"000000"`.
+    pub centroid_id: Option<String>,
+
+    /// The name of the nearest cluster, such as `"Cluster 0"`.
+    pub centroid_name: Option<String>,
+
+    /// The distance from our input to the center of the nearest cluster.
+    pub distance: Option<f64>,
+}
+
+/// Arguments used to create a centroid.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Centroid")]
+#[non_exhaustive]
+pub struct Args {
+    /// The cluster to use.
+    pub cluster: Id<Cluster>,
+
+    /// Input values, keyed by field ID or name.
+    pub input_data: HashMap<String, serde_json::Value>,
+
+    /// The name of this centroid.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new(
+        cluster: Id<Cluster>,
+        input_data: HashMap<String, serde_json::Value>,
+    ) -> Args {
+        Args {
+            cluster,
+            input_data,
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_centroid() {
+    let centroid: Centroid = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 201,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "centroid/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The centroid has been created" },
+        "cluster": "cluster/5e5d87f6e476845bd8000001",
+        "input_data": { "000000": 5.1 },
+        "centroid_id": "000002",
+        "centroid_name": "Cluster 2",
+        "distance": 0.25,
+    }))
+    .unwrap();
+    assert_eq!(centroid.centroid_name.as_deref(), Some("Cluster 2"));
+    assert_eq!(centroid.distance, Some(0.25));
+}
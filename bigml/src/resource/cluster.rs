@@ -1,21 +1,24 @@
 //! https://bigml.com/api/clusters
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::id::*;
+use super::source::Field;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
 
 /// An cluster of multiple predictive models.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "cluster"]
 #[non_exhaustive]
 pub struct Cluster {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -24,8 +27,182 @@ pub struct Cluster {
     /// The current status of this cluster.
     pub status: GenericStatus,
 
-    /// Details about the clusters that BigML found.
-    ///
-    /// TODO: Convert to a strongly-typed struct.
-    pub clusters: Option<serde_json::Value>,
+    /// The dataset used to create this cluster.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The number of clusters requested.
+    pub k: Option<u64>,
+
+    /// The critical value used to choose `k` automatically using G-means, if
+    /// `k` was not specified.
+    pub critical_value: Option<f64>,
+
+    /// The scale applied to each field, keyed by BigML field ID.
+    #[serde(default)]
+    pub scales: HashMap<String, f64>,
+
+    /// Details about the clusters that BigML found. Will be missing while the
+    /// cluster is being created.
+    pub clusters: Option<ClusterInfo>,
+}
+
+/// Details about the clusters found by BigML.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ClusterInfo {
+    /// The centroid of each cluster.
+    #[serde(default)]
+    pub clusters: Vec<Centroid>,
+
+    /// Metadata describing each field used by this cluster, keyed by BigML
+    /// field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+}
+
+/// The center of a single cluster.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Centroid {
+    /// The ID of this centroid, such as `"000000"`.
+    pub id: String,
+
+    /// The human-readable name of this centroid, such as `"Cluster 0"`.
+    pub name: String,
+
+    /// The value of each field at the center of this cluster, keyed by BigML
+    /// field ID. Numeric fields use numbers, and categorical and text fields
+    /// use strings.
+    pub center: HashMap<String, serde_json::Value>,
+
+    /// The number of training instances in this cluster.
+    pub count: u64,
+
+    /// Statistics about the distance between the instances in this cluster
+    /// and its center.
+    pub distance: Option<DistanceStats>,
+}
+
+/// Statistics about the distances between instances and a centroid.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DistanceStats {
+    /// Histogram of distances, as `(distance, count)` pairs.
+    #[serde(default)]
+    pub bins: Vec<(f64, u64)>,
+
+    /// The largest distance.
+    pub maximum: Option<f64>,
+
+    /// The mean distance.
+    pub mean: Option<f64>,
+
+    /// The median distance.
+    pub median: Option<f64>,
+
+    /// The smallest distance.
+    pub minimum: Option<f64>,
+
+    /// The number of instances.
+    pub population: Option<u64>,
+
+    /// The standard deviation of the distances.
+    pub standard_deviation: Option<f64>,
+
+    /// The sum of the distances.
+    pub sum: Option<f64>,
+
+    /// The sum of the squared distances.
+    pub sum_squares: Option<f64>,
+
+    /// The variance of the distances.
+    pub variance: Option<f64>,
+}
+
+/// Arguments used to create a cluster.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Cluster")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to cluster.
+    pub dataset: Id<Dataset>,
+
+    /// The number of clusters to find. If this is omitted, BigML will choose
+    /// a value using G-means.
+    pub k: Option<u64>,
+
+    /// The critical value used by G-means. Only used when `k` is omitted.
+    pub critical_value: Option<f64>,
+
+    /// The name of this cluster.
+    pub name: Option<String>,
+
+    /// A scale to apply to each field, keyed by BigML field ID or name.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub field_scales: HashMap<String, f64>,
+
+    /// Should BigML scale all numeric fields to have the same standard
+    /// deviation?
+    pub balance_fields: Option<bool>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            k: None,
+            critical_value: None,
+            name: None,
+            field_scales: HashMap::new(),
+            balance_fields: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_clusters() {
+    let json = r#"{
+        "clusters": [
+            {
+                "center": { "000000": 5.006, "000004": "Iris-setosa" },
+                "count": 50,
+                "distance": {
+                    "bins": [[0.05, 3], [0.1, 12]],
+                    "maximum": 0.5,
+                    "mean": 0.17,
+                    "median": 0.16,
+                    "minimum": 0.02,
+                    "population": 50,
+                    "standard_deviation": 0.08,
+                    "sum": 8.5,
+                    "sum_squares": 1.8,
+                    "variance": 0.0064
+                },
+                "id": "000000",
+                "name": "Cluster 0"
+            }
+        ],
+        "fields": {
+            "000000": { "name": "sepal length", "optype": "numeric" }
+        }
+    }"#;
+    let info: ClusterInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(info.clusters.len(), 1);
+    let centroid = &info.clusters[0];
+    assert_eq!(centroid.name, "Cluster 0");
+    assert_eq!(centroid.count, 50);
+    assert_eq!(centroid.center["000004"], "Iris-setosa");
+    let distance = centroid.distance.as_ref().unwrap();
+    assert_eq!(distance.bins[1], (0.1, 12));
+    assert_eq!(distance.population, Some(50));
 }
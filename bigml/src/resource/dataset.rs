@@ -2,28 +2,32 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 use super::id::*;
-use super::source::Field;
+use super::source::{Field, Optype};
 use super::status::*;
-use super::{Resource, ResourceCommon, Source};
+use super::{Project, Resource, ResourceCommon, Source, Updatable};
+use crate::errors::*;
 
 /// A BigML dataset. Basically a table of data with named columns.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "dataset"]
+#[ready_when(summarized)]
 #[non_exhaustive]
 pub struct Dataset {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
     pub resource: Id<Dataset>,
 
-    /// The current status of this execution.
+    /// The current status of this dataset.
     pub status: GenericStatus,
 
     /// The number of columns in the dataset.
@@ -39,42 +43,594 @@ pub struct Dataset {
     /// Metadata describing each field. Will be empty while object is being
     /// created.
     #[serde(default)]
+    #[updatable]
     pub fields: HashMap<String, Field>,
 
     /// Field IDs included when building this dataset.
     pub input_fields: Vec<String>,
 
+    /// The default field to predict when building models from this dataset.
+    pub objective_field: Option<ObjectiveField>,
+
+    /// The dataset used to create this dataset, if any.
+    pub origin_dataset: Option<Id<Dataset>>,
+
     /// The number of rows in this dataset.
     pub rows: usize,
+
+    /// The source used to create this dataset, if any.
+    pub source: Option<Id<Source>>,
+}
+
+impl Dataset {
+    /// The summary statistics for the field with ID `field_id`, interpreted
+    /// according to the field's optype. Returns `None` if the field doesn't
+    /// exist or hasn't been summarized yet.
+    pub fn field_summary(&self, field_id: &str) -> Result<Option<FieldSummary>> {
+        match self.fields.get(field_id) {
+            Some(field) => match &field.summary {
+                Some(summary) => {
+                    Ok(Some(FieldSummary::from_value(field.optype, summary)?))
+                }
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The summary statistics for every summarized field, keyed by BigML
+    /// field ID.
+    pub fn field_summaries(&self) -> Result<HashMap<String, FieldSummary>> {
+        let mut summaries = HashMap::new();
+        for (id, field) in &self.fields {
+            if let Some(summary) = &field.summary {
+                summaries.insert(
+                    id.to_owned(),
+                    FieldSummary::from_value(field.optype, summary)?,
+                );
+            }
+        }
+        Ok(summaries)
+    }
+}
+
+/// Summary statistics computed by BigML for a single dataset field. The
+/// available statistics depend on the field's optype.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FieldSummary {
+    /// Statistics for a numeric field.
+    Numeric(NumericSummary),
+    /// Statistics for a categorical field.
+    Categorical(CategoricalSummary),
+    /// Statistics for a text field.
+    Text(TextSummary),
+    /// Statistics for an items field.
+    Items(ItemsSummary),
+    /// Statistics for a datetime field. BigML summarizes the components of
+    /// datetime fields as separate, generated fields.
+    DateTime(DateTimeSummary),
+}
+
+impl FieldSummary {
+    /// Interpret the raw JSON `summary` of a field with type `optype`.
+    pub fn from_value(optype: Optype, summary: &serde_json::Value) -> Result<Self> {
+        let summary = summary.clone();
+        Ok(match optype {
+            Optype::Numeric => FieldSummary::Numeric(serde_json::from_value(summary)?),
+            Optype::Categorical => {
+                FieldSummary::Categorical(serde_json::from_value(summary)?)
+            }
+            Optype::Text => FieldSummary::Text(serde_json::from_value(summary)?),
+            Optype::Items => FieldSummary::Items(serde_json::from_value(summary)?),
+            Optype::DateTime => {
+                FieldSummary::DateTime(serde_json::from_value(summary)?)
+            }
+        })
+    }
+
+    /// The number of rows where this field is missing.
+    pub fn missing_count(&self) -> u64 {
+        match self {
+            FieldSummary::Numeric(summary) => summary.missing_count,
+            FieldSummary::Categorical(summary) => summary.missing_count,
+            FieldSummary::Text(summary) => summary.missing_count,
+            FieldSummary::Items(summary) => summary.missing_count,
+            FieldSummary::DateTime(summary) => summary.missing_count,
+        }
+    }
+}
+
+/// Summary statistics for a numeric field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct NumericSummary {
+    /// The number of rows where this field is missing.
+    #[serde(default)]
+    pub missing_count: u64,
+    /// The number of rows where this field is present.
+    pub population: Option<u64>,
+    /// The smallest value.
+    pub minimum: Option<f64>,
+    /// The largest value.
+    pub maximum: Option<f64>,
+    /// The mean value.
+    pub mean: Option<f64>,
+    /// The median value.
+    pub median: Option<f64>,
+    /// The standard deviation of our values.
+    pub standard_deviation: Option<f64>,
+    /// The variance of our values.
+    pub variance: Option<f64>,
+    /// The sum of our values.
+    pub sum: Option<f64>,
+    /// An approximate histogram, as `(value, count)` pairs. BigML uses this
+    /// when there are many distinct values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bins: Vec<(f64, u64)>,
+    /// The exact count of each value, as `(value, count)` pairs. BigML uses
+    /// this when there are few distinct values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub counts: Vec<(f64, u64)>,
+}
+
+impl NumericSummary {
+    /// Our histogram, as `(value, count)` pairs, using either `counts` or
+    /// `bins`, whichever BigML provided.
+    pub fn histogram(&self) -> &[(f64, u64)] {
+        if self.counts.is_empty() {
+            &self.bins
+        } else {
+            &self.counts
+        }
+    }
+}
+
+/// Summary statistics for a categorical field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct CategoricalSummary {
+    /// The number of rows where this field is missing.
+    #[serde(default)]
+    pub missing_count: u64,
+    /// The number of rows in each category, as `(category, count)` pairs.
+    #[serde(default)]
+    pub categories: Vec<(String, u64)>,
+}
+
+/// Summary statistics for a text field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TextSummary {
+    /// The number of rows where this field is missing.
+    #[serde(default)]
+    pub missing_count: u64,
+    /// The most common terms, as `(term, count)` pairs.
+    #[serde(default)]
+    pub tag_cloud: Vec<(String, u64)>,
+    /// Other forms of each term which were treated as the same term, keyed
+    /// by term.
+    #[serde(default)]
+    pub term_forms: HashMap<String, Vec<String>>,
+}
+
+/// Summary statistics for an items field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ItemsSummary {
+    /// The number of rows where this field is missing.
+    #[serde(default)]
+    pub missing_count: u64,
+    /// The most common items, as `(item, count)` pairs.
+    #[serde(default)]
+    pub items: Vec<(String, u64)>,
+}
+
+/// Summary statistics for a datetime field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct DateTimeSummary {
+    /// The number of rows where this field is missing.
+    #[serde(default)]
+    pub missing_count: u64,
+}
+
+/// Information about the objective field of a dataset.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ObjectiveField {
+    /// The position of this field in the dataset.
+    pub column_number: Option<usize>,
+
+    /// The BigML field ID.
+    pub id: String,
+
+    /// The human-readable name of this field.
+    pub name: Option<String>,
+
+    /// The type of data stored in this field.
+    pub optype: Option<Optype>,
 }
 
 /// Arguments used to create a dataset.
-#[derive(Debug, Serialize)]
+#[bigml_args]
+#[derive(Args, Debug, Default, Serialize)]
+#[args(resource = "Dataset")]
 #[non_exhaustive]
 pub struct Args {
     /// The ID of the BigML `Source` from which to import data.
-    pub source: Id<Source>,
+    pub source: Option<Id<Source>>,
+
+    /// The ID of an existing dataset from which to build this dataset.
+    pub origin_dataset: Option<Id<Dataset>>,
+
+    /// Existing datasets to merge into this dataset.
+    pub origin_datasets: Vec<Id<Dataset>>,
+
+    /// When merging `origin_datasets`, the fraction of rows to sample from
+    /// each, keyed by dataset ID.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub sample_rates: HashMap<String, f64>,
+
+    /// When merging `origin_datasets`, how to map the field IDs of each
+    /// dataset (keyed by dataset ID) onto the field IDs of the first
+    /// dataset.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub fields_maps: HashMap<String, HashMap<String, String>>,
+
+    /// Field IDs to exclude when building this dataset.
+    pub excluded_fields: Vec<String>,
+
+    /// Copy all fields from `origin_dataset` except these.
+    pub all_but: Vec<String>,
+
+    /// Copy only these fields from `origin_dataset`.
+    pub input_fields: Vec<String>,
+
+    /// New fields to compute using Flatline expressions.
+    pub new_fields: Vec<NewField>,
 
     /// The name of this dataset.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// The fraction of rows to sample from the source, between 0.0 and 1.0.
+    pub sample_rate: Option<f64>,
+
+    /// The number of bytes of the source to use.
+    pub size: Option<u64>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
     /// User-defined tags.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 }
 
 impl Args {
-    /// Create a new `Args`.
+    /// Create a new `Args` which imports data from `source`.
     pub fn from_source(source: Id<Source>) -> Args {
         Args {
-            source,
-            name: None,
-            tags: vec![],
+            source: Some(source),
+            ..Args::default()
+        }
+    }
+
+    /// Create a new `Args` which builds a dataset from an existing dataset,
+    /// typically with some fields removed or added.
+    pub fn from_dataset(origin_dataset: Id<Dataset>) -> Args {
+        Args {
+            origin_dataset: Some(origin_dataset),
+            ..Args::default()
+        }
+    }
+
+    /// Create a new `Args` which merges the rows of `origin_datasets` into a
+    /// single dataset. By default, fields are matched up by position. Use
+    /// `sample_rates` and `fields_maps` to customize this.
+    pub fn from_datasets<I>(origin_datasets: I) -> Args
+    where
+        I: IntoIterator<Item = Id<Dataset>>,
+    {
+        Args {
+            origin_datasets: origin_datasets.into_iter().collect(),
+            ..Args::default()
+        }
+    }
+
+    /// Add a new field named `name`, computed using `expr`.
+    pub fn add_new_field<S: Into<String>>(&mut self, name: S, expr: Flatline) {
+        self.new_fields.push(NewField::new(name, expr));
+    }
+}
+
+/// A new field to compute when creating a dataset.
+#[derive(Clone, Debug, Serialize)]
+#[non_exhaustive]
+pub struct NewField {
+    /// The Flatline expression used to compute this field.
+    pub field: Flatline,
+
+    /// The name of the new field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// A human-readable description of the new field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl NewField {
+    /// Create a new field named `name`, computed using `expr`.
+    pub fn new<S: Into<String>>(name: S, expr: Flatline) -> NewField {
+        NewField {
+            field: expr,
+            name: Some(name.into()),
+            description: None,
         }
     }
 }
 
-impl super::Args for Args {
-    type Resource = Dataset;
+/// A [Flatline][] expression, used to compute new fields from existing ones.
+/// This provides helpers for building common expressions, but you can use
+/// `Flatline::raw` for anything else.
+///
+/// ```
+/// use bigml::resource::dataset::Flatline;
+///
+/// let expr = Flatline::field("price").div(Flatline::field("area"));
+/// assert_eq!(expr.to_string(), r#"(/ (f "price") (f "area"))"#);
+/// ```
+///
+/// [Flatline]: https://github.com/bigmlcom/flatline
+#[derive(Clone, Debug, Serialize)]
+#[serde(transparent)]
+pub struct Flatline(String);
+
+impl Flatline {
+    /// Use `expr` as-is.
+    pub fn raw<S: Into<String>>(expr: S) -> Flatline {
+        Flatline(expr.into())
+    }
+
+    /// The value of the field with the specified name or ID.
+    pub fn field(name: &str) -> Flatline {
+        Flatline::call("f", vec![Flatline::string(name)])
+    }
+
+    /// A numeric constant.
+    pub fn number(n: f64) -> Flatline {
+        Flatline(n.to_string())
+    }
+
+    /// A string constant.
+    pub fn string(s: &str) -> Flatline {
+        // Flatline strings use the same escapes as JSON.
+        Flatline(serde_json::Value::from(s).to_string())
+    }
+
+    /// Call the Flatline function `name` with `args`.
+    pub fn call(name: &str, args: Vec<Flatline>) -> Flatline {
+        let mut expr = format!("({}", name);
+        for arg in args {
+            expr.push(' ');
+            expr.push_str(&arg.0);
+        }
+        expr.push(')');
+        Flatline(expr)
+    }
+
+    /// Is the field with the specified name or ID missing?
+    pub fn missing(name: &str) -> Flatline {
+        Flatline::call("missing?", vec![Flatline::string(name)])
+    }
+
+    /// `self` if it isn't missing, and `default` otherwise.
+    pub fn or_default(self, name: &str, default: Flatline) -> Flatline {
+        Flatline::call("if", vec![Flatline::missing(name), default, self])
+    }
+
+    /// Add `other` to `self`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, other: Flatline) -> Flatline {
+        Flatline::call("+", vec![self, other])
+    }
+
+    /// Subtract `other` from `self`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(self, other: Flatline) -> Flatline {
+        Flatline::call("-", vec![self, other])
+    }
+
+    /// Multiply `self` by `other`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(self, other: Flatline) -> Flatline {
+        Flatline::call("*", vec![self, other])
+    }
+
+    /// Divide `self` by `other`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(self, other: Flatline) -> Flatline {
+        Flatline::call("/", vec![self, other])
+    }
+}
+
+impl fmt::Display for Flatline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[test]
+fn deserialize_dataset() {
+    let json = r#"{
+        "category": 0,
+        "code": 200,
+        "columns": 2,
+        "description": "",
+        "excluded_fields": [],
+        "field_types": {"categorical": 1, "numeric": 1, "preferred": 2},
+        "fields": {
+            "000000": {"name": "id", "optype": "numeric"},
+            "000001": {"name": "color", "optype": "categorical"}
+        },
+        "input_fields": ["000000", "000001"],
+        "name": "example",
+        "objective_field": {
+            "column_number": 1,
+            "datatype": "string",
+            "id": "000001",
+            "name": "color",
+            "optype": "categorical"
+        },
+        "origin_dataset": null,
+        "resource": "dataset/5e5d87f6e476845bd8000000",
+        "rows": 2,
+        "shared": false,
+        "source": "source/5e5d87e0e476845bd7000000",
+        "status": {"code": 5, "message": "The dataset has been created"},
+        "subscription": false,
+        "tags": []
+    }"#;
+    let dataset: Dataset = serde_json::from_str(json).unwrap();
+    assert_eq!(dataset.rows, 2);
+    let objective_field = dataset.objective_field.unwrap();
+    assert_eq!(objective_field.id, "000001");
+    assert_eq!(objective_field.optype, Some(Optype::Categorical));
+    assert!(dataset.origin_dataset.is_none());
+    assert_eq!(
+        dataset.source.unwrap().as_str(),
+        "source/5e5d87e0e476845bd7000000"
+    );
+}
+
+#[test]
+fn field_metadata_can_be_updated() {
+    use super::source::FieldUpdate;
+
+    let mut fields = HashMap::new();
+    fields.insert(
+        "000001".to_owned(),
+        FieldUpdate {
+            label: Some(Some("Color".to_owned())),
+            preferred: Some(Some(false)),
+            ..FieldUpdate::default()
+        },
+    );
+    let update = DatasetUpdate {
+        fields: Some(fields),
+        ..DatasetUpdate::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        serde_json::json!({
+            "fields": { "000001": { "label": "Color", "preferred": false } },
+        }),
+    );
+}
+
+#[test]
+fn serialize_args_with_new_fields() {
+    let origin: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut args = Args::from_dataset(origin);
+    args.all_but = vec!["000002".to_owned()];
+    args.add_new_field(
+        "price_per_area",
+        Flatline::field("price")
+            .div(Flatline::field("area"))
+            .or_default("area", Flatline::number(0.0)),
+    );
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "origin_dataset": "dataset/5e5d87f6e476845bd8000000",
+            "all_but": ["000002"],
+            "new_fields": [{
+                "field": r#"(if (missing? "area") 0 (/ (f "price") (f "area")))"#,
+                "name": "price_per_area",
+            }],
+        }),
+    );
+}
+
+#[test]
+fn serialize_args_merging_datasets() {
+    let january: Id<Dataset> = "dataset/5e5d87f6e476845bd8000001".parse().unwrap();
+    let february: Id<Dataset> = "dataset/5e5d87f6e476845bd8000002".parse().unwrap();
+    let mut args = Args::from_datasets(vec![january, february.clone()]);
+    args.sample_rates.insert(february.to_string(), 0.5);
+    let mut fields_map = HashMap::new();
+    fields_map.insert("000001".to_owned(), "000000".to_owned());
+    args.fields_maps.insert(february.to_string(), fields_map);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "origin_datasets": [
+                "dataset/5e5d87f6e476845bd8000001",
+                "dataset/5e5d87f6e476845bd8000002",
+            ],
+            "sample_rates": { "dataset/5e5d87f6e476845bd8000002": 0.5 },
+            "fields_maps": {
+                "dataset/5e5d87f6e476845bd8000002": { "000001": "000000" },
+            },
+        }),
+    );
+}
+
+#[test]
+fn typed_field_summaries() {
+    let dataset: Dataset = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "columns": 2,
+        "description": "",
+        "excluded_fields": [],
+        "field_types": {"categorical": 1, "numeric": 1, "preferred": 2},
+        "fields": {
+            "000000": {
+                "name": "size",
+                "optype": "numeric",
+                "summary": {
+                    "counts": [[1, 2], [3, 1]],
+                    "maximum": 3,
+                    "mean": 1.66667,
+                    "minimum": 1,
+                    "missing_count": 1,
+                    "population": 3,
+                },
+            },
+            "000001": {
+                "name": "color",
+                "optype": "categorical",
+                "summary": {
+                    "categories": [["red", 3], ["blue", 1]],
+                    "missing_count": 0,
+                },
+            },
+        },
+        "input_fields": ["000000", "000001"],
+        "name": "example",
+        "resource": "dataset/5e5d87f6e476845bd8000000",
+        "rows": 4,
+        "shared": false,
+        "status": {"code": 5, "message": "The dataset has been created"},
+        "subscription": false,
+        "tags": [],
+    }))
+    .unwrap();
+    match dataset.field_summary("000000").unwrap().unwrap() {
+        FieldSummary::Numeric(summary) => {
+            assert_eq!(summary.maximum, Some(3.0));
+            assert_eq!(summary.histogram(), &[(1.0, 2), (3.0, 1)]);
+            assert_eq!(summary.missing_count, 1);
+        }
+        other => panic!("expected numeric summary, got {:?}", other),
+    }
+    let summaries = dataset.field_summaries().unwrap();
+    match &summaries["000001"] {
+        FieldSummary::Categorical(summary) => {
+            assert_eq!(summary.categories[0], ("red".to_owned(), 3));
+        }
+        other => panic!("expected categorical summary, got {:?}", other),
+    }
+    assert!(dataset.field_summary("000002").unwrap().is_none());
 }
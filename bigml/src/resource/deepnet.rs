@@ -0,0 +1,212 @@
+//! https://bigml.com/api/deepnets
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::model::ModelBuildOptions;
+use super::source::Field;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+
+/// A deep neural network model.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "deepnet"]
+#[non_exhaustive]
+pub struct Deepnet {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Deepnet>,
+
+    /// The current status of this deepnet.
+    pub status: GenericStatus,
+
+    /// The dataset used to create this deepnet.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The ID of the field this deepnet predicts.
+    pub objective_field: Option<String>,
+
+    /// The trained network. Will be missing while the deepnet is being
+    /// created.
+    pub deepnet: Option<DeepnetInfo>,
+}
+
+/// The contents of a deepnet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DeepnetInfo {
+    /// Metadata describing each field used by this deepnet, keyed by BigML
+    /// field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+
+    /// The relative importance of each field, keyed by BigML field ID.
+    #[serde(default)]
+    pub importance: HashMap<String, f64>,
+
+    /// The maximum number of gradient steps used during training.
+    pub max_iterations: Option<u64>,
+
+    /// The trained network.
+    pub network: Option<Network>,
+
+    /// The number of gradient steps actually taken.
+    pub number_of_iterations: Option<u64>,
+}
+
+/// A summary of a trained network.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Network {
+    /// The hidden layers of this network, in order from input to output.
+    #[serde(default)]
+    pub hidden_layers: Vec<HiddenLayer>,
+
+    /// The layers of this network, including learned weights, in order from
+    /// input to output.
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+
+    /// Details of the optimizer used for training, which vary based on the
+    /// optimizer.
+    pub optimizer: Option<serde_json::Value>,
+}
+
+/// The structure of a single hidden layer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct HiddenLayer {
+    /// The activation function used by this layer, such as `"relu"` or
+    /// `"tanh"`.
+    pub activation_function: String,
+
+    /// The number of nodes in this layer.
+    pub number_of_nodes: u64,
+}
+
+/// A layer of a trained network.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Layer {
+    /// The activation function used by this layer.
+    pub activation_function: Option<String>,
+
+    /// The number of nodes in this layer.
+    pub number_of_nodes: Option<u64>,
+
+    /// The bias of each node in this layer.
+    #[serde(default)]
+    pub offset: Vec<f64>,
+
+    /// The learned weights of this layer, with one row per input.
+    #[serde(default)]
+    pub weights: Vec<Vec<f64>>,
+}
+
+/// Arguments used to create a deepnet.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Deepnet")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to train on.
+    pub dataset: Id<Dataset>,
+
+    /// The structure of the hidden layers. If this is empty, BigML will
+    /// choose a structure for us.
+    pub hidden_layers: Vec<HiddenLayer>,
+
+    /// The maximum time to spend training, in seconds.
+    pub max_training_time: Option<u64>,
+
+    /// The name of this deepnet.
+    pub name: Option<String>,
+
+    /// The number of hidden layers to use, when `hidden_layers` is empty.
+    pub number_of_hidden_layers: Option<u64>,
+
+    /// The number of networks to try when `search` is true.
+    pub number_of_model_candidates: Option<u64>,
+
+    /// Options shared by all supervised models.
+    #[serde(flatten)]
+    pub options: ModelBuildOptions,
+
+    /// Should BigML search for the best network structure? This overrides
+    /// most other structural options.
+    pub search: Option<bool>,
+
+    /// Should BigML suggest a network structure based on past results?
+    pub suggest_structure: Option<bool>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            hidden_layers: vec![],
+            max_training_time: None,
+            name: None,
+            number_of_hidden_layers: None,
+            number_of_model_candidates: None,
+            options: ModelBuildOptions::default(),
+            search: None,
+            suggest_structure: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_network() {
+    let json = r#"{
+        "fields": {},
+        "max_iterations": 1000,
+        "network": {
+            "hidden_layers": [
+                { "activation_function": "relu", "number_of_nodes": 2 }
+            ],
+            "layers": [
+                {
+                    "activation_function": "relu",
+                    "number_of_nodes": 2,
+                    "offset": [0.1, -0.2],
+                    "weights": [[0.5, 0.25], [-1.0, 0.75]]
+                },
+                {
+                    "activation_function": "softmax",
+                    "number_of_nodes": 3,
+                    "offset": [0, 0, 0],
+                    "weights": [[1, 2, 3], [4, 5, 6]]
+                }
+            ],
+            "optimizer": { "adam": { "beta1": 0.9 } }
+        },
+        "number_of_iterations": 250
+    }"#;
+    let info: DeepnetInfo = serde_json::from_str(json).unwrap();
+    let network = info.network.unwrap();
+    assert_eq!(network.hidden_layers[0].number_of_nodes, 2);
+    assert_eq!(network.layers.len(), 2);
+    assert_eq!(
+        network.layers[1].activation_function.as_deref(),
+        Some("softmax")
+    );
+    assert_eq!(network.layers[0].weights[1], vec![-1.0, 0.75]);
+}
@@ -1,22 +1,30 @@
 //! An ensemble of multiple predictive models.
 
+use futures::prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 use super::id::*;
+use super::model::{ModelBuildOptions, Output, TreeNode};
+use super::source::Field;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Model, Project, Resource, ResourceCommon, Updatable};
+use crate::client::Client;
+use crate::errors::*;
 
-/// An ensemble of multiple predictive models.
-///
-/// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+/// An ensemble of multiple predictive models, either a random decision
+/// forest or a set of boosted trees.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "ensemble"]
 #[non_exhaustive]
 pub struct Ensemble {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -25,34 +33,576 @@ pub struct Ensemble {
     /// The current status of this ensemble.
     pub status: GenericStatus,
 
-    /// Extra information about this ensemble. Does not appear to be
-    /// documented in the official API.
-    ///
-    /// TODO: This may need to be wrapped in `Option` to handle the early
-    /// stages of resource creation, when not all fields are present.
-    pub ensemble: EnsembleInfo,
-
-    /// Maps BigML field IDs to average importance per field.
-    ///
-    /// TODO: This may need to be wrapped in `Option` to handle the early
-    /// stages of resource creation, when not all fields are present.
+    /// The dataset used to create this ensemble.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The ID of the field this ensemble predicts.
+    pub objective_field: Option<String>,
+
+    /// The boosting options used to train this ensemble. Only present for
+    /// boosted trees.
+    pub boosting: Option<Boosting>,
+
+    /// The number of models in this ensemble. For boosted trees, this is
+    /// the number of models built per iteration.
+    pub number_of_models: Option<u64>,
+
+    /// Was this ensemble trained as a random decision forest, choosing from
+    /// a random subset of fields at each split?
+    pub randomize: Option<bool>,
+
+    /// With `randomize`, the number of fields considered at each split.
+    pub random_candidates: Option<u64>,
+
+    /// How each model's training data was sampled from our dataset.
+    pub ensemble_sample: Option<EnsembleSample>,
+
+    /// Extra information about this ensemble. Will be missing while the
+    /// ensemble is being created.
+    pub ensemble: Option<EnsembleInfo>,
+
+    /// Maps BigML field IDs to the importance of each field, averaged over
+    /// all our models.
+    #[serde(default)]
     pub importance: HashMap<String, f64>,
-    // The dataset used to create this ensemble.
-    //pub dataset: Id<Dataset>,
+
+    /// The models which make up this ensemble.
+    #[serde(default)]
+    pub models: Vec<Id<Model>>,
+}
+
+impl Ensemble {
+    /// Is this ensemble made of boosted trees?
+    pub fn is_boosted(&self) -> bool {
+        self.boosting.is_some()
+    }
 }
 
 /// Information about this ensemble.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct EnsembleInfo {
-    /// Information about this ensemble's fields. Keyed by BigML field ID.
-    pub fields: HashMap<String, EnsembleField>,
+    /// Metadata describing each field used by this ensemble, keyed by BigML
+    /// field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
 }
 
-/// List of field codes mapped to input fields
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Options for training boosted trees. Each iteration fits new trees to the
+/// errors made by the previous ones.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[non_exhaustive]
-pub struct EnsembleField {
-    /// The original name of this field (not the BigML field ID).
-    pub name: String,
+pub struct Boosting {
+    /// The maximum number of boosting iterations. BigML defaults to `10`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u64>,
+
+    /// How much each iteration's trees contribute to the prediction.
+    /// BigML defaults to `0.1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub learning_rate: Option<f64>,
+
+    /// The fraction of the dataset to hold out to decide when to stop
+    /// iterating early.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub early_holdout: Option<f64>,
+
+    /// Should we use out-of-bag samples to decide when to stop iterating
+    /// early?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub early_out_of_bag: Option<bool>,
+
+    /// Should we use out-of-bag samples to choose the step size of each
+    /// iteration?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_out_of_bag: Option<bool>,
+}
+
+impl Boosting {
+    /// Run at most `iterations` boosting iterations.
+    pub fn iterations(mut self, iterations: u64) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// Scale the contribution of each iteration by `learning_rate`.
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = Some(learning_rate);
+        self
+    }
+}
+
+/// How to sample the training data for each model in an ensemble.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct EnsembleSample {
+    /// The fraction of the dataset to use for each model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+
+    /// Should we sample with replacement?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<bool>,
+
+    /// A seed for the random sampling, so that it can be repeated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+}
+
+/// Arguments used to create an ensemble.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Ensemble")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to train on.
+    pub dataset: Id<Dataset>,
+
+    /// Train boosted trees using these options, instead of a bagged
+    /// ensemble or random decision forest.
+    pub boosting: Option<Boosting>,
+
+    /// How to sample the training data for each model.
+    pub ensemble_sample: Option<EnsembleSample>,
+
+    /// The name of this ensemble.
+    pub name: Option<String>,
+
+    /// The number of models to build. BigML defaults to `10`. For boosted
+    /// trees, this is the number of models built per iteration.
+    pub number_of_models: Option<u64>,
+
+    /// Options shared by all supervised models.
+    #[serde(flatten)]
+    pub options: ModelBuildOptions,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// With `randomize`, the fraction of fields to consider at each split.
+    pub random_candidate_ratio: Option<f64>,
+
+    /// With `randomize`, the number of fields to consider at each split.
+    pub random_candidates: Option<u64>,
+
+    /// Train a random decision forest, choosing from a random subset of
+    /// fields at each split.
+    pub randomize: Option<bool>,
+
+    /// A seed for BigML's random choices, so that training can be
+    /// repeated.
+    pub seed: Option<String>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            boosting: None,
+            ensemble_sample: None,
+            name: None,
+            number_of_models: None,
+            options: ModelBuildOptions::default(),
+            project: None,
+            random_candidate_ratio: None,
+            random_candidates: None,
+            randomize: None,
+            seed: None,
+            tags: vec![],
+        }
+    }
+
+    /// Create `Args` for a random decision forest trained on `dataset`.
+    pub fn random_decision_forest(dataset: Id<Dataset>) -> Args {
+        let mut args = Args::from_dataset(dataset);
+        args.randomize = Some(true);
+        args
+    }
+
+    /// Create `Args` for boosted trees trained on `dataset`.
+    pub fn boosted_trees(dataset: Id<Dataset>, boosting: Boosting) -> Args {
+        let mut args = Args::from_dataset(dataset);
+        args.boosting = Some(boosting);
+        args
+    }
+}
+
+/// How to combine the predictions of the models in an ensemble. These follow
+/// the semantics of BigML's `combiner` option.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Combiner {
+    /// Each model gets a single vote. For regression ensembles, we average
+    /// the predictions.
+    Plurality,
+    /// Each model's vote is weighted by its confidence. For regression
+    /// ensembles, we compute an average weighted by confidence.
+    ConfidenceWeighted,
+    /// Each model votes for every class in proportion to the distribution of
+    /// training instances at its prediction node. For regression ensembles,
+    /// we average the predictions.
+    ProbabilityWeighted,
+    /// Predict `class` if at least `threshold` models vote for it, and
+    /// otherwise fall back to a plurality vote among the other classes.
+    Threshold {
+        /// The minimum number of votes needed to predict `class`.
+        threshold: usize,
+        /// The class to predict when we have enough votes.
+        class: String,
+    },
+}
+
+/// A prediction made locally by a `LocalEnsemble`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EnsemblePrediction {
+    /// The predicted value.
+    pub output: Output,
+
+    /// For classification ensembles, the fraction of the (weighted) vote
+    /// received by `output`.
+    pub confidence: Option<f64>,
+}
+
+/// An ensemble which can make predictions locally, without calling BigML.
+///
+/// Only bagged ensembles and random decision forests are supported. Boosted
+/// trees combine their models' outputs differently, so `fetch` rejects them.
+#[derive(Clone, Debug)]
+pub struct LocalEnsemble {
+    /// The decision tree of each model.
+    trees: Vec<TreeNode>,
+
+    /// How to combine the predictions of our trees.
+    combiner: Combiner,
+}
+
+impl LocalEnsemble {
+    /// Create a `LocalEnsemble` from already-downloaded `models`. Fails if
+    /// any model does not include a decision tree, or if any tree tests for
+    /// terms in a text or items field (see `TreeNode::uses_terms`). The models
+    /// must not come from a boosted ensemble.
+    pub fn new(models: Vec<Model>, combiner: Combiner) -> Result<LocalEnsemble> {
+        let trees = models
+            .into_iter()
+            .map(|model| {
+                let resource = model.resource;
+                let tree =
+                    model.model.and_then(|info| info.root).ok_or_else(|| {
+                        format_err!("model {} has no decision tree", resource)
+                    })?;
+                if tree.uses_terms() {
+                    return Err(format_err!(
+                        "model {} tests for terms in text or items fields, which \
+                         LocalEnsemble does not support",
+                        resource,
+                    ));
+                }
+                Ok(tree)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(LocalEnsemble { trees, combiner })
+    }
+
+    /// Download `ensemble` and all of its models, and create a
+    /// `LocalEnsemble`. We wait for any resources which aren't ready yet.
+    /// Fails if `ensemble` is made of boosted trees.
+    pub async fn fetch(
+        client: &Client,
+        ensemble: &Id<Ensemble>,
+        combiner: Combiner,
+    ) -> Result<LocalEnsemble> {
+        let ensemble = client.wait(ensemble).await?;
+        if ensemble.is_boosted() {
+            return Err(format_err!(
+                "{} uses boosted trees, which LocalEnsemble does not support",
+                ensemble.resource,
+            ));
+        }
+        let models = client
+            .fetch_many(ensemble.models, MAX_CONCURRENT_DOWNLOADS)
+            .try_collect::<Vec<Model>>()
+            .await?;
+        LocalEnsemble::new(models, combiner)
+    }
+
+    /// Predict the objective field for `input`, which maps BigML field IDs
+    /// to values. Returns `None` if this ensemble contains no models.
+    pub fn predict(
+        &self,
+        input: &HashMap<String, Value>,
+    ) -> Option<EnsemblePrediction> {
+        let nodes = self
+            .trees
+            .iter()
+            .map(|tree| {
+                tree.predict(input)
+                    .expect("LocalEnsemble::new should have rejected term conditions")
+            })
+            .collect::<Vec<_>>();
+        match nodes.first()?.output() {
+            Output::Numeric(_) => self.combine_numeric(&nodes),
+            Output::Categorical(_) => self.combine_categorical(&nodes),
+        }
+    }
+
+    /// Predict the objective field for each of `inputs`. If the `rayon`
+    /// feature is enabled, this will run in parallel.
+    pub fn predict_batch(
+        &self,
+        inputs: &[HashMap<String, Value>],
+    ) -> Vec<Option<EnsemblePrediction>> {
+        #[cfg(feature = "rayon")]
+        let inputs = inputs.par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let inputs = inputs.iter();
+        inputs.map(|input| self.predict(input)).collect()
+    }
+
+    /// Combine the predictions of a regression ensemble.
+    fn combine_numeric(&self, nodes: &[&TreeNode]) -> Option<EnsemblePrediction> {
+        let mut total = 0.0;
+        let mut total_weight = 0.0;
+        for node in nodes {
+            if let Output::Numeric(value) = node.output() {
+                let weight = match self.combiner {
+                    Combiner::ConfidenceWeighted => node.confidence().unwrap_or(1.0),
+                    _ => 1.0,
+                };
+                total += value * weight;
+                total_weight += weight;
+            }
+        }
+        if total_weight > 0.0 {
+            Some(EnsemblePrediction {
+                output: Output::Numeric(total / total_weight),
+                confidence: None,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Combine the predictions of a classification ensemble.
+    fn combine_categorical(&self, nodes: &[&TreeNode]) -> Option<EnsemblePrediction> {
+        let mut votes = HashMap::<&str, f64>::new();
+        for node in nodes {
+            let class = match node.output() {
+                Output::Categorical(class) => class.as_str(),
+                Output::Numeric(_) => continue,
+            };
+            match &self.combiner {
+                Combiner::ConfidenceWeighted => {
+                    *votes.entry(class).or_default() +=
+                        node.confidence().unwrap_or(1.0);
+                }
+                Combiner::ProbabilityWeighted => {
+                    let categories = node
+                        .objective_summary()
+                        .map(|summary| &summary.categories[..])
+                        .unwrap_or(&[]);
+                    let count = categories.iter().map(|(_, n)| n).sum::<u64>();
+                    if count == 0 {
+                        *votes.entry(class).or_default() += 1.0;
+                    } else {
+                        for (category, n) in categories {
+                            *votes.entry(category.as_str()).or_default() +=
+                                *n as f64 / count as f64;
+                        }
+                    }
+                }
+                Combiner::Plurality | Combiner::Threshold { .. } => {
+                    *votes.entry(class).or_default() += 1.0;
+                }
+            }
+        }
+
+        if let Combiner::Threshold { threshold, class } = &self.combiner {
+            let class_votes = votes.remove(class.as_str()).unwrap_or(0.0);
+            if class_votes >= *threshold as f64 {
+                return Some(EnsemblePrediction {
+                    output: Output::Categorical(class.to_owned()),
+                    confidence: Some(class_votes / nodes.len() as f64),
+                });
+            }
+        }
+
+        // Pick the class with the most votes, breaking ties by name so that
+        // our results are deterministic.
+        let total = votes.values().sum::<f64>();
+        let (class, weight) = votes.into_iter().max_by(|(c1, w1), (c2, w2)| {
+            w1.partial_cmp(w2)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| c2.cmp(c1))
+        })?;
+        Some(EnsemblePrediction {
+            output: Output::Categorical(class.to_owned()),
+            confidence: if total > 0.0 {
+                Some(weight / total)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// How many models should we download at once?
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+#[test]
+fn local_ensemble_combiners() {
+    use serde_json::json;
+
+    let tree = |output: &str, confidence: f64, categories: Value| -> TreeNode {
+        serde_json::from_value(json!({
+            "children": [
+                {
+                    "confidence": confidence,
+                    "count": 10,
+                    "id": 1,
+                    "objective_summary": { "categories": categories },
+                    "output": output,
+                    "predicate": { "field": "000000", "operator": "<=", "value": 5 }
+                },
+                {
+                    "confidence": 0.9,
+                    "count": 10,
+                    "id": 2,
+                    "output": "other",
+                    "predicate": { "field": "000000", "operator": ">", "value": 5 }
+                }
+            ],
+            "count": 20,
+            "id": 0,
+            "output": "other",
+            "predicate": true
+        }))
+        .unwrap()
+    };
+    let trees = vec![
+        tree("a", 0.3, json!([["a", 6], ["b", 4]])),
+        tree("a", 0.3, json!([["a", 6], ["b", 4]])),
+        tree("b", 0.9, json!([["b", 10]])),
+    ];
+    let ensemble = |combiner| LocalEnsemble {
+        trees: trees.clone(),
+        combiner,
+    };
+    let mut input = HashMap::new();
+    input.insert("000000".to_owned(), json!(1));
+    let predict = |combiner| {
+        let prediction = ensemble(combiner).predict(&input).unwrap();
+        (
+            prediction.output.to_string(),
+            prediction.confidence.unwrap(),
+        )
+    };
+
+    let (class, confidence) = predict(Combiner::Plurality);
+    assert_eq!(class, "a");
+    assert!((confidence - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(predict(Combiner::ConfidenceWeighted).0, "b");
+    assert_eq!(predict(Combiner::ProbabilityWeighted).0, "b");
+    let threshold = |threshold| Combiner::Threshold {
+        threshold,
+        class: "b".to_owned(),
+    };
+    assert_eq!(predict(threshold(1)).0, "b");
+    assert_eq!(predict(threshold(2)).0, "a");
+
+    let predictions = ensemble(Combiner::Plurality).predict_batch(&[input.clone()]);
+    assert_eq!(predictions.len(), 1);
+}
+
+#[test]
+fn deserialize_boosted_ensemble() {
+    use serde_json::json;
+
+    let ensemble: Ensemble = serde_json::from_value(json!({
+        "category": 0,
+        "code": 201,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "ensemble/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The ensemble has been created" },
+        "dataset": "dataset/5e5d87f6e476845bd8000001",
+        "objective_field": "000004",
+        "boosting": {
+            "iterations": 20,
+            "learning_rate": 0.05,
+            "early_out_of_bag": true,
+        },
+        "number_of_models": 1,
+        "ensemble": {
+            "fields": {
+                "000000": { "name": "petal length", "optype": "numeric" },
+            },
+        },
+        "importance": { "000000": 0.75, "000001": 0.25 },
+        "models": ["model/5e5d87f6e476845bd8000002"],
+    }))
+    .unwrap();
+    assert!(ensemble.is_boosted());
+    let boosting = ensemble.boosting.as_ref().unwrap();
+    assert_eq!(boosting.iterations, Some(20));
+    assert_eq!(boosting.early_out_of_bag, Some(true));
+    assert_eq!(ensemble.models.len(), 1);
+    assert_eq!(ensemble.importance["000000"], 0.75);
+    assert_eq!(
+        ensemble.ensemble.unwrap().fields["000000"].name,
+        "petal length"
+    );
+
+    let dataset: Id<Dataset> = "dataset/5e5d87f6e476845bd8000001".parse().unwrap();
+    let args = Args::boosted_trees(
+        dataset,
+        Boosting::default().iterations(20).learning_rate(0.05),
+    );
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        json!({
+            "dataset": "dataset/5e5d87f6e476845bd8000001",
+            "boosting": { "iterations": 20, "learning_rate": 0.05 },
+        }),
+    );
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn local_ensembles_reject_boosted_trees() {
+    use crate::mock::MockServer;
+    use serde_json::json;
+
+    let server = MockServer::start().await;
+    let id: Id<Ensemble> = "ensemble/5e5d87f6e476845bd8000000".parse().unwrap();
+    server
+        .mount_fetch(
+            &id,
+            json!({
+                "category": 0,
+                "code": 200,
+                "description": "",
+                "name": "boosted",
+                "shared": false,
+                "subscription": false,
+                "tags": [],
+                "resource": id.as_str(),
+                "status": { "code": 5, "message": "The ensemble has been created" },
+                "boosting": { "iterations": 10 },
+                "models": ["model/5e5d87f6e476845bd8000001"],
+            }),
+        )
+        .await;
+    let client = server.client().unwrap();
+    let err = LocalEnsemble::fetch(&client, &id, Combiner::Plurality)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("boosted trees"));
 }
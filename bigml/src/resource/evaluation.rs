@@ -1,47 +1,139 @@
 //! An evaluation of how well a model (or ensemble) predicts the data.
 
-use serde::de::DeserializeOwned;
-use serde::{Deserialize, Serialize};
-use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
+use std::fmt::Write;
 
 use super::id::*;
+use super::prediction::Predictor;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
 
 /// An evaluation of how well a model (or ensemble) predicts the data.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
-#[serde(bound(deserialize = ""))]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "evaluation"]
 #[non_exhaustive]
-pub struct Evaluation<R: Result> {
+pub struct Evaluation {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
-    pub resource: Id<Evaluation<R>>,
+    pub resource: Id<Evaluation>,
 
     /// The status of this resource.
     pub status: GenericStatus,
 
+    /// The dataset used to evaluate our model.
+    pub dataset: Option<Id<Dataset>>,
+
     /// The result of this evaluation.
-    pub result: R,
+    pub result: EvaluationResult,
 }
 
-/// The result of an evaluation.
-///
-/// TODO: I'm not sure we want to shadow `Result`.  But this name will
-/// basically always be qualified, so maybe it's OK.
-pub trait Result:
-    fmt::Debug + DeserializeOwned + Send + Serialize + Sized + Sync + 'static
-{
+/// The result of an evaluation, which depends on the type of model being
+/// evaluated. When deserializing, we look at the shape of the JSON to decide
+/// which variant to use.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum EvaluationResult {
+    /// The result of evaluating a classifier.
+    Classification(ClassificationResult),
+    /// The result of evaluating a regression model.
+    Regression(RegressionResult),
+}
+
+impl EvaluationResult {
+    /// Get this result if it was a classification result.
+    pub fn as_classification(&self) -> Option<&ClassificationResult> {
+        match self {
+            EvaluationResult::Classification(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Get this result if it was a regression result.
+    pub fn as_regression(&self) -> Option<&RegressionResult> {
+        match self {
+            EvaluationResult::Regression(result) => Some(result),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EvaluationResult {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        // Only classification results have `class_names`, and only regression
+        // results have `mean`. We check explicitly so that we can report
+        // useful errors, instead of the unhelpful errors generated by
+        // `#[serde(untagged)]`.
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if value.get("class_names").is_some() {
+            ClassificationResult::deserialize(value)
+                .map(EvaluationResult::Classification)
+                .map_err(|e| D::Error::custom(format!("classification result: {}", e)))
+        } else if value.get("mean").is_some() {
+            RegressionResult::deserialize(value)
+                .map(EvaluationResult::Regression)
+                .map_err(|e| D::Error::custom(format!("regression result: {}", e)))
+        } else {
+            Err(D::Error::custom(
+                "expected evaluation result with either `class_names` or `mean`",
+            ))
+        }
+    }
+}
+
+/// Arguments used to create an evaluation.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Evaluation")]
+#[non_exhaustive]
+pub struct Args {
+    /// The model or ensemble to evaluate.
+    #[serde(flatten)]
+    pub predictor: Predictor,
+
+    /// The dataset to use for the evaluation. This should normally not
+    /// include any of the data used to train the model.
+    pub dataset: Id<Dataset>,
+
+    /// The name of this evaluation.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new<P: Into<Predictor>>(predictor: P, dataset: Id<Dataset>) -> Args {
+        Args {
+            predictor: predictor.into(),
+            dataset,
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
 }
 
 /// The result of evaluating a classifier.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct ClassificationResult {
     /// The names of our classifications.
@@ -59,10 +151,8 @@ pub struct ClassificationResult {
     pub random: DetailedClassificationResult,
 }
 
-impl Result for ClassificationResult {}
-
 /// The detailed result of an evaluation using specific criteria.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct DetailedClassificationResult {
     /// The portion of instances we classified correctly.
@@ -75,14 +165,112 @@ pub struct DetailedClassificationResult {
     pub average_precision: f64,
     /// The average recall over all classes.
     pub average_recall: f64,
-    /// A list of rows of the confusion matrix for this model.
+    /// A list of rows of the confusion matrix for this model. Each row
+    /// corresponds to an actual class, and each column to a predicted class,
+    /// in the same order as `ClassificationResult::class_names`.
     pub confusion_matrix: Vec<Vec<f64>>,
     /// Statistics for each of the individidual classes.
     pub per_class_statistics: Vec<ClassificationPerClassStatistics>,
 }
 
+impl DetailedClassificationResult {
+    /// The number of instances of class `actual` which we predicted as
+    /// `predicted`. `class_names` should come from our `ClassificationResult`.
+    pub fn confusion_count(
+        &self,
+        class_names: &[String],
+        actual: &str,
+        predicted: &str,
+    ) -> Option<f64> {
+        let row = class_index(class_names, actual)?;
+        let column = class_index(class_names, predicted)?;
+        self.confusion_matrix.get(row)?.get(column).copied()
+    }
+
+    /// The confusion matrix with each row divided by its total, so that each
+    /// entry is the fraction of an actual class predicted as each class.
+    /// Rows with no instances are left as zeros.
+    pub fn normalized_confusion_matrix(&self) -> Vec<Vec<f64>> {
+        self.confusion_matrix
+            .iter()
+            .map(|row| {
+                let total = row.iter().sum::<f64>();
+                row.iter()
+                    .map(|&count| if total > 0.0 { count / total } else { 0.0 })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Derive the true and false positives and negatives for `class`, treating
+    /// it as the positive class and all other classes as negative.
+    pub fn class_counts(
+        &self,
+        class_names: &[String],
+        class: &str,
+    ) -> Option<ClassCounts> {
+        let index = class_index(class_names, class)?;
+        let mut counts = ClassCounts::default();
+        for (row, counts_row) in self.confusion_matrix.iter().enumerate() {
+            for (column, &count) in counts_row.iter().enumerate() {
+                match (row == index, column == index) {
+                    (true, true) => counts.true_positives += count,
+                    (false, true) => counts.false_positives += count,
+                    (true, false) => counts.false_negatives += count,
+                    (false, false) => counts.true_negatives += count,
+                }
+            }
+        }
+        Some(counts)
+    }
+
+    /// Render our confusion matrix as a Markdown table, which is also
+    /// readable as plain text. Rows are actual classes, and columns are
+    /// predicted classes.
+    pub fn confusion_matrix_markdown(&self, class_names: &[String]) -> String {
+        let mut out = String::new();
+        out.push_str("| actual \\ predicted |");
+        for name in class_names {
+            write!(out, " {} |", name).expect("could not write to String");
+        }
+        out.push_str("\n|---|");
+        for _ in class_names {
+            out.push_str("---:|");
+        }
+        out.push('\n');
+        for (name, row) in class_names.iter().zip(&self.confusion_matrix) {
+            write!(out, "| {} |", name).expect("could not write to String");
+            for count in row {
+                write!(out, " {} |", count).expect("could not write to String");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Find the position of `class` in `class_names`.
+fn class_index(class_names: &[String], class: &str) -> Option<usize> {
+    class_names.iter().position(|name| name == class)
+}
+
+/// Counts derived from a confusion matrix by treating one class as positive
+/// and all the others as negative.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ClassCounts {
+    /// Instances of our class which we predicted correctly.
+    pub true_positives: f64,
+    /// Instances of other classes which we predicted as our class.
+    pub false_positives: f64,
+    /// Instances of our class which we predicted as other classes.
+    pub false_negatives: f64,
+    /// Instances of other classes which we didn't predict as our class.
+    pub true_negatives: f64,
+}
+
 /// The detailed result of an evaluation using specific criteria.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct ClassificationPerClassStatistics {
     /// The portion of instances in this class that were correctly
@@ -100,6 +288,296 @@ pub struct ClassificationPerClassStatistics {
     /// The number of true positives over the number of actual positives in
     /// the dataset. (TP / (TP + FN))
     pub recall: f64,
+    /// The area under the ROC curve, as computed by BigML.
+    pub area_under_roc_curve: Option<f64>,
+    /// Points on the ROC curve for this class. Only included for some model
+    /// types.
+    #[serde(default)]
+    pub roc_curve: Vec<RocPoint>,
+    /// The confusion matrix for this class at each probability threshold.
+    /// Only included for some model types.
+    #[serde(default)]
+    pub per_threshold_confusion_matrices: Vec<ThresholdConfusionMatrix>,
+}
+
+impl ClassificationPerClassStatistics {
+    /// The points on our ROC curve, sorted by false positive rate. If BigML
+    /// didn't include `roc_curve`, we compute it from
+    /// `per_threshold_confusion_matrices`.
+    pub fn roc_points(&self) -> Vec<RocPoint> {
+        let mut points = if self.roc_curve.is_empty() {
+            self.per_threshold_confusion_matrices
+                .iter()
+                .map(ThresholdConfusionMatrix::roc_point)
+                .collect()
+        } else {
+            self.roc_curve.clone()
+        };
+        points.sort_by(|p1, p2| {
+            p1.false_positive_rate
+                .partial_cmp(&p2.false_positive_rate)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    p1.true_positive_rate
+                        .partial_cmp(&p2.true_positive_rate)
+                        .unwrap_or(Ordering::Equal)
+                })
+        });
+        points
+    }
+
+    /// Compute the area under our ROC curve locally, using the trapezoidal
+    /// rule. The curve is extended to `(0, 0)` and `(1, 1)`. Returns `None`
+    /// if we have no ROC data.
+    pub fn roc_auc(&self) -> Option<f64> {
+        let points = self.roc_points();
+        if points.is_empty() {
+            return None;
+        }
+        let mut area = 0.0;
+        let mut previous = (0.0, 0.0);
+        let curve = points
+            .iter()
+            .map(|p| (p.false_positive_rate, p.true_positive_rate))
+            .chain(Some((1.0, 1.0)));
+        for (fpr, tpr) in curve {
+            area += (fpr - previous.0) * (tpr + previous.1) / 2.0;
+            previous = (fpr, tpr);
+        }
+        Some(area)
+    }
+}
+
+/// A point on an ROC curve. BigML represents these as
+/// `[false_positive_rate, true_positive_rate, threshold]`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "(f64, f64, f64)", into = "(f64, f64, f64)")]
+#[non_exhaustive]
+pub struct RocPoint {
+    /// The fraction of negative instances predicted as positive.
+    pub false_positive_rate: f64,
+    /// The fraction of positive instances predicted as positive.
+    pub true_positive_rate: f64,
+    /// The probability threshold at which we predict the positive class.
+    pub threshold: f64,
+}
+
+impl From<(f64, f64, f64)> for RocPoint {
+    fn from(
+        (false_positive_rate, true_positive_rate, threshold): (f64, f64, f64),
+    ) -> Self {
+        RocPoint {
+            false_positive_rate,
+            true_positive_rate,
+            threshold,
+        }
+    }
+}
+
+impl From<RocPoint> for (f64, f64, f64) {
+    fn from(point: RocPoint) -> Self {
+        (
+            point.false_positive_rate,
+            point.true_positive_rate,
+            point.threshold,
+        )
+    }
+}
+
+/// The confusion matrix for a single class at a single probability
+/// threshold. BigML represents these as `[[tp, fp, tn, fn], threshold]`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "([f64; 4], f64)", into = "([f64; 4], f64)")]
+#[non_exhaustive]
+pub struct ThresholdConfusionMatrix {
+    /// The probability threshold at which we predict the positive class.
+    pub threshold: f64,
+    /// Instances of our class which we predicted correctly.
+    pub true_positives: f64,
+    /// Instances of other classes which we predicted as our class.
+    pub false_positives: f64,
+    /// Instances of other classes which we didn't predict as our class.
+    pub true_negatives: f64,
+    /// Instances of our class which we predicted as other classes.
+    pub false_negatives: f64,
+}
+
+impl ThresholdConfusionMatrix {
+    /// The point on the ROC curve corresponding to this threshold.
+    pub fn roc_point(&self) -> RocPoint {
+        let rate = |numerator: f64, other: f64| {
+            if numerator + other > 0.0 {
+                numerator / (numerator + other)
+            } else {
+                0.0
+            }
+        };
+        RocPoint {
+            false_positive_rate: rate(self.false_positives, self.true_negatives),
+            true_positive_rate: rate(self.true_positives, self.false_negatives),
+            threshold: self.threshold,
+        }
+    }
+}
+
+impl From<([f64; 4], f64)> for ThresholdConfusionMatrix {
+    fn from(([tp, fp, tn, fn_], threshold): ([f64; 4], f64)) -> Self {
+        ThresholdConfusionMatrix {
+            threshold,
+            true_positives: tp,
+            false_positives: fp,
+            true_negatives: tn,
+            false_negatives: fn_,
+        }
+    }
+}
+
+impl From<ThresholdConfusionMatrix> for ([f64; 4], f64) {
+    fn from(m: ThresholdConfusionMatrix) -> Self {
+        (
+            [
+                m.true_positives,
+                m.false_positives,
+                m.true_negatives,
+                m.false_negatives,
+            ],
+            m.threshold,
+        )
+    }
+}
+
+/// The result of evaluating a regression model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RegressionResult {
+    /// According to BigML, "Measures the performance of the regression that
+    /// predicts the mean for all the instances in the dataset."
+    pub mean: DetailedRegressionResult,
+
+    /// The performance of this model.
+    pub model: DetailedRegressionResult,
+
+    /// According to BigML, "Measures the performance of the regression that
+    /// predicts a random value for all the instances in the dataset."
+    pub random: DetailedRegressionResult,
+}
+
+/// The detailed result of a regression evaluation using specific criteria.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct DetailedRegressionResult {
+    /// The mean of the absolute values of our errors.
+    pub mean_absolute_error: f64,
+    /// The mean of the squares of our errors.
+    pub mean_squared_error: f64,
+    /// The coefficient of determination.
+    pub r_squared: f64,
+}
+
+#[test]
+fn deserialize_regression_result() {
+    let json = r#"{
+        "mean": {
+            "mean_absolute_error": 0.65,
+            "mean_squared_error": 0.67,
+            "r_squared": 0
+        },
+        "model": {
+            "mean_absolute_error": 0.15,
+            "mean_squared_error": 0.04,
+            "r_squared": 0.94
+        },
+        "random": {
+            "mean_absolute_error": 1.02,
+            "mean_squared_error": 1.6,
+            "r_squared": -1.39
+        }
+    }"#;
+    let result: EvaluationResult = serde_json::from_str(json).unwrap();
+    let regression = result.as_regression().unwrap();
+    assert!((regression.model.r_squared - 0.94).abs() < 1e-9);
+    assert!(result.as_classification().is_none());
 }
 
-// TODO: RegressionResult.
+#[test]
+fn deserialize_unknown_result_fails_clearly() {
+    let err = serde_json::from_str::<EvaluationResult>("{}").unwrap_err();
+    assert!(err.to_string().contains("class_names"));
+}
+
+#[test]
+fn confusion_matrix_utilities() {
+    let result: DetailedClassificationResult =
+        serde_json::from_value(serde_json::json!({
+            "accuracy": 0.8,
+            "average_f_measure": 0.8,
+            "average_phi": 0.6,
+            "average_precision": 0.8,
+            "average_recall": 0.8,
+            "confusion_matrix": [[3, 1], [1, 5]],
+            "per_class_statistics": [],
+        }))
+        .unwrap();
+    let names = vec!["no".to_owned(), "yes".to_owned()];
+    assert_eq!(result.confusion_count(&names, "no", "yes"), Some(1.0));
+    assert_eq!(result.confusion_count(&names, "maybe", "yes"), None);
+    assert_eq!(
+        result.normalized_confusion_matrix(),
+        vec![vec![0.75, 0.25], vec![1.0 / 6.0, 5.0 / 6.0]],
+    );
+    let counts = result.class_counts(&names, "yes").unwrap();
+    assert_eq!(
+        (
+            counts.true_positives,
+            counts.false_positives,
+            counts.false_negatives,
+            counts.true_negatives,
+        ),
+        (5.0, 1.0, 1.0, 3.0),
+    );
+    assert_eq!(
+        result.confusion_matrix_markdown(&names),
+        "| actual \\ predicted | no | yes |\n\
+         |---|---:|---:|\n\
+         | no | 3 | 1 |\n\
+         | yes | 1 | 5 |\n",
+    );
+}
+
+#[test]
+fn compute_roc_auc_locally() {
+    let stats = |extra: serde_json::Value| {
+        let mut json = serde_json::json!({
+            "accuracy": 0.9,
+            "class_name": "yes",
+            "f_measure": 0.9,
+            "phi_coefficient": 0.8,
+            "precision": 0.9,
+            "recall": 0.9,
+        });
+        json.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value::<ClassificationPerClassStatistics>(json).unwrap()
+    };
+
+    let from_curve = stats(serde_json::json!({
+        "area_under_roc_curve": 0.75,
+        "roc_curve": [[0.5, 1.0, 0.2], [0.0, 0.5, 0.8]],
+    }));
+    assert_eq!(from_curve.roc_points()[0].threshold, 0.8);
+    assert!((from_curve.roc_auc().unwrap() - 0.875).abs() < 1e-9);
+
+    let from_matrices = stats(serde_json::json!({
+        "per_threshold_confusion_matrices": [
+            [[5, 0, 5, 5], 0.8],
+            [[10, 5, 0, 0], 0.2],
+        ],
+    }));
+    let points = from_matrices.roc_points();
+    assert_eq!(points[0].true_positive_rate, 0.5);
+    assert_eq!(points[1].false_positive_rate, 1.0);
+    assert!((from_matrices.roc_auc().unwrap() - 0.75).abs() < 1e-9);
+
+    assert_eq!(stats(serde_json::json!({})).roc_auc(), None);
+}
@@ -3,6 +3,7 @@ use serde::de;
 use serde::de::DeserializeOwned;
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
 
@@ -10,31 +11,63 @@ use super::Execution;
 use crate::errors::*;
 use crate::resource;
 use crate::resource::id::*;
-use crate::resource::Script;
+use crate::resource::{Project, Resource, Script};
 
 /// Arguments for creating a script execution.
-///
-/// TODO: Lots of missing fields.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[bigml_args]
+#[derive(Args, Clone, Debug, Default, Deserialize, Serialize)]
+#[args(resource = "Execution")]
 #[non_exhaustive]
 pub struct Args {
     /// The ID of the script to run.
     pub script: Option<Id<Script>>,
 
+    /// Several scripts to run one after another, instead of `script`. Each
+    /// script receives the outputs of the previous one as inputs.
+    #[serde(default)]
+    pub scripts: Vec<Id<Script>>,
+
     /// A nice name for the execution.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// A human-readable description of this execution.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// The category code which best describes this execution.
+    #[serde(default)]
+    pub category: Option<i64>,
+
+    /// Default arguments for resources created by our script, keyed by
+    /// resource type (such as `"model"`) and then by argument name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub creation_defaults: HashMap<String, HashMap<String, serde_json::Value>>,
+
+    /// Field IDs or names which our script should never use as an objective
+    /// field.
+    #[serde(default)]
+    pub illegal_objective_fields: Vec<String>,
+
+    /// The priority of this execution relative to other executions in the
+    /// same account. Higher values run first.
+    #[serde(default)]
+    pub priority: Option<i64>,
+
+    /// A free-form string describing where this execution came from, such
+    /// as the name of the tool which created it.
+    #[serde(default)]
+    pub origin: Option<String>,
+
     /// Inputs to our script.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub inputs: Vec<(String, serde_json::Value)>,
 
     /// Outputs to place into the `result` field of our `Data`.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub outputs: Vec<String>,
 
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
     /// User-defined tags.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 }
 
@@ -52,6 +85,79 @@ impl Args {
         self.name = Some(name.into());
     }
 
+    /// Set the description of the execution.
+    pub fn set_description<S>(&mut self, description: S) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the category code of the execution.
+    pub fn set_category(&mut self, category: i64) -> &mut Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Set the project to put this execution in.
+    pub fn set_project(&mut self, project: Id<Project>) -> &mut Self {
+        self.project = Some(project);
+        self
+    }
+
+    /// Add a user-defined tag.
+    pub fn add_tag<S>(&mut self, tag: S) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Use `value` as the default for argument `name` whenever our script
+    /// creates a resource of type `R`.
+    pub fn creation_default<R, S>(
+        &mut self,
+        name: S,
+        value: serde_json::Value,
+    ) -> &mut Self
+    where
+        R: Resource,
+        S: Into<String>,
+    {
+        let resource_type = R::api_name().to_owned();
+        self.creation_defaults
+            .entry(resource_type)
+            .or_default()
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Forbid our script from using `field` as an objective field.
+    pub fn illegal_objective_field<S>(&mut self, field: S) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.illegal_objective_fields.push(field.into());
+        self
+    }
+
+    /// Set the priority of the execution.
+    pub fn set_priority(&mut self, priority: i64) -> &mut Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Record where this execution came from.
+    pub fn set_origin<S>(&mut self, origin: S) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.origin = Some(origin.into());
+        self
+    }
+
     /// Add a named input to our script.
     pub fn add_input<S, V>(&mut self, name: S, value: V) -> Result<()>
     where
@@ -67,6 +173,77 @@ impl Args {
         Ok(())
     }
 
+    /// Add a named input containing a resource ID, such as a dataset.
+    pub fn input_resource<S, R>(&mut self, name: S, id: &Id<R>) -> &mut Self
+    where
+        S: Into<String>,
+        R: Resource,
+    {
+        self.input_json(name, serde_json::Value::String(id.as_str().to_owned()))
+    }
+
+    /// Add a named input containing a list of resource IDs.
+    pub fn input_resource_list<S, R>(&mut self, name: S, ids: &[Id<R>]) -> &mut Self
+    where
+        S: Into<String>,
+        R: Resource,
+    {
+        let ids = ids
+            .iter()
+            .map(|id| serde_json::Value::String(id.as_str().to_owned()))
+            .collect();
+        self.input_json(name, serde_json::Value::Array(ids))
+    }
+
+    /// Add a named string input.
+    pub fn input_str<S, V>(&mut self, name: S, value: V) -> &mut Self
+    where
+        S: Into<String>,
+        V: Into<String>,
+    {
+        self.input_json(name, serde_json::Value::String(value.into()))
+    }
+
+    /// Add a named numeric input. Values which are not finite will be
+    /// omitted, because JSON cannot represent them.
+    pub fn input_number<S>(&mut self, name: S, value: f64) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        let value = serde_json::Number::from_f64(value)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null);
+        self.input_json(name, value)
+    }
+
+    /// Add a named integer input.
+    pub fn input_integer<S>(&mut self, name: S, value: i64) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.input_json(name, serde_json::Value::from(value))
+    }
+
+    /// Add a named boolean input.
+    pub fn input_bool<S>(&mut self, name: S, value: bool) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        self.input_json(name, serde_json::Value::Bool(value))
+    }
+
+    /// Add a named input containing arbitrary JSON, such as a list or a map.
+    /// `null` values will be omitted, because WhizzML does not allow them.
+    pub fn input_json<S>(&mut self, name: S, value: serde_json::Value) -> &mut Self
+    where
+        S: Into<String>,
+    {
+        if !value.is_null() {
+            self.inputs.push((name.into(), value));
+        }
+        self
+    }
+
     /// Add a named output parameter that we want place into `result`.
     pub fn add_output<S>(&mut self, name: S)
     where
@@ -76,8 +253,59 @@ impl Args {
     }
 }
 
-impl resource::Args for Args {
-    type Resource = Execution;
+#[test]
+fn typed_inputs() {
+    use resource::Dataset;
+
+    let dataset: Id<Dataset> = "dataset/5bd0e4a1fd4d9f1cbd000000".parse().unwrap();
+    let mut args = Args::default();
+    args.input_resource("dataset", &dataset)
+        .input_resource_list("datasets", std::slice::from_ref(&dataset))
+        .input_str("name", "example")
+        .input_number("rate", 0.5)
+        .input_number("nan", f64::NAN)
+        .input_integer("count", 3)
+        .input_bool("verbose", true)
+        .input_json("missing", serde_json::Value::Null);
+    assert_eq!(
+        serde_json::to_value(&args.inputs).unwrap(),
+        serde_json::json!([
+            ["dataset", "dataset/5bd0e4a1fd4d9f1cbd000000"],
+            ["datasets", ["dataset/5bd0e4a1fd4d9f1cbd000000"]],
+            ["name", "example"],
+            ["rate", 0.5],
+            ["count", 3],
+            ["verbose", true],
+        ]),
+    );
+}
+
+#[test]
+fn serialize_creation_options() {
+    use resource::Model;
+
+    let project: Id<Project> = "project/5bd0e4a1fd4d9f1cbd000000".parse().unwrap();
+    let script: Id<Script> = "script/5bd0e4a1fd4d9f1cbd000001".parse().unwrap();
+    let mut args = Args::default();
+    args.set_script(script);
+    args.set_project(project)
+        .add_tag("nightly")
+        .creation_default::<Model, _>("missing_splits", serde_json::json!(true))
+        .illegal_objective_field("id")
+        .set_priority(2)
+        .set_origin("example");
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "script": "script/5bd0e4a1fd4d9f1cbd000001",
+            "creation_defaults": { "model": { "missing_splits": true } },
+            "illegal_objective_fields": ["id"],
+            "priority": 2,
+            "origin": "example",
+            "project": "project/5bd0e4a1fd4d9f1cbd000000",
+            "tags": ["nightly"],
+        }),
+    );
 }
 
 /// A named output value from an execution.
@@ -190,14 +418,14 @@ fn deserialize_output_with_name_and_value_but_no_type() {
 
 #[test]
 fn deserialize_output_with_everything() {
-    use resource::evaluation::{ClassificationResult, Evaluation};
+    use resource::evaluation::Evaluation;
 
     let json =
         r#"["evaluation", "evaluation/50650d563c19202679000000", "evaluation"]"#;
     let output: Output = serde_json::from_str(&json).unwrap();
 
     assert_eq!(output.name, "evaluation");
-    let value: Id<Evaluation<ClassificationResult>> = output.get().unwrap();
+    let value: Id<Evaluation> = output.get().unwrap();
     assert_eq!(value.as_str(), "evaluation/50650d563c19202679000000");
     assert_eq!(output.type_.unwrap(), "evaluation");
 }
@@ -255,6 +483,16 @@ pub enum LogLevel {
     Error,
 }
 
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevel::Info => write!(f, "info"),
+            LogLevel::Warning => write!(f, "warning"),
+            LogLevel::Error => write!(f, "error"),
+        }
+    }
+}
+
 /// A log entry output by the script.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
@@ -271,6 +509,26 @@ pub struct LogEntry {
     pub message: String,
 }
 
+impl LogEntry {
+    /// Is this entry at `level` or more severe?
+    pub fn is_at_level(&self, level: LogLevel) -> bool {
+        self.log_level >= level
+    }
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} (line {}): {}",
+            self.timestamp.to_rfc3339(),
+            self.log_level,
+            self.line_number,
+            self.message,
+        )
+    }
+}
+
 impl<'de> Deserialize<'de> for LogEntry {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -351,6 +609,19 @@ fn deserialize_serialize_log_entry() {
     assert_eq!(ser_json, json);
 }
 
+#[test]
+fn filter_log_entries_by_level() {
+    let json = r#"["warning","2016-04-17T01:13:30.713Z",0,30,"few rows"]"#;
+    let entry: LogEntry = serde_json::from_str(json).unwrap();
+    assert!(entry.is_at_level(LogLevel::Info));
+    assert!(entry.is_at_level(LogLevel::Warning));
+    assert!(!entry.is_at_level(LogLevel::Error));
+    assert_eq!(
+        entry.to_string(),
+        "2016-04-17T01:13:30.713+00:00 warning (line 30): few rows",
+    );
+}
+
 /// A resource created by the script.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -374,3 +645,70 @@ pub struct OutputResource {
     /// This appears to be a textual representation of a `StatusCode`.
     pub state: String,
 }
+
+impl OutputResource {
+    /// Parse `id`, so it can be matched by type or converted to an `Id<R>`.
+    /// This fails if BigML returned a resource type we don't support.
+    pub fn resource_id(&self) -> Result<AnyResourceId> {
+        self.id.parse()
+    }
+
+    /// Get `id` as an `Id<R>`, or `None` if this isn't a resource of type
+    /// `R`.
+    pub fn as_id<R: Resource>(&self) -> Option<Id<R>> {
+        self.id.parse().ok()
+    }
+
+    /// Get `id` if this is a source.
+    pub fn as_source_id(&self) -> Option<Id<resource::Source>> {
+        self.as_id()
+    }
+
+    /// Get `id` if this is a dataset.
+    pub fn as_dataset_id(&self) -> Option<Id<resource::Dataset>> {
+        self.as_id()
+    }
+
+    /// Get `id` if this is a model.
+    pub fn as_model_id(&self) -> Option<Id<resource::Model>> {
+        self.as_id()
+    }
+
+    /// Get `id` if this is an ensemble.
+    pub fn as_ensemble_id(&self) -> Option<Id<resource::Ensemble>> {
+        self.as_id()
+    }
+
+    /// Get `id` if this is an evaluation.
+    pub fn as_evaluation_id(&self) -> Option<Id<resource::Evaluation>> {
+        self.as_id()
+    }
+
+    /// Get `id` if this is a batch prediction.
+    pub fn as_batch_prediction_id(&self) -> Option<Id<resource::BatchPrediction>> {
+        self.as_id()
+    }
+}
+
+#[test]
+fn output_resource_ids_are_typed() {
+    let json = r#"{
+        "id": "dataset/5e5d87f6e476845bd8000000",
+        "variable": "ds",
+        "last_update": 1583122422000,
+        "progress": 1.0,
+        "task": "Done",
+        "state": "finished"
+    }"#;
+    let output: OutputResource = serde_json::from_str(json).unwrap();
+    assert_eq!(output.variable.as_deref(), Some("ds"));
+    assert!(matches!(
+        output.resource_id().unwrap(),
+        AnyResourceId::Dataset(_)
+    ));
+    assert_eq!(
+        output.as_dataset_id().unwrap().as_str(),
+        "dataset/5e5d87f6e476845bd8000000",
+    );
+    assert!(output.as_model_id().is_none());
+}
@@ -32,6 +32,9 @@ pub struct ExecutionStatus {
     /// The cause of the error.
     pub cause: Option<Cause>,
 
+    /// The BigML error code, if an error occurred.
+    pub error: Option<i64>,
+
     /// The number of milliseconds elapsed during different phases of execution.
     #[serde(default)]
     pub elapsed_times: HashMap<String, u64>,
@@ -50,14 +53,32 @@ pub struct ExecutionStatus {
 }
 
 impl ExecutionStatus {
-    /// The `message` for this status, plus the `cause` and any other useful
-    /// information that might be present.
+    /// The `message` for this status, plus the `cause`, the location of the
+    /// error in the WhizzML source code, and any other useful information
+    /// that might be present.
     pub fn full_message(&self) -> String {
+        let mut message = self.message.clone();
         if let Some(ref cause) = self.cause {
-            format!("{} ({})", self.message, cause)
-        } else {
-            self.message.clone()
+            message.push_str(&format!(" ({})", cause));
+        }
+        let location = self
+            .instruction
+            .as_ref()
+            .map(|instruction| &instruction.source)
+            .or(self.source_location.as_ref());
+        if let Some(location) = location {
+            message.push_str(&format!(" at {}", location));
+        }
+        if let Some(ref call_stack) = self.call_stack {
+            if !call_stack.is_empty() {
+                let call_stack = call_stack
+                    .iter()
+                    .map(|location| location.to_string())
+                    .collect::<Vec<_>>();
+                message.push_str(&format!(" [call stack: {}]", call_stack.join("; ")));
+            }
         }
+        message
     }
 }
 
@@ -77,6 +98,10 @@ impl Status for ExecutionStatus {
     fn progress(&self) -> Option<f32> {
         self.progress
     }
+
+    fn full_message(&self) -> String {
+        ExecutionStatus::full_message(self)
+    }
 }
 
 /// Functions for (de)serializing WhizzML call stacks.
@@ -136,6 +161,16 @@ pub struct SourceLocation {
     pub origin: usize,
 }
 
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "source {}, line {}, column {}",
+            self.origin, self.lines.0, self.columns.0,
+        )
+    }
+}
+
 /// The cause of an error.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -180,5 +215,10 @@ fn deserialize_error_status() {
 
     let json = r#"{"call_stack": [[1, [32, 47], [15, 1]]], "cause": {"code": -1206, "extra": {"all_fields": "Must be true or false", "fields": "Must be an object"}, "http_status": 400}, "code": -1, "elapsed": 8896, "elapsed_times": {"in-progress": 8834, "queued": 22, "started": 62}, "error": -8200, "instruction": {"instruction": "apply", "source": {"columns": [15, 1], "lines": [32, 47], "origin": 1}}, "message": "Problem while executing script: Error handling resource (Validation error)", "progress": 0.195, "source_location": {"columns": [15, 1], "lines": [32, 47], "origin": 1}}"#;
     let status: ExecutionStatus = serde_json::from_str(json).unwrap();
+    assert_eq!(status.error, Some(-8200));
+    assert_eq!(
+        status.full_message(),
+        "Problem while executing script: Error handling resource (Validation error) (code: -1206, HTTP status: 400, extra: {\"all_fields\":\"Must be true or false\",\"fields\":\"Must be an object\"}) at source 1, line 32, column 15 [call stack: source 1, line 32, column 15]",
+    );
     assert_eq!(status.cause.unwrap().code, -1206);
 }
@@ -10,7 +10,7 @@ use url::Url;
 use super::id::*;
 use super::status::*;
 use super::{Library, Script};
-use super::{Resource, ResourceCommon};
+use super::{Resource, ResourceCommon, Updatable};
 use crate::client::Client;
 use crate::errors::*;
 
@@ -23,13 +23,14 @@ pub use self::execution_status::*;
 /// An execution of a WhizzML script.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "execution"]
 #[non_exhaustive]
 pub struct Execution {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -42,6 +43,47 @@ pub struct Execution {
     pub execution: Data,
 }
 
+impl Execution {
+    /// Summarize how far this execution has progressed.
+    pub fn progress(&self) -> ExecutionProgress {
+        ExecutionProgress {
+            id: self.resource.clone(),
+            code: self.status.code,
+            progress: self.status.progress,
+            message: self.status.message.clone(),
+            call_stack: self.status.call_stack.clone().unwrap_or_default(),
+            source_location: self.status.source_location.clone(),
+        }
+    }
+}
+
+/// How far an execution has progressed. This is passed to the callback
+/// specified using `Client::wait_for_execution_opt`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ExecutionProgress {
+    /// The ID of the execution.
+    pub id: Id<Execution>,
+
+    /// The status code of the execution.
+    pub code: StatusCode,
+
+    /// A number between 0.0 and 1.0 representing how far the execution has
+    /// progressed, if known.
+    pub progress: Option<f32>,
+
+    /// A human-readable status message.
+    pub message: String,
+
+    /// The WhizzML call stack, as reported by BigML. This will often be
+    /// empty.
+    pub call_stack: Vec<SourceLocation>,
+
+    /// The location in the WhizzML source that is currently executing, if
+    /// known.
+    pub source_location: Option<SourceLocation>,
+}
+
 /// Data about a script execution.
 ///
 /// TODO: Lots of missing fields.
@@ -77,7 +119,48 @@ impl Data {
                 return output.get();
             }
         }
-        Err(Error::could_not_get_output(name, format_err!("not found")))
+        let available = self
+            .outputs
+            .iter()
+            .map(|output| output.name.as_str())
+            .collect::<Vec<_>>();
+        Err(Error::could_not_get_output(
+            name,
+            format_err!("not found (available outputs: {})", available.join(", ")),
+        ))
+    }
+
+    /// Log entries at `level` or more severe. For example, passing
+    /// `LogLevel::Warning` returns both warnings and errors.
+    pub fn logs_at_level(&self, level: LogLevel) -> impl Iterator<Item = &LogEntry> {
+        self.logs
+            .iter()
+            .filter(move |entry| entry.is_at_level(level))
+    }
+
+    /// Deserialize the entire `result` of this execution.
+    pub fn result_as<D: DeserializeOwned>(&self) -> Result<D> {
+        let result = self.result.as_ref().ok_or_else(|| {
+            Error::could_not_get_output("result", Error::OutputNotAvailable)
+        })?;
+        serde_json::from_value(result.to_owned())
+            .map_err(|e| Error::could_not_get_output("result", e))
+    }
+
+    /// Deserialize part of the `result` of this execution, specified using a
+    /// [JSON pointer][pointer] like `"/models/0/id"`.
+    ///
+    /// [pointer]: https://tools.ietf.org/html/rfc6901
+    pub fn result_pointer<D: DeserializeOwned>(&self, pointer: &str) -> Result<D> {
+        let name = format!("result{}", pointer);
+        let result = self.result.as_ref().ok_or_else(|| {
+            Error::could_not_get_output(&name, Error::OutputNotAvailable)
+        })?;
+        let value = result.pointer(pointer).ok_or_else(|| {
+            Error::could_not_get_output(&name, format_err!("not found"))
+        })?;
+        serde_json::from_value(value.to_owned())
+            .map_err(|e| Error::could_not_get_output(&name, e))
     }
 }
 
@@ -228,3 +311,22 @@ impl Serialize for SourceId {
         }
     }
 }
+
+#[test]
+fn data_result_helpers() {
+    let data: Data = serde_json::from_value(serde_json::json!({
+        "outputs": [["answer", 42, "number"]],
+        "result": {"models": [{"id": "model/1"}]},
+    }))
+    .unwrap();
+    assert_eq!(data.get::<u64>("answer").unwrap(), 42);
+    let err = data.get::<u64>("question").unwrap_err();
+    assert!(err.to_string().contains("available outputs: answer"));
+    assert_eq!(
+        data.result_pointer::<String>("/models/0/id").unwrap(),
+        "model/1",
+    );
+    assert!(data.result_pointer::<String>("/models/1/id").is_err());
+    let result: serde_json::Value = data.result_as().unwrap();
+    assert_eq!(result["models"][0]["id"], "model/1");
+}
@@ -0,0 +1,150 @@
+//! https://bigml.com/api/externalconnectors
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::status::*;
+use super::{Project, Resource, ResourceCommon, Updatable};
+
+/// A connection to an external database, which can be used to create sources
+/// from SQL queries. See `source::Args::external_data`.
+///
+/// These can be fetched, listed, updated and deleted like any other
+/// resource.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "externalconnector"]
+#[non_exhaustive]
+pub struct ExternalConnector {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<ExternalConnector>,
+
+    /// The current status of this connector.
+    pub status: GenericStatus,
+
+    /// The kind of database we connect to.
+    pub source: ExternalSource,
+
+    /// How to connect to the database. BigML does not return passwords.
+    #[serde(default)]
+    pub connection: HashMap<String, serde_json::Value>,
+}
+
+/// The kinds of external database supported by BigML.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ExternalSource {
+    /// A PostgreSQL database.
+    #[serde(rename = "postgresql")]
+    PostgreSql,
+    /// A MySQL database.
+    #[serde(rename = "mysql")]
+    MySql,
+    /// A Microsoft SQL Server database.
+    #[serde(rename = "sqlserver")]
+    SqlServer,
+    /// An Elasticsearch cluster.
+    Elasticsearch,
+}
+
+/// How to connect to an external database.
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct Connection {
+    /// The host name of the database server.
+    pub host: String,
+
+    /// The port of the database server, if not the default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+
+    /// The name of the database.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+
+    /// The user to connect as.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    /// The password to connect with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// Should we connect using SSL?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssl: Option<bool>,
+}
+
+impl Connection {
+    /// Create a new `Connection` to `host`.
+    pub fn new<S: Into<String>>(host: S) -> Connection {
+        Connection {
+            host: host.into(),
+            ..Connection::default()
+        }
+    }
+}
+
+/// Arguments used to create an external connector.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "ExternalConnector")]
+#[non_exhaustive]
+pub struct Args {
+    /// The kind of database to connect to.
+    pub source: ExternalSource,
+
+    /// How to connect to the database.
+    pub connection: Connection,
+
+    /// The name of this connector.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value for connecting to a `source` database using
+    /// `connection`.
+    pub fn new(source: ExternalSource, connection: Connection) -> Args {
+        Args {
+            source,
+            connection,
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn serialize_args() {
+    let mut connection = Connection::new("db.example.com");
+    connection.database = Some("sales".to_owned());
+    connection.user = Some("bigml".to_owned());
+    let args = Args::new(ExternalSource::PostgreSql, connection);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "source": "postgresql",
+            "connection": {
+                "host": "db.example.com",
+                "database": "sales",
+                "user": "bigml",
+            },
+        }),
+    );
+}
@@ -0,0 +1,133 @@
+//! https://bigml.com/api/fusions
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::status::*;
+use super::{Project, Resource, ResourceCommon, Updatable};
+
+/// A fusion, which combines the predictions of several supervised models,
+/// possibly of different types. Fusions can be used to make predictions and
+/// batch predictions, just like models and ensembles.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "fusion"]
+#[non_exhaustive]
+pub struct Fusion {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Fusion>,
+
+    /// The current status of this fusion.
+    pub status: GenericStatus,
+
+    /// The models combined by this fusion.
+    #[serde(default)]
+    pub models: Vec<AnyResourceId>,
+
+    /// The weight of each model in `models`, if weights were specified.
+    #[serde(default)]
+    pub weights: Option<Vec<f64>>,
+}
+
+/// A model to include in a fusion.
+#[derive(Clone, Debug, Serialize)]
+#[non_exhaustive]
+pub struct FusionModel {
+    /// The ID of a model, ensemble, logistic regression or deepnet.
+    pub id: AnyResourceId,
+
+    /// How much weight to give this model's predictions. BigML defaults to
+    /// `1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+impl FusionModel {
+    /// Include the model `id` with the default weight.
+    pub fn new<I: Into<AnyResourceId>>(id: I) -> FusionModel {
+        FusionModel {
+            id: id.into(),
+            weight: None,
+        }
+    }
+
+    /// Include the model `id` with the specified `weight`.
+    pub fn weighted<I: Into<AnyResourceId>>(id: I, weight: f64) -> FusionModel {
+        FusionModel {
+            id: id.into(),
+            weight: Some(weight),
+        }
+    }
+}
+
+/// Arguments used to create a fusion.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Fusion")]
+#[non_exhaustive]
+pub struct Args {
+    /// The models to combine.
+    #[args(always_serialize)]
+    pub models: Vec<FusionModel>,
+
+    /// The name of this fusion.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value which combines `models`.
+    pub fn new<I>(models: I) -> Args
+    where
+        I: IntoIterator<Item = FusionModel>,
+    {
+        Args {
+            models: models.into_iter().collect(),
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn serialize_args() {
+    use super::{Deepnet, Ensemble};
+
+    let ensemble: Id<Ensemble> = "ensemble/5e5d87f6e476845bd8000000".parse().unwrap();
+    let deepnet: Id<Deepnet> = "deepnet/5e5d87f6e476845bd8000001".parse().unwrap();
+    let args = Args::new(vec![
+        FusionModel::new(ensemble),
+        FusionModel::weighted(deepnet, 2.0),
+    ]);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "models": [
+                { "id": "ensemble/5e5d87f6e476845bd8000000" },
+                { "id": "deepnet/5e5d87f6e476845bd8000001", "weight": 2.0 },
+            ],
+        }),
+    );
+}
+
+#[test]
+fn serialize_args_without_models() {
+    let args = Args::new(vec![]).with_tags(vec!["test"]);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({ "models": [], "tags": ["test"] }),
+    );
+}
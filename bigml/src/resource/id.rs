@@ -2,17 +2,23 @@
 
 use serde::de::Unexpected;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
 use std::str::FromStr;
 use url::Url;
 
-use super::Resource;
+use super::{
+    Association, BatchCentroid, BatchPrediction, BatchProjection, Centroid, Cluster,
+    Dataset, Deepnet, Ensemble, Evaluation, Execution, ExternalConnector, Fusion,
+    Library, LogisticRegression, Model, OptiML, Pca, Prediction, Project, Projection,
+    Resource, Sample, Script, Source, Updatable,
+};
 use crate::errors::*;
 
 /// A strongly-typed "resource ID" used to identify many different kinds of
 /// BigML resources.
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Id<R: Resource> {
     /// The ID of the resource.
     id: String,
@@ -29,6 +35,9 @@ impl<R: Resource> Id<R> {
     }
 
     /// Get a URL pointing at the human-readable version of this resource.
+    ///
+    /// This always points at `bigml.com`. If you use a different BigML
+    /// domain, use `Client::dashboard_url` instead.
     pub fn dashboard_url(&self) -> Url {
         Url::parse(&format!("https://bigml.com/dashboard/{}", self))
             // This should never fail to parse.
@@ -36,6 +45,27 @@ impl<R: Resource> Id<R> {
     }
 }
 
+/// Get a URL pointing at the human-readable version of the resource `id`,
+/// using the dashboard which corresponds to the API at `api_url`.
+///
+/// BigML's own API domains end in `bigml.io`, and the matching dashboards
+/// end in `bigml.com`. Private deployments serve the API and the dashboard
+/// from the same host.
+pub(crate) fn dashboard_url_for_api(api_url: &Url, id: &str) -> Url {
+    let mut url = api_url.clone();
+    if let Some(host) = api_url.host_str() {
+        if host == "bigml.io" || host.ends_with(".bigml.io") {
+            let host = format!("{}com", host.trim_end_matches("io"));
+            url.set_host(Some(&host))
+                .expect("dashboard host unexpectedly failed to parse");
+        }
+    }
+    url.set_path(&format!("/dashboard/{}", id));
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
 impl<R: Resource> FromStr for Id<R> {
     type Err = Error;
 
@@ -54,6 +84,38 @@ impl<R: Resource> FromStr for Id<R> {
     }
 }
 
+// We implement these traits manually, because `#[derive]` would require `R`
+// to implement them, too.
+
+impl<R: Resource> Clone for Id<R> {
+    fn clone(&self) -> Self {
+        Id {
+            id: self.id.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: Resource> PartialEq for Id<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<R: Resource> Eq for Id<R> {}
+
+impl<R: Resource> PartialOrd for Id<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R: Resource> Ord for Id<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
 impl<R: Resource> fmt::Debug for Id<R> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{}", &self.id)
@@ -97,3 +159,188 @@ impl<R: Resource> Serialize for Id<R> {
         self.id.serialize(serializer)
     }
 }
+
+/// IDs can only be updated as a whole.
+impl<R: Resource> Updatable for Id<R> {
+    type Update = Self;
+
+    fn to_update(&self) -> Self::Update {
+        self.clone()
+    }
+
+    fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+        if self == other {
+            None
+        } else {
+            Some(other.clone())
+        }
+    }
+}
+
+impl<R: Resource> TryFrom<AnyResourceId> for Id<R> {
+    type Error = Error;
+
+    fn try_from(id: AnyResourceId) -> Result<Self> {
+        id.as_str().parse()
+    }
+}
+
+/// Declare `AnyResourceId`, with one variant for each resource type.
+macro_rules! any_resource_id {
+    ($($variant:ident,)*) => {
+        /// The ID of a BigML resource of any supported type. This is useful
+        /// when BigML returns a mixture of resource types, such as in
+        /// [`Execution`]'s `output_resources`.
+        ///
+        /// To convert this to an `Id<R>`, use `Id::<R>::try_from`.
+        #[derive(Clone, Debug)]
+        #[non_exhaustive]
+        pub enum AnyResourceId {
+            $(
+                #[allow(missing_docs)]
+                $variant(Id<$variant>),
+            )*
+        }
+
+        impl AnyResourceId {
+            /// Get this resource as a string.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $( AnyResourceId::$variant(id) => id.as_str(), )*
+                }
+            }
+
+            /// Get a URL pointing at the human-readable version of this
+            /// resource. See `Id::dashboard_url`.
+            pub fn dashboard_url(&self) -> Url {
+                match self {
+                    $( AnyResourceId::$variant(id) => id.dashboard_url(), )*
+                }
+            }
+        }
+
+        impl FromStr for AnyResourceId {
+            type Err = Error;
+
+            fn from_str(id: &str) -> Result<Self> {
+                $(
+                    if id.starts_with($variant::id_prefix()) {
+                        return Ok(AnyResourceId::$variant(id.parse()?));
+                    }
+                )*
+                Err(Error::WrongResourceType {
+                    expected: "a supported BigML resource type",
+                    found: id.to_owned(),
+                })
+            }
+        }
+
+        $(
+            impl From<Id<$variant>> for AnyResourceId {
+                fn from(id: Id<$variant>) -> Self {
+                    AnyResourceId::$variant(id)
+                }
+            }
+        )*
+    };
+}
+
+any_resource_id! {
+    Association,
+    BatchCentroid,
+    BatchPrediction,
+    BatchProjection,
+    Centroid,
+    Cluster,
+    Dataset,
+    Deepnet,
+    Ensemble,
+    Evaluation,
+    Execution,
+    ExternalConnector,
+    Fusion,
+    Library,
+    LogisticRegression,
+    Model,
+    OptiML,
+    Pca,
+    Prediction,
+    Project,
+    Projection,
+    Sample,
+    Script,
+    Source,
+}
+
+impl fmt::Display for AnyResourceId {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyResourceId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let id: String = String::deserialize(deserializer)?;
+        id.parse().map_err(|_| {
+            <D::Error as serde::de::Error>::invalid_value(
+                Unexpected::Str(&id),
+                &"a BigML resource ID with a supported type",
+            )
+        })
+    }
+}
+
+impl Serialize for AnyResourceId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.as_str().serialize(serializer)
+    }
+}
+
+#[test]
+fn any_resource_id_parses_and_converts() {
+    let id: AnyResourceId = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    assert!(matches!(id, AnyResourceId::Dataset(_)));
+    assert_eq!(id.to_string(), "dataset/5e5d87f6e476845bd8000000");
+
+    let dataset = Id::<Dataset>::try_from(id.clone()).unwrap();
+    assert_eq!(dataset.as_str(), id.as_str());
+    assert!(Id::<Source>::try_from(id).is_err());
+
+    let id: AnyResourceId =
+        "batchprediction/5e5d87f6e476845bd8000000".parse().unwrap();
+    assert!(matches!(id, AnyResourceId::BatchPrediction(_)));
+
+    assert!("widget/5e5d87f6e476845bd8000000"
+        .parse::<AnyResourceId>()
+        .is_err());
+}
+
+#[test]
+fn dashboard_urls_match_api_domain() {
+    let id = "dataset/5e5d87f6e476845bd8000000";
+    let examples = &[
+        ("https://bigml.io/", "https://bigml.com/dashboard/"),
+        ("https://au.bigml.io/", "https://au.bigml.com/dashboard/"),
+        (
+            "https://bigml.example.com/andromeda/?username=x",
+            "https://bigml.example.com/dashboard/",
+        ),
+        ("http://127.0.0.1:8080/", "http://127.0.0.1:8080/dashboard/"),
+    ];
+    for &(api_url, expected) in examples {
+        let url = dashboard_url_for_api(&Url::parse(api_url).unwrap(), id);
+        assert_eq!(url.as_str(), format!("{}{}", expected, id));
+    }
+
+    let any: AnyResourceId = id.parse().unwrap();
+    assert_eq!(
+        any.dashboard_url().as_str(),
+        "https://bigml.com/dashboard/dataset/5e5d87f6e476845bd8000000",
+    );
+}
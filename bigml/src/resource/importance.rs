@@ -0,0 +1,142 @@
+//! A uniform way to ask supervised models which fields matter most.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use super::source::Field;
+use super::{Deepnet, Ensemble, LogisticRegression, Model};
+
+/// A model which can report how much each input field contributed to its
+/// predictions.
+pub trait FieldImportance {
+    /// Return `(field_id, name, importance)` for each input field, sorted
+    /// from most to least important. Importances are normalized to sum to 1.
+    /// Returns an empty list if the model has not finished training.
+    fn field_importance(&self) -> Vec<(String, String, f64)>;
+}
+
+impl FieldImportance for Model {
+    fn field_importance(&self) -> Vec<(String, String, f64)> {
+        match &self.model {
+            Some(info) => sort_importance(&info.fields, info.importance.clone()),
+            None => vec![],
+        }
+    }
+}
+
+impl FieldImportance for Ensemble {
+    fn field_importance(&self) -> Vec<(String, String, f64)> {
+        let no_fields = HashMap::new();
+        let fields = self
+            .ensemble
+            .as_ref()
+            .map(|info| &info.fields)
+            .unwrap_or(&no_fields);
+        sort_importance(fields, self.importance.clone())
+    }
+}
+
+impl FieldImportance for Deepnet {
+    fn field_importance(&self) -> Vec<(String, String, f64)> {
+        match &self.deepnet {
+            Some(info) => sort_importance(&info.fields, info.importance.clone()),
+            None => vec![],
+        }
+    }
+}
+
+/// BigML doesn't report field importance for logistic regressions, so we
+/// estimate it from the absolute values of each field's coefficients, summed
+/// over all classes. This is only meaningful when fields are on comparable
+/// scales, which is true when BigML normalizes them.
+impl FieldImportance for LogisticRegression {
+    fn field_importance(&self) -> Vec<(String, String, f64)> {
+        let info = match &self.logistic_regression {
+            Some(info) => info,
+            None => return vec![],
+        };
+
+        // Coefficients are listed in order of field ID, followed by the bias
+        // term.
+        let mut input_fields = info
+            .fields
+            .keys()
+            .filter(|&id| Some(id) != self.objective_field.as_ref())
+            .collect::<Vec<_>>();
+        input_fields.sort();
+        let mut importance = HashMap::<String, f64>::new();
+        for class in &info.coefficients {
+            for (id, coefficients) in input_fields.iter().zip(&class.coefficients) {
+                *importance.entry((*id).to_owned()).or_default() +=
+                    coefficients.iter().map(|c| c.abs()).sum::<f64>();
+            }
+        }
+        sort_importance(&info.fields, importance)
+    }
+}
+
+/// Normalize `importance`, look up the name of each field in `fields`, and
+/// sort from most to least important, breaking ties by field ID.
+fn sort_importance<I>(
+    fields: &HashMap<String, Field>,
+    importance: I,
+) -> Vec<(String, String, f64)>
+where
+    I: IntoIterator<Item = (String, f64)>,
+{
+    let importance = importance.into_iter().collect::<Vec<_>>();
+    let total = importance.iter().map(|(_, value)| value).sum::<f64>();
+    let mut result = importance
+        .into_iter()
+        .map(|(id, value)| {
+            let name = fields
+                .get(&id)
+                .map(|field| field.name.clone())
+                .unwrap_or_else(|| id.clone());
+            let value = if total > 0.0 { value / total } else { value };
+            (id, name, value)
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|(id1, _, value1), (id2, _, value2)| {
+        value2
+            .partial_cmp(value1)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| id1.cmp(id2))
+    });
+    result
+}
+
+#[test]
+fn logistic_regression_importance_uses_coefficients() {
+    let lr: LogisticRegression = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "logisticregression/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The logistic regression has been created" },
+        "objective_field": "000002",
+        "logistic_regression": {
+            "coefficients": [
+                ["a", [[0.5], [-1.0], [0.2]]],
+                ["b", [[-0.5], [2.0], [0.3]]],
+            ],
+            "fields": {
+                "000000": { "name": "width", "optype": "numeric" },
+                "000001": { "name": "height", "optype": "numeric" },
+                "000002": { "name": "species", "optype": "categorical" },
+            },
+        },
+    }))
+    .unwrap();
+    let importance = lr.field_importance();
+    assert_eq!(importance.len(), 2);
+    assert_eq!(importance[0].0, "000001");
+    assert_eq!(importance[0].1, "height");
+    assert!((importance[0].2 - 0.75).abs() < 1e-9);
+    assert_eq!(importance[1].1, "width");
+    assert!((importance[1].2 - 0.25).abs() < 1e-9);
+}
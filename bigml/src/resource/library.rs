@@ -1,21 +1,24 @@
-//! BigML dataset support.
+//! A WhizzML library on BigML.
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 use super::id::*;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Project, Resource, ResourceCommon, Updatable};
+use crate::errors::*;
 
 /// A BigML library for use in a WhizzML script.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "library"]
 #[non_exhaustive]
 pub struct Library {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -28,36 +31,36 @@ pub struct Library {
     pub source_code: String,
 }
 
-/// Arguments used to create a new BigML script.
-#[derive(Debug, Serialize)]
+/// Arguments used to create a new BigML library.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Library")]
 #[non_exhaustive]
 pub struct Args {
     /// The category code which best describes this script.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<i64>,
 
     /// A human-readable description of this script.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     /// A list of "library/..." identifiers to import.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub imports: Vec<Id<Library>>,
 
     /// A human-readable name for this script.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// The source code of this script.
     pub source_code: String,
 
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
     /// User-defined tags.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 }
 
 impl Args {
-    /// Create a new `ScriptNew` value.
+    /// Create a new `Args` value.
     pub fn new<S: Into<String>>(source_code: S) -> Args {
         Args {
             category: Default::default(),
@@ -65,11 +68,113 @@ impl Args {
             imports: Default::default(),
             name: Default::default(),
             source_code: source_code.into(),
+            project: Default::default(),
             tags: Default::default(),
         }
     }
 }
 
-impl super::Args for Args {
-    type Resource = Library;
+/// A library which has not been uploaded to BigML yet, and which may import
+/// other local libraries by name. See `Client::create_libraries`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LocalLibrary {
+    /// A local name for this library, used by `imports`.
+    pub name: String,
+
+    /// The arguments used to create this library. Any IDs in `args.imports`
+    /// will be kept, and the IDs of local libraries will be added to them.
+    pub args: Args,
+
+    /// The names of the local libraries imported by this library.
+    pub imports: Vec<String>,
+}
+
+impl LocalLibrary {
+    /// Create a new `LocalLibrary` value with no imports.
+    pub fn new<N, S>(name: N, source_code: S) -> LocalLibrary
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        LocalLibrary {
+            name: name.into(),
+            args: Args::new(source_code),
+            imports: vec![],
+        }
+    }
+}
+
+/// Sort `libraries` so that every library appears after the libraries it
+/// imports, returning indices into `libraries`. Fails if a library imports
+/// an unknown library, or if there is an import cycle.
+pub(crate) fn dependency_order(libraries: &[LocalLibrary]) -> Result<Vec<usize>> {
+    let indices = libraries
+        .iter()
+        .enumerate()
+        .map(|(i, lib)| (lib.name.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let mut order = Vec::with_capacity(libraries.len());
+    let mut visited = HashSet::new();
+    let mut in_progress = HashSet::new();
+
+    fn visit(
+        i: usize,
+        libraries: &[LocalLibrary],
+        indices: &HashMap<&str, usize>,
+        visited: &mut HashSet<usize>,
+        in_progress: &mut HashSet<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<()> {
+        if visited.contains(&i) {
+            return Ok(());
+        }
+        if !in_progress.insert(i) {
+            return Err(format_err!(
+                "WhizzML library {:?} imports itself",
+                libraries[i].name
+            ));
+        }
+        for import in &libraries[i].imports {
+            let &dep = indices.get(import.as_str()).ok_or_else(|| {
+                format_err!(
+                    "WhizzML library {:?} imports unknown library {:?}",
+                    libraries[i].name,
+                    import,
+                )
+            })?;
+            visit(dep, libraries, indices, visited, in_progress, order)?;
+        }
+        in_progress.remove(&i);
+        visited.insert(i);
+        order.push(i);
+        Ok(())
+    }
+
+    for i in 0..libraries.len() {
+        visit(
+            i,
+            libraries,
+            &indices,
+            &mut visited,
+            &mut in_progress,
+            &mut order,
+        )?;
+    }
+    Ok(order)
+}
+
+#[test]
+fn libraries_are_sorted_by_dependencies() {
+    let mut app = LocalLibrary::new("app", "");
+    app.imports = vec!["utils".to_owned(), "base".to_owned()];
+    let mut utils = LocalLibrary::new("utils", "");
+    utils.imports = vec!["base".to_owned()];
+    let base = LocalLibrary::new("base", "");
+    let mut libraries = vec![app, utils, base];
+    assert_eq!(dependency_order(&libraries).unwrap(), vec![2, 1, 0]);
+
+    // Cycles are an error.
+    libraries[2].imports = vec!["app".to_owned()];
+    assert!(dependency_order(&libraries).is_err());
 }
@@ -0,0 +1,156 @@
+//! https://bigml.com/api/logisticregressions
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::model::ModelBuildOptions;
+use super::source::Field;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+
+/// A logistic regression model.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "logisticregression"]
+#[non_exhaustive]
+pub struct LogisticRegression {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<LogisticRegression>,
+
+    /// The current status of this logistic regression.
+    pub status: GenericStatus,
+
+    /// The dataset used to create this logistic regression.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The ID of the field this logistic regression predicts.
+    pub objective_field: Option<String>,
+
+    /// The learned model. Will be missing while the logistic regression is
+    /// being created.
+    pub logistic_regression: Option<LogisticRegressionInfo>,
+}
+
+/// The contents of a logistic regression.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct LogisticRegressionInfo {
+    /// Was a bias term included in the model?
+    pub bias: Option<bool>,
+
+    /// The inverse of the regularization strength.
+    pub c: Option<f64>,
+
+    /// The learned coefficients for each class of the objective field.
+    #[serde(default)]
+    pub coefficients: Vec<ClassCoefficients>,
+
+    /// Metadata describing each field used by this logistic regression, keyed
+    /// by BigML field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+
+    /// The type of regularization used, either `"l1"` or `"l2"`.
+    pub regularization: Option<String>,
+}
+
+/// The coefficients learned for a single class.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "(String, Vec<Vec<f64>>)", into = "(String, Vec<Vec<f64>>)")]
+#[non_exhaustive]
+pub struct ClassCoefficients {
+    /// The class these coefficients predict.
+    pub class: String,
+
+    /// The coefficients for each input field, in the order used by BigML
+    /// (sorted by field ID). Numeric fields have a single coefficient, and
+    /// categorical, text and items fields have one coefficient for each
+    /// category, term or item. The final entry contains the bias term, if any.
+    pub coefficients: Vec<Vec<f64>>,
+}
+
+impl From<(String, Vec<Vec<f64>>)> for ClassCoefficients {
+    fn from((class, coefficients): (String, Vec<Vec<f64>>)) -> Self {
+        ClassCoefficients {
+            class,
+            coefficients,
+        }
+    }
+}
+
+impl From<ClassCoefficients> for (String, Vec<Vec<f64>>) {
+    fn from(coefficients: ClassCoefficients) -> Self {
+        (coefficients.class, coefficients.coefficients)
+    }
+}
+
+/// Arguments used to create a logistic regression.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "LogisticRegression")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to train on.
+    pub dataset: Id<Dataset>,
+
+    /// Should we include a bias term? BigML defaults to `true`.
+    pub bias: Option<bool>,
+
+    /// The inverse of the regularization strength. BigML defaults to `1`.
+    pub c: Option<f64>,
+
+    /// The name of this logistic regression.
+    pub name: Option<String>,
+
+    /// Options shared by all supervised models.
+    #[serde(flatten)]
+    pub options: ModelBuildOptions,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            bias: None,
+            c: None,
+            name: None,
+            options: ModelBuildOptions::default(),
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_coefficients() {
+    let json = r#"{
+        "bias": true,
+        "c": 1,
+        "coefficients": [
+            ["Iris-setosa", [[0.7], [-1.4], [0.2]]],
+            ["Iris-virginica", [[-0.5], [1.1], [-0.3]]]
+        ],
+        "fields": {},
+        "regularization": "l2"
+    }"#;
+    let info: LogisticRegressionInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(info.c, Some(1.0));
+    assert_eq!(info.coefficients.len(), 2);
+    assert_eq!(info.coefficients[1].class, "Iris-virginica");
+    assert_eq!(info.coefficients[1].coefficients[1], vec![1.1]);
+}
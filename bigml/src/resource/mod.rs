@@ -2,22 +2,42 @@
 
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt, hash::BuildHasher};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::BuildHasher,
+};
+use url::Url;
 
 // We re-export everything from our support submodules.
 pub use self::id::*;
+pub use self::importance::FieldImportance;
 pub use self::status::*;
 
 // We only re-export the main names from our resource submodules.  For any
 // other types, use a fully-qualified name.
+pub use self::association::Association;
 pub use self::batchcentroid::BatchCentroid;
 pub use self::batchprediction::BatchPrediction;
+pub use self::batchprojection::BatchProjection;
+pub use self::centroid::Centroid;
 pub use self::cluster::Cluster;
 pub use self::dataset::Dataset;
-pub use self::ensemble::{Ensemble, EnsembleField};
+pub use self::deepnet::Deepnet;
+pub use self::ensemble::Ensemble;
 pub use self::evaluation::Evaluation;
 pub use self::execution::Execution;
+pub use self::externalconnector::ExternalConnector;
+pub use self::fusion::Fusion;
 pub use self::library::Library;
+pub use self::logisticregression::LogisticRegression;
+pub use self::model::Model;
+pub use self::optiml::OptiML;
+pub use self::pca::Pca;
+pub use self::prediction::Prediction;
+pub use self::project::Project;
+pub use self::projection::Projection;
+pub use self::sample::Sample;
 pub use self::script::Script;
 pub use self::source::Source;
 
@@ -54,15 +74,43 @@ pub use self::source::Source;
 ///     // Resource-specific fields here.
 /// }
 /// ```
+///
+/// If BigML doesn't allow some operations on a resource type, you can mark
+/// them with `#[api_unsupported(create, update, delete)]`, and `Client` will
+/// refuse to perform them.
 pub trait Resource:
     fmt::Debug + DeserializeOwned + Send + Serialize + Sync + 'static
 {
+    /// Can resources of this type be created using the API?
+    const SUPPORTS_CREATE: bool = true;
+
+    /// Can resources of this type be updated using the API?
+    const SUPPORTS_UPDATE: bool = true;
+
+    /// Can resources of this type be deleted using the API?
+    const SUPPORTS_DELETE: bool = true;
+
+    /// The name of this resource type in the API, such as `"dataset"`.
+    fn api_name() -> &'static str {
+        Self::id_prefix().trim_end_matches('/')
+    }
+
     /// The prefix used for all IDs of this type.
     fn id_prefix() -> &'static str;
 
     /// The URL path used to create a new resource of this type.
     fn create_path() -> &'static str;
 
+    /// The URL path used to list resources of this type.
+    fn list_path() -> &'static str {
+        Self::create_path()
+    }
+
+    /// The URL path used to fetch, update or delete the resource `id`.
+    fn resource_path(id: &Id<Self>) -> String {
+        format!("/{}", id)
+    }
+
     /// Fields shared between all resource types. These are "flattened" into the
     /// top-level of the JSON version of this resource.
     fn common(&self) -> &ResourceCommon;
@@ -70,11 +118,26 @@ pub trait Resource:
     /// The ID of this resource.
     fn id(&self) -> &Id<Self>;
 
+    /// Get a URL pointing at the human-readable version of this resource.
+    /// See `Id::dashboard_url`.
+    fn dashboard_url(&self) -> Url {
+        self.id().dashboard_url()
+    }
+
     /// The status code for this resource.
     ///
     /// TODO: Does this need to go in a separate trait in order to maintain
     /// trait object support?
     fn status(&self) -> &dyn Status;
+
+    /// Is this resource ready to be used, according to `readiness`? By
+    /// default, resources are only ready when they're finished. Resources
+    /// which can be used earlier override this, normally using
+    /// `#[ready_when(summarized)]` with `#[derive(Resource)]`.
+    fn is_ready_for(&self, readiness: Readiness) -> bool {
+        let _ = readiness;
+        self.status().code().is_ready()
+    }
 }
 
 /// A value which can be updated using the BigML API. May be a `Resource` or a
@@ -105,9 +168,32 @@ pub trait Resource:
 /// fields with `#[updatable]`. For a struct `Foo`, this will generate a
 /// corresponding `FooUpdate` type, containing only those fields marked as
 /// `#[updatable]` (with appropriate types).
+///
+/// For `enum` types, `#[derive(Updatable)]` will define `type Update = Self`,
+/// because an `enum` can only be updated as a whole.
+///
+/// Structs deriving `Updatable` also get a `diff` method, which returns an
+/// update containing only the fields which differ between two values:
+///
+/// ```
+/// # async fn example(client: &bigml::Client, id: &bigml::resource::Id<bigml::resource::Source>) -> bigml::Result<()> {
+/// let original = client.fetch(id).await?;
+/// let mut source = original.clone();
+/// source.common.name = "renamed".to_owned();
+/// client.update(id, &original.diff(&source)).await?;
+/// # Ok(())
+/// # }
+/// ```
 pub trait Updatable {
     /// The type of the data used to update this value.
     type Update: Serialize + fmt::Debug;
+
+    /// An update which would set every updatable part of this value.
+    fn to_update(&self) -> Self::Update;
+
+    /// An update which would change `self` into `other`, or `None` if there
+    /// are no updatable differences.
+    fn diff_update(&self, other: &Self) -> Option<Self::Update>;
 }
 
 /// Primitive types are updated using plain values of the same type.
@@ -116,6 +202,18 @@ macro_rules! primitive_updatable_types {
         $(
             impl Updatable for $ty {
                 type Update = Self;
+
+                fn to_update(&self) -> Self::Update {
+                    self.clone()
+                }
+
+                fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+                    if self == other {
+                        None
+                    } else {
+                        Some(other.clone())
+                    }
+                }
             }
         )*
     };
@@ -124,18 +222,127 @@ macro_rules! primitive_updatable_types {
 primitive_updatable_types!(bool, i64, String, u16);
 
 /// `HashMap<String, T>` can be updated using `HashMap<String, T::Update>`.
+/// Only the keys present in the update will be sent to BigML, and other keys
+/// will be left alone.
+///
+/// Keys which are missing from `other` are ignored by `diff_update`, because
+/// there is no way to express a deletion.
 impl<T: Updatable, H: BuildHasher> Updatable for HashMap<String, T, H> {
     type Update = HashMap<String, <T as Updatable>::Update>;
+
+    fn to_update(&self) -> Self::Update {
+        self.iter()
+            .map(|(key, value)| (key.to_owned(), value.to_update()))
+            .collect()
+    }
+
+    fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+        let update = other
+            .iter()
+            .filter_map(|(key, value)| {
+                let changes = match self.get(key) {
+                    Some(old) => old.diff_update(value)?,
+                    None => value.to_update(),
+                };
+                Some((key.to_owned(), changes))
+            })
+            .collect::<Self::Update>();
+        if update.is_empty() {
+            None
+        } else {
+            Some(update)
+        }
+    }
 }
 
 /// `Option<T>` can be updated using `Option<T::Update>`.
 impl<T: Updatable> Updatable for Option<T> {
     type Update = Option<<T as Updatable>::Update>;
+
+    fn to_update(&self) -> Self::Update {
+        self.as_ref().map(|value| value.to_update())
+    }
+
+    fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+        match (self, other) {
+            (Some(old), Some(new)) => old.diff_update(new).map(Some),
+            (None, Some(new)) => Some(Some(new.to_update())),
+            (Some(_), None) => Some(None),
+            (None, None) => None,
+        }
+    }
 }
 
-/// `Vec<T>` can be updated using `Vec<T::Update>`.
+/// `Vec<T>` can be updated using `VecUpdate<T::Update>`.
 impl<T: Updatable> Updatable for Vec<T> {
-    type Update = Vec<<T as Updatable>::Update>;
+    type Update = VecUpdate<<T as Updatable>::Update>;
+
+    fn to_update(&self) -> Self::Update {
+        VecUpdate::Replace(self.iter().map(|value| value.to_update()).collect())
+    }
+
+    fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+        let changed = self.len() != other.len()
+            || self
+                .iter()
+                .zip(other)
+                .any(|(old, new)| old.diff_update(new).is_some());
+        if changed {
+            Some(other.to_update())
+        } else {
+            None
+        }
+    }
+}
+
+/// An update to a `Vec<T>`, where `U` is `T::Update`.
+///
+/// Use `From` to convert a `Vec<U>` into a `VecUpdate::Replace` value. Note
+/// that `diff_update` always replaces the entire list, because most BigML
+/// list fields can't be updated element-by-element.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum VecUpdate<U> {
+    /// Replace the entire list.
+    Replace(Vec<U>),
+    /// Update individual elements, keyed by index, and leave the rest alone.
+    /// This is serialized as a JSON object with indices as keys.
+    Elements(BTreeMap<usize, U>),
+}
+
+impl<U> VecUpdate<U> {
+    /// Create an update which only changes the element at `index`. Use
+    /// `element` to change additional elements.
+    pub fn at(index: usize, update: U) -> Self {
+        VecUpdate::Elements(BTreeMap::new()).element(index, update)
+    }
+
+    /// Also change the element at `index`. If this update replaces the
+    /// entire list, `update` replaces the corresponding element of the new
+    /// list, or is appended if the list is too short.
+    pub fn element(self, index: usize, update: U) -> Self {
+        match self {
+            VecUpdate::Replace(mut values) => {
+                if index < values.len() {
+                    values[index] = update;
+                } else {
+                    values.push(update);
+                }
+                VecUpdate::Replace(values)
+            }
+            VecUpdate::Elements(mut elements) => {
+                elements.insert(index, update);
+                VecUpdate::Elements(elements)
+            }
+        }
+    }
+}
+
+impl<U> From<Vec<U>> for VecUpdate<U> {
+    fn from(values: Vec<U>) -> Self {
+        VecUpdate::Replace(values)
+    }
 }
 
 /// Arguments which can be used to create a resource.
@@ -151,6 +358,7 @@ pub trait Args: fmt::Debug + Serialize {
 #[non_exhaustive]
 pub struct ResourceCommon {
     /// Used to classify by industry or category.  0 is "Miscellaneous".
+    #[updatable]
     pub category: i64,
 
     /// An HTTP status code, typically either 201 or 200.
@@ -162,20 +370,32 @@ pub struct ResourceCommon {
     //
     // TODO: The response is missing the `Z`, which makes chrono sad.
     //pub created: DateTime<UTC>,
+    /// The username of the user who created this resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+
     /// Was this created in development mode?
     pub dev: Option<bool>,
 
     /// Text describing this resource.  May contain limited Markdown.
+    #[updatable]
     pub description: String,
 
     /// The name of this resource.
     #[updatable]
     pub name: String,
 
-    // What project is this associated with?
-    //
-    // TODO: Define `Project` type and then enable this.
-    //pub project: Id<Project>,
+    /// Is this resource private? Some resources may be made public, in
+    /// which case they can be found by other users.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private: Option<bool>,
+
+    /// The project this resource belongs to, if any. Update this to move a
+    /// resource to another project.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<Id<Project>>,
+
     /// Has this been shared using a private link?
     pub shared: bool,
 
@@ -183,6 +403,7 @@ pub struct ResourceCommon {
     pub subscription: bool,
 
     /// User-defined tags.
+    #[updatable]
     pub tags: Vec<String>,
     // The last time this was updated.
     //
@@ -192,18 +413,33 @@ pub struct ResourceCommon {
 
 // Support modules defining general types.
 mod id;
+mod importance;
 mod status;
 
 // Individual resource types.  These need to go after our `response!` macro
 // definition, above, because macros are processed as source is being read.
+pub mod association;
 pub mod batchcentroid;
 pub mod batchprediction;
+pub mod batchprojection;
+pub mod centroid;
 pub mod cluster;
 pub mod dataset;
+pub mod deepnet;
 pub mod ensemble;
 pub mod evaluation;
 pub mod execution;
+pub mod externalconnector;
+pub mod fusion;
 pub mod library;
+pub mod logisticregression;
+pub mod model;
+pub mod optiml;
+pub mod pca;
+pub mod prediction;
+pub mod project;
+pub mod projection;
+pub mod sample;
 pub mod script;
 pub mod source;
 
@@ -216,3 +452,133 @@ fn can_compile_update_struct_expressions() {
         ..SourceUpdate::default()
     };
 }
+
+#[test]
+fn resource_updates_flatten_common_fields() {
+    use model::ModelUpdate;
+    let update = ModelUpdate {
+        common: Some(ResourceCommonUpdate {
+            name: Some("renamed".to_owned()),
+            ..ResourceCommonUpdate::default()
+        }),
+        ..ModelUpdate::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        serde_json::json!({ "name": "renamed" }),
+    );
+}
+
+#[test]
+fn vec_updates_can_replace_or_change_elements() {
+    let replace: VecUpdate<String> = vec!["a".to_owned()].into();
+    assert_eq!(
+        serde_json::to_value(&replace).unwrap(),
+        serde_json::json!(["a"]),
+    );
+    let elements = VecUpdate::at(2, "c".to_owned()).element(0, "a".to_owned());
+    assert_eq!(
+        serde_json::to_value(&elements).unwrap(),
+        serde_json::json!({ "0": "a", "2": "c" }),
+    );
+}
+
+#[test]
+fn diff_only_includes_changed_fields() {
+    use source::{Field, Optype, Source, SourceUpdate};
+
+    let original: Source = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": ["a"],
+        "resource": "source/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The source has been created" },
+        "file_name": "iris.csv",
+        "md5": "d41d8cd98f00b204e9800998ecf8427e",
+        "size": 0,
+        "fields": {
+            "000000": { "name": "species", "optype": "text" },
+            "000001": { "name": "petal length", "optype": "numeric" },
+        },
+    }))
+    .unwrap();
+    assert_eq!(original.diff(&original), SourceUpdate::default());
+
+    let mut changed = original.clone();
+    changed.common.name = "iris v2".to_owned();
+    changed.common.tags.push("b".to_owned());
+    let fields = changed.fields.as_mut().unwrap();
+    let species: &mut Field = fields.get_mut("000000").unwrap();
+    species.optype = Optype::Categorical;
+    assert_eq!(
+        serde_json::to_value(original.diff(&changed)).unwrap(),
+        serde_json::json!({
+            "name": "iris v2",
+            "tags": ["a", "b"],
+            "fields": { "000000": { "optype": "categorical" } },
+        }),
+    );
+}
+
+#[test]
+fn derive_resource_generates_endpoint_metadata() {
+    #[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+    #[api_name = "readonlything"]
+    #[api_unsupported(create, update)]
+    struct ReadOnlyThing {
+        #[serde(flatten)]
+        common: ResourceCommon,
+        resource: Id<ReadOnlyThing>,
+        status: GenericStatus,
+    }
+
+    assert_eq!(ReadOnlyThing::api_name(), "readonlything");
+    assert_eq!(ReadOnlyThing::list_path(), "/readonlything");
+    let supports = (
+        ReadOnlyThing::SUPPORTS_CREATE,
+        ReadOnlyThing::SUPPORTS_UPDATE,
+        ReadOnlyThing::SUPPORTS_DELETE,
+        Source::SUPPORTS_UPDATE,
+    );
+    assert_eq!(supports, (false, false, true, true));
+
+    let id: Id<ReadOnlyThing> =
+        "readonlything/5e5d87f6e476845bd8000000".parse().unwrap();
+    assert_eq!(
+        ReadOnlyThing::resource_path(&id),
+        "/readonlything/5e5d87f6e476845bd8000000",
+    );
+}
+
+#[test]
+fn common_fields_include_project() {
+    let json = serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "creator": "example",
+        "description": "",
+        "name": "iris",
+        "private": true,
+        "project": "project/5e5d87f6e476845bd8000000",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+    });
+    let common: ResourceCommon = serde_json::from_value(json).unwrap();
+    assert_eq!(common.creator.as_deref(), Some("example"));
+    let project = common.project.unwrap();
+    assert_eq!(project.as_str(), "project/5e5d87f6e476845bd8000000");
+
+    let update = ResourceCommonUpdate {
+        project: Some(Some(project)),
+        ..ResourceCommonUpdate::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        serde_json::json!({ "project": "project/5e5d87f6e476845bd8000000" }),
+    );
+}
@@ -0,0 +1,738 @@
+//! A decision tree model.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::id::*;
+use super::source::Field;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+use crate::errors::*;
+
+/// A decision tree model.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "model"]
+#[non_exhaustive]
+pub struct Model {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Model>,
+
+    /// The current status of this model.
+    pub status: GenericStatus,
+
+    /// The dataset used to create this model.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The ID of the field this model predicts.
+    pub objective_field: Option<String>,
+
+    /// The actual decision tree. Will be missing while the model is being
+    /// created.
+    pub model: Option<ModelInfo>,
+}
+
+/// The contents of a decision tree model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ModelInfo {
+    /// Metadata describing each field used by this model, keyed by BigML
+    /// field ID.
+    #[serde(default)]
+    pub fields: HashMap<String, Field>,
+
+    /// The relative importance of each field, as `(field_id, importance)`
+    /// pairs.
+    #[serde(default)]
+    pub importance: Vec<(String, f64)>,
+
+    /// The root node of our decision tree.
+    pub root: Option<TreeNode>,
+}
+
+/// A node in a decision tree.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(from = "TreeNodeRepr", into = "TreeNodeRepr")]
+#[non_exhaustive]
+pub enum TreeNode {
+    /// An interior node, which splits the data among its children.
+    Split {
+        /// The ID of this node.
+        id: u64,
+        /// The condition which must be true to reach this node.
+        predicate: Predicate,
+        /// The prediction we would make if we stopped at this node.
+        output: Output,
+        /// How confident we are in `output`, for classification models.
+        confidence: Option<f64>,
+        /// The number of training instances which reached this node.
+        count: u64,
+        /// The distribution of the objective field at this node.
+        objective_summary: Option<ObjectiveSummary>,
+        /// The children of this node.
+        children: Vec<TreeNode>,
+    },
+    /// A leaf node, which makes a final prediction.
+    Leaf {
+        /// The ID of this node.
+        id: u64,
+        /// The condition which must be true to reach this node.
+        predicate: Predicate,
+        /// The prediction made by this node.
+        output: Output,
+        /// How confident we are in `output`, for classification models.
+        confidence: Option<f64>,
+        /// The number of training instances which reached this node.
+        count: u64,
+        /// The distribution of the objective field at this node.
+        objective_summary: Option<ObjectiveSummary>,
+    },
+}
+
+impl TreeNode {
+    /// The ID of this node.
+    pub fn id(&self) -> u64 {
+        match self {
+            TreeNode::Split { id, .. } | TreeNode::Leaf { id, .. } => *id,
+        }
+    }
+
+    /// The condition which must be true to reach this node.
+    pub fn predicate(&self) -> &Predicate {
+        match self {
+            TreeNode::Split { predicate, .. } | TreeNode::Leaf { predicate, .. } => {
+                predicate
+            }
+        }
+    }
+
+    /// The prediction made at this node.
+    pub fn output(&self) -> &Output {
+        match self {
+            TreeNode::Split { output, .. } | TreeNode::Leaf { output, .. } => output,
+        }
+    }
+
+    /// How confident we are in `output`, for classification models.
+    pub fn confidence(&self) -> Option<f64> {
+        match self {
+            TreeNode::Split { confidence, .. } | TreeNode::Leaf { confidence, .. } => {
+                *confidence
+            }
+        }
+    }
+
+    /// The number of training instances which reached this node.
+    pub fn count(&self) -> u64 {
+        match self {
+            TreeNode::Split { count, .. } | TreeNode::Leaf { count, .. } => *count,
+        }
+    }
+
+    /// The distribution of the objective field at this node.
+    pub fn objective_summary(&self) -> Option<&ObjectiveSummary> {
+        match self {
+            TreeNode::Split {
+                objective_summary, ..
+            }
+            | TreeNode::Leaf {
+                objective_summary, ..
+            } => objective_summary.as_ref(),
+        }
+    }
+
+    /// The children of this node, or an empty slice for leaves.
+    pub fn children(&self) -> &[TreeNode] {
+        match self {
+            TreeNode::Split { children, .. } => children,
+            TreeNode::Leaf { .. } => &[],
+        }
+    }
+
+    /// Find the node which makes a prediction for `input`, which maps BigML
+    /// field IDs to values. Starting at this node, we follow the first child
+    /// whose predicate matches `input`, and we stop when no child matches
+    /// (which may happen if a value is missing).
+    ///
+    /// Fails if we need to test for a term in a text or items field. See
+    /// `Condition::matches`.
+    pub fn predict(&self, input: &HashMap<String, Value>) -> Result<&TreeNode> {
+        let mut node = self;
+        'descend: loop {
+            for child in node.children() {
+                if child.predicate().matches(input)? {
+                    node = child;
+                    continue 'descend;
+                }
+            }
+            return Ok(node);
+        }
+    }
+
+    /// Does this node or any of its descendants test for a term in a text or
+    /// items field? `predict` can't evaluate these conditions.
+    pub fn uses_terms(&self) -> bool {
+        let uses_term = match self.predicate() {
+            Predicate::Condition(cond) => cond.term.is_some(),
+            Predicate::True => false,
+        };
+        uses_term || self.children().iter().any(TreeNode::uses_terms)
+    }
+}
+
+/// The distribution of the objective field at a `TreeNode`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ObjectiveSummary {
+    /// For classification models, the number of training instances in each
+    /// category, as `(category, count)` pairs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<(String, u64)>,
+}
+
+/// The JSON representation of a `TreeNode`.
+#[derive(Clone, Deserialize, Serialize)]
+struct TreeNodeRepr {
+    id: u64,
+    predicate: Predicate,
+    output: Output,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confidence: Option<f64>,
+    count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    objective_summary: Option<ObjectiveSummary>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<TreeNode>,
+}
+
+impl From<TreeNodeRepr> for TreeNode {
+    fn from(repr: TreeNodeRepr) -> Self {
+        let TreeNodeRepr {
+            id,
+            predicate,
+            output,
+            confidence,
+            count,
+            objective_summary,
+            children,
+        } = repr;
+        if children.is_empty() {
+            TreeNode::Leaf {
+                id,
+                predicate,
+                output,
+                confidence,
+                count,
+                objective_summary,
+            }
+        } else {
+            TreeNode::Split {
+                id,
+                predicate,
+                output,
+                confidence,
+                count,
+                objective_summary,
+                children,
+            }
+        }
+    }
+}
+
+impl From<TreeNode> for TreeNodeRepr {
+    fn from(node: TreeNode) -> Self {
+        match node {
+            TreeNode::Split {
+                id,
+                predicate,
+                output,
+                confidence,
+                count,
+                objective_summary,
+                children,
+            } => TreeNodeRepr {
+                id,
+                predicate,
+                output,
+                confidence,
+                count,
+                objective_summary,
+                children,
+            },
+            TreeNode::Leaf {
+                id,
+                predicate,
+                output,
+                confidence,
+                count,
+                objective_summary,
+            } => TreeNodeRepr {
+                id,
+                predicate,
+                output,
+                confidence,
+                count,
+                objective_summary,
+                children: vec![],
+            },
+        }
+    }
+}
+
+/// A condition which must be true to reach a `TreeNode`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "PredicateRepr", into = "PredicateRepr")]
+#[non_exhaustive]
+pub enum Predicate {
+    /// Always true. Used for the root node.
+    True,
+    /// A condition on the value of a field.
+    Condition(Condition),
+}
+
+impl Predicate {
+    /// Does `input` satisfy this predicate? See `Condition::matches`.
+    pub fn matches(&self, input: &HashMap<String, Value>) -> Result<bool> {
+        match self {
+            Predicate::True => Ok(true),
+            Predicate::Condition(cond) => cond.matches(input),
+        }
+    }
+}
+
+/// The JSON representation of a `Predicate`, which is either `true` or an
+/// object.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+enum PredicateRepr {
+    Bool(bool),
+    Condition(Condition),
+}
+
+impl From<PredicateRepr> for Predicate {
+    fn from(repr: PredicateRepr) -> Self {
+        match repr {
+            // BigML never generates `false` here.
+            PredicateRepr::Bool(_) => Predicate::True,
+            PredicateRepr::Condition(cond) => Predicate::Condition(cond),
+        }
+    }
+}
+
+impl From<Predicate> for PredicateRepr {
+    fn from(predicate: Predicate) -> Self {
+        match predicate {
+            Predicate::True => PredicateRepr::Bool(true),
+            Predicate::Condition(cond) => PredicateRepr::Condition(cond),
+        }
+    }
+}
+
+/// A condition on the value of a field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Condition {
+    /// The BigML ID of the field we're testing.
+    pub field: String,
+
+    /// The comparison to perform.
+    pub operator: Operator,
+
+    /// The value we're comparing against. For text fields with a `term`, this
+    /// is the number of times the term appears.
+    pub value: serde_json::Value,
+
+    /// For text and items fields, the term we're looking for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term: Option<String>,
+}
+
+impl Condition {
+    /// Does `input` satisfy this condition? Numeric values may be passed as
+    /// either JSON numbers or strings.
+    ///
+    /// Fails if this condition tests for a `term` in a value which is
+    /// present. BigML splits text into terms using the field's term analysis
+    /// settings, including stemming and stop words, and we can't reproduce
+    /// that locally.
+    pub fn matches(&self, input: &HashMap<String, Value>) -> Result<bool> {
+        let value = match input.get(&self.field) {
+            Some(value) if !value.is_null() => value,
+            // A missing value only matches `*` operators, or an explicit test
+            // for `null`.
+            _ => {
+                return Ok(self.operator.matches_missing()
+                    || (self.value.is_null()
+                        && self.operator.compare(Ordering::Equal)))
+            }
+        };
+        if self.value.is_null() {
+            // We have a value, so this only matches `!= null`.
+            return Ok(!self.operator.compare(Ordering::Equal));
+        }
+        if let Some(term) = &self.term {
+            return Err(format_err!(
+                "cannot test for term {:?} in field {} locally",
+                term,
+                self.field,
+            ));
+        }
+        let ordering = if let Some(expected) = self.value.as_f64() {
+            value_to_f64(value).and_then(|v| v.partial_cmp(&expected))
+        } else {
+            Some(value_to_string(value).cmp(&value_to_string(&self.value)))
+        };
+        Ok(ordering
+            .map(|ordering| self.operator.compare(ordering))
+            .unwrap_or(false))
+    }
+}
+
+/// Convert a JSON value to a number, parsing strings if necessary.
+fn value_to_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Convert a JSON value to a string, without quoting strings.
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_owned(),
+        other => other.to_string(),
+    }
+}
+
+/// A comparison operator used in a `Condition`.
+///
+/// BigML adds a `*` suffix to operators when missing values should also match
+/// the condition.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Operator {
+    /// `<`
+    #[serde(rename = "<")]
+    LessThan,
+    /// `<=`
+    #[serde(rename = "<=")]
+    LessThanOrEqual,
+    /// `=`
+    #[serde(rename = "=")]
+    Equal,
+    /// `!=`
+    #[serde(rename = "!=")]
+    NotEqual,
+    /// `>`
+    #[serde(rename = ">")]
+    GreaterThan,
+    /// `>=`
+    #[serde(rename = ">=")]
+    GreaterThanOrEqual,
+    /// `<`, or missing.
+    #[serde(rename = "<*")]
+    LessThanOrMissing,
+    /// `<=`, or missing.
+    #[serde(rename = "<=*")]
+    LessThanOrEqualOrMissing,
+    /// `=`, or missing.
+    #[serde(rename = "=*")]
+    EqualOrMissing,
+    /// `!=`, or missing.
+    #[serde(rename = "!=*")]
+    NotEqualOrMissing,
+    /// `>`, or missing.
+    #[serde(rename = ">*")]
+    GreaterThanOrMissing,
+    /// `>=`, or missing.
+    #[serde(rename = ">=*")]
+    GreaterThanOrEqualOrMissing,
+}
+
+impl Operator {
+    /// Does this operator also match missing values?
+    pub fn matches_missing(self) -> bool {
+        use Operator::*;
+        matches!(
+            self,
+            LessThanOrMissing
+                | LessThanOrEqualOrMissing
+                | EqualOrMissing
+                | NotEqualOrMissing
+                | GreaterThanOrMissing
+                | GreaterThanOrEqualOrMissing
+        )
+    }
+
+    /// Is this operator true when an input value compares to the expected
+    /// value with `ordering`?
+    pub fn compare(self, ordering: Ordering) -> bool {
+        use Operator::*;
+        match self {
+            LessThan | LessThanOrMissing => ordering == Ordering::Less,
+            LessThanOrEqual | LessThanOrEqualOrMissing => {
+                ordering != Ordering::Greater
+            }
+            Equal | EqualOrMissing => ordering == Ordering::Equal,
+            NotEqual | NotEqualOrMissing => ordering != Ordering::Equal,
+            GreaterThan | GreaterThanOrMissing => ordering == Ordering::Greater,
+            GreaterThanOrEqual | GreaterThanOrEqualOrMissing => {
+                ordering != Ordering::Less
+            }
+        }
+    }
+}
+
+/// A value predicted by a model. This is a category name for classification
+/// models, or a number for regression models.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Output {
+    /// A predicted numeric value (for regression models).
+    Numeric(f64),
+    /// A predicted category (for classification models).
+    Categorical(String),
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Output::Numeric(n) => n.fmt(f),
+            Output::Categorical(s) => s.fmt(f),
+        }
+    }
+}
+
+/// Arguments used to create a model.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Model")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to train on.
+    pub dataset: Id<Dataset>,
+
+    /// The name of this model.
+    pub name: Option<String>,
+
+    /// Options shared by all supervised models.
+    #[serde(flatten)]
+    pub options: ModelBuildOptions,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            name: None,
+            options: ModelBuildOptions::default(),
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+/// Options for training supervised models, shared by the `Args` types of
+/// models, ensembles, logistic regressions and deepnets.
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct ModelBuildOptions {
+    /// The ID of the field to predict. BigML defaults to the last field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objective_field: Option<String>,
+
+    /// The IDs of the fields to train on. If empty, BigML uses all fields
+    /// except the objective field.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub input_fields: Vec<String>,
+
+    /// The IDs of fields which should not be used for training.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_fields: Vec<String>,
+
+    /// The ID of a numeric field containing the weight of each row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight_field: Option<String>,
+
+    /// Weights for each class of a categorical objective field, as
+    /// `(class, weight)` pairs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub objective_weights: Vec<(String, f64)>,
+
+    /// The fraction of the dataset to train on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+}
+
+impl ModelBuildOptions {
+    /// Predict the field with ID `field`.
+    pub fn objective_field<S: Into<String>>(mut self, field: S) -> Self {
+        self.objective_field = Some(field.into());
+        self
+    }
+
+    /// Train on the field with ID `field`. May be called more than once.
+    pub fn input_field<S: Into<String>>(mut self, field: S) -> Self {
+        self.input_fields.push(field.into());
+        self
+    }
+
+    /// Do not train on the field with ID `field`. May be called more than
+    /// once.
+    pub fn excluded_field<S: Into<String>>(mut self, field: S) -> Self {
+        self.excluded_fields.push(field.into());
+        self
+    }
+
+    /// Weight each row using the numeric field with ID `field`.
+    pub fn weight_field<S: Into<String>>(mut self, field: S) -> Self {
+        self.weight_field = Some(field.into());
+        self
+    }
+
+    /// Give rows with objective value `class` the weight `weight`. May be
+    /// called more than once.
+    pub fn objective_weight<S: Into<String>>(mut self, class: S, weight: f64) -> Self {
+        self.objective_weights.push((class.into(), weight));
+        self
+    }
+
+    /// Train on a `sample_rate` fraction of the dataset.
+    pub fn sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+}
+
+#[test]
+fn deserialize_tree() {
+    let json = r#"{
+        "children": [
+            {
+                "confidence": 0.92865,
+                "count": 50,
+                "id": 1,
+                "output": "Iris-setosa",
+                "predicate": {
+                    "field": "000002",
+                    "operator": "<=",
+                    "value": 2.45
+                }
+            },
+            {
+                "confidence": 0.92865,
+                "count": 100,
+                "id": 2,
+                "output": "Iris-versicolor",
+                "predicate": {
+                    "field": "000002",
+                    "operator": ">*",
+                    "value": 2.45
+                }
+            }
+        ],
+        "confidence": 0.26289,
+        "count": 150,
+        "id": 0,
+        "output": "Iris-setosa",
+        "predicate": true
+    }"#;
+    let root: TreeNode = serde_json::from_str(json).unwrap();
+    assert_eq!(root.predicate(), &Predicate::True);
+    assert_eq!(root.children().len(), 2);
+    let leaf = &root.children()[1];
+    assert!(matches!(leaf, TreeNode::Leaf { .. }));
+    assert_eq!(
+        leaf.output(),
+        &Output::Categorical("Iris-versicolor".to_owned())
+    );
+    match leaf.predicate() {
+        Predicate::Condition(cond) => {
+            assert_eq!(cond.field, "000002");
+            assert_eq!(cond.operator, Operator::GreaterThanOrMissing);
+        }
+        other => panic!("unexpected predicate {:?}", other),
+    }
+
+    // Make sure we can make local predictions.
+    let mut input = HashMap::new();
+    input.insert("000002".to_owned(), serde_json::json!(1.4));
+    assert_eq!(root.predict(&input).unwrap().id(), 1);
+    input.insert("000002".to_owned(), serde_json::json!("4.7"));
+    assert_eq!(root.predict(&input).unwrap().id(), 2);
+    input.remove("000002");
+    assert_eq!(root.predict(&input).unwrap().id(), 2);
+    assert!(!root.uses_terms());
+
+    // Make sure we round-trip correctly.
+    let reparsed: TreeNode =
+        serde_json::from_value(serde_json::to_value(&root).unwrap()).unwrap();
+    assert_eq!(reparsed.children().len(), 2);
+}
+
+#[test]
+fn serialize_args_with_build_options() {
+    let dataset: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut args = Args::from_dataset(dataset);
+    args.options = ModelBuildOptions::default()
+        .objective_field("000004")
+        .excluded_field("000000")
+        .objective_weight("Iris-setosa", 2.0)
+        .sample_rate(0.8);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "dataset": "dataset/5e5d87f6e476845bd8000000",
+            "objective_field": "000004",
+            "excluded_fields": ["000000"],
+            "objective_weights": [["Iris-setosa", 2.0]],
+            "sample_rate": 0.8,
+        }),
+    );
+}
+
+#[test]
+fn term_conditions_are_not_guessed() {
+    let cond: Condition = serde_json::from_value(serde_json::json!({
+        "field": "000001",
+        "operator": ">",
+        "value": 0,
+        "term": "cat",
+    }))
+    .unwrap();
+    let mut input = HashMap::new();
+    assert!(!cond.matches(&input).unwrap());
+    input.insert("000001".to_owned(), serde_json::json!("concatenate"));
+    assert!(cond.matches(&input).is_err());
+
+    let root: TreeNode = serde_json::from_value(serde_json::json!({
+        "id": 0,
+        "count": 2,
+        "output": "a",
+        "predicate": true,
+        "children": [
+            { "id": 1, "count": 1, "output": "b", "predicate": cond },
+        ],
+    }))
+    .unwrap();
+    assert!(root.uses_terms());
+    assert!(root.predict(&input).is_err());
+}
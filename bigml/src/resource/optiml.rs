@@ -0,0 +1,154 @@
+//! https://bigml.com/api/optimls
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::model::ModelBuildOptions;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+
+/// An automatic search for the best model for a dataset. BigML trains many
+/// different models, and ranks them using `metric`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "optiml"]
+#[non_exhaustive]
+pub struct OptiML {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<OptiML>,
+
+    /// The current status of this OptiML.
+    pub status: GenericStatus,
+
+    /// The dataset used to train our models.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// The metric used to rank our models.
+    #[serde(default)]
+    pub metric: Option<Metric>,
+
+    /// The models we created, ranked from best to worst according to
+    /// `metric`. These may be of several different resource types.
+    #[serde(default)]
+    pub models: Vec<AnyResourceId>,
+}
+
+impl OptiML {
+    /// The best model we found, if any.
+    pub fn best_model(&self) -> Option<&AnyResourceId> {
+        self.models.first()
+    }
+}
+
+/// A metric used to compare models.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Metric {
+    /// The fraction of correct predictions (classification).
+    Accuracy,
+    /// The area under the ROC curve (classification).
+    AreaUnderRocCurve,
+    /// The area under the precision-recall curve (classification).
+    AreaUnderPrCurve,
+    /// The harmonic mean of precision and recall (classification).
+    FMeasure,
+    /// The phi coefficient (classification).
+    PhiCoefficient,
+    /// The precision (classification).
+    Precision,
+    /// The recall (classification).
+    Recall,
+    /// The mean absolute error (regression).
+    MeanAbsoluteError,
+    /// The mean squared error (regression).
+    MeanSquaredError,
+    /// The coefficient of determination (regression).
+    RSquared,
+}
+
+/// Arguments used to create an OptiML.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "OptiML")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to train on.
+    pub dataset: Id<Dataset>,
+
+    /// The maximum time to spend searching, in seconds. BigML defaults to
+    /// one hour.
+    pub max_training_time: Option<u64>,
+
+    /// The metric used to rank models. BigML chooses one based on the type
+    /// of the objective field.
+    pub metric: Option<Metric>,
+
+    /// The name of this OptiML.
+    pub name: Option<String>,
+
+    /// The number of models to try.
+    pub number_of_model_candidates: Option<u64>,
+
+    /// Options shared by all supervised models.
+    #[serde(flatten)]
+    pub options: ModelBuildOptions,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            max_training_time: None,
+            metric: None,
+            name: None,
+            number_of_model_candidates: None,
+            options: ModelBuildOptions::default(),
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn deserialize_ranked_models() {
+    let optiml: OptiML = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "optiml/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The optiml has been created" },
+        "dataset": "dataset/5e5d87f6e476845bd8000001",
+        "metric": "area_under_roc_curve",
+        "models": [
+            "ensemble/5e5d87f6e476845bd8000002",
+            "model/5e5d87f6e476845bd8000003",
+        ],
+    }))
+    .unwrap();
+    assert_eq!(optiml.metric, Some(Metric::AreaUnderRocCurve));
+    match optiml.best_model() {
+        Some(AnyResourceId::Ensemble(id)) => {
+            assert_eq!(id.as_str(), "ensemble/5e5d87f6e476845bd8000002")
+        }
+        other => panic!("unexpected best model {:?}", other),
+    }
+}
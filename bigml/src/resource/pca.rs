@@ -0,0 +1,120 @@
+//! https://bigml.com/api/pcas
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+
+/// A principal component analysis (PCA) of a dataset, which can be used to
+/// reduce the number of dimensions in the data. Use a `Projection` or
+/// `BatchProjection` to transform data using a PCA.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "pca"]
+#[non_exhaustive]
+pub struct Pca {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Pca>,
+
+    /// The current status of this PCA.
+    pub status: GenericStatus,
+
+    /// The dataset we analyzed.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// Information about our principal components. This is only present
+    /// once the PCA has been created.
+    #[serde(default)]
+    pub pca: Option<PcaInfo>,
+}
+
+/// Information about the principal components of a PCA.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct PcaInfo {
+    /// The fraction of the variance explained by each component, from most
+    /// to least important.
+    #[serde(default)]
+    pub variance: Vec<f64>,
+
+    /// The fraction of the variance explained by each component and all the
+    /// components before it.
+    #[serde(default)]
+    pub cumulative_variance: Vec<f64>,
+}
+
+impl PcaInfo {
+    /// The number of components needed to explain at least `fraction` of the
+    /// variance, or `None` if all our components together explain less than
+    /// that.
+    pub fn components_explaining(&self, fraction: f64) -> Option<usize> {
+        self.cumulative_variance
+            .iter()
+            .position(|&cumulative| cumulative >= fraction)
+            .map(|idx| idx + 1)
+    }
+}
+
+/// Arguments used to create a PCA.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Pca")]
+#[non_exhaustive]
+pub struct Args {
+    /// The ID of the dataset to analyze.
+    pub dataset: Id<Dataset>,
+
+    /// The IDs of fields which should not be analyzed.
+    pub excluded_fields: Vec<String>,
+
+    /// The IDs of the fields to analyze. If empty, BigML uses all fields.
+    pub input_fields: Vec<String>,
+
+    /// The name of this PCA.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// Should fields be scaled to have unit variance? BigML defaults to
+    /// `true`.
+    pub standardized: Option<bool>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn from_dataset(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            excluded_fields: vec![],
+            input_fields: vec![],
+            name: None,
+            project: None,
+            standardized: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn components_explaining_variance() {
+    let info: PcaInfo = serde_json::from_value(serde_json::json!({
+        "variance": [0.7, 0.2, 0.1],
+        "cumulative_variance": [0.7, 0.9, 1.0],
+    }))
+    .unwrap();
+    assert_eq!(info.components_explaining(0.5), Some(1));
+    assert_eq!(info.components_explaining(0.9), Some(2));
+    assert_eq!(info.components_explaining(1.5), None);
+}
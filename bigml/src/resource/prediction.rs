@@ -0,0 +1,187 @@
+//! A single prediction made using a model or ensemble.
+
+use serde::de::Unexpected;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::model::Output;
+use super::status::*;
+use super::{Ensemble, Fusion, Model, Project, Resource, ResourceCommon, Updatable};
+
+/// A single prediction made using a model or ensemble.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "prediction"]
+#[non_exhaustive]
+pub struct Prediction {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Prediction>,
+
+    /// The current status of this prediction.
+    pub status: GenericStatus,
+
+    /// The input values used to make this prediction, keyed by field ID or
+    /// name.
+    #[serde(default)]
+    pub input_data: HashMap<String, serde_json::Value>,
+
+    /// The ID of the field we predicted.
+    pub objective_field: Option<String>,
+
+    /// The predicted value.
+    pub output: Option<Output>,
+
+    /// How confident we are in `output`, for classification models.
+    pub confidence: Option<f64>,
+
+    /// The probability of `output`, for classification models.
+    pub probability: Option<f64>,
+}
+
+/// A resource which can be used to make predictions.
+///
+/// When used as part of `Args`, this serializes as a key naming the resource
+/// type, such as `"model": "model/123"`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub enum Predictor {
+    /// Predict using an ensemble.
+    #[serde(rename = "ensemble")]
+    Ensemble(Id<Ensemble>),
+    /// Predict using a fusion of several models.
+    #[serde(rename = "fusion")]
+    Fusion(Id<Fusion>),
+    /// Predict using a decision tree.
+    #[serde(rename = "model")]
+    Model(Id<Model>),
+}
+
+impl From<Id<Ensemble>> for Predictor {
+    fn from(id: Id<Ensemble>) -> Self {
+        Predictor::Ensemble(id)
+    }
+}
+
+impl From<Id<Fusion>> for Predictor {
+    fn from(id: Id<Fusion>) -> Self {
+        Predictor::Fusion(id)
+    }
+}
+
+impl From<Id<Model>> for Predictor {
+    fn from(id: Id<Model>) -> Self {
+        Predictor::Model(id)
+    }
+}
+
+/// How should we handle missing input values?
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MissingStrategy {
+    /// Stop at the last tree node we reached before needing a missing value.
+    LastPrediction,
+    /// Combine the predictions of all possible subtrees.
+    Proportional,
+}
+
+impl<'de> Deserialize<'de> for MissingStrategy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u64::deserialize(deserializer)? {
+            0 => Ok(MissingStrategy::LastPrediction),
+            1 => Ok(MissingStrategy::Proportional),
+            code => {
+                let unexpected = Unexpected::Unsigned(code);
+                let expected = "0 or 1";
+                Err(<D::Error as serde::de::Error>::invalid_value(
+                    unexpected, &expected,
+                ))
+            }
+        }
+    }
+}
+
+impl Serialize for MissingStrategy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let code: u64 = match *self {
+            MissingStrategy::LastPrediction => 0,
+            MissingStrategy::Proportional => 1,
+        };
+        code.serialize(serializer)
+    }
+}
+
+/// Arguments used to create a prediction.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Prediction")]
+#[non_exhaustive]
+pub struct Args {
+    /// The model or ensemble to use.
+    #[serde(flatten)]
+    pub predictor: Predictor,
+
+    /// Input values, keyed by field ID or name.
+    pub input_data: HashMap<String, serde_json::Value>,
+
+    /// How should we handle missing input values?
+    pub missing_strategy: Option<MissingStrategy>,
+
+    /// The name of this prediction.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new<P: Into<Predictor>>(
+        predictor: P,
+        input_data: HashMap<String, serde_json::Value>,
+    ) -> Args {
+        Args {
+            predictor: predictor.into(),
+            input_data,
+            missing_strategy: None,
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+#[test]
+fn serialize_args() {
+    use serde_json::json;
+
+    let model: Id<Model> = "model/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut input_data = HashMap::new();
+    input_data.insert("petal length".to_owned(), json!(2.5));
+    let mut args = Args::new(model, input_data);
+    args.missing_strategy = Some(MissingStrategy::Proportional);
+    assert_eq!(
+        json!(args),
+        json!({
+            "model": "model/5e5d87f6e476845bd8000000",
+            "input_data": { "petal length": 2.5 },
+            "missing_strategy": 1,
+        })
+    );
+}
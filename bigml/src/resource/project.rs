@@ -0,0 +1,56 @@
+//! https://bigml.com/api/projects
+
+use serde::{Deserialize, Serialize};
+
+use super::id::*;
+use super::status::*;
+use super::{Resource, ResourceCommon, Updatable};
+
+/// A project, which is used to group related resources.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "project"]
+#[non_exhaustive]
+pub struct Project {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Project>,
+
+    /// The current status of this project.
+    pub status: GenericStatus,
+}
+
+/// Arguments used to create a project.
+#[bigml_args]
+#[derive(Args, Debug, Default, Serialize)]
+#[args(resource = "Project")]
+#[non_exhaustive]
+pub struct Args {
+    /// The category code which best describes this project.
+    pub category: Option<i64>,
+
+    /// A human-readable description of this project.
+    pub description: Option<String>,
+
+    /// The name of this project.
+    pub name: Option<String>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value for a project with the specified name.
+    pub fn new<S: Into<String>>(name: S) -> Args {
+        Args {
+            name: Some(name.into()),
+            ..Args::default()
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! https://bigml.com/api/projections
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::id::*;
+use super::status::*;
+use super::{Pca, Project, Resource, ResourceCommon, Updatable};
+
+/// A single row of data projected onto the principal components of a `Pca`.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "projection"]
+#[non_exhaustive]
+pub struct Projection {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Projection>,
+
+    /// The current status of this projection.
+    pub status: GenericStatus,
+
+    /// The PCA used to make this projection.
+    pub pca: Option<Id<Pca>>,
+
+    /// The input values used to make this projection, keyed by field ID or
+    /// name.
+    #[serde(default)]
+    pub input_data: HashMap<String, serde_json::Value>,
+
+    /// The result of this projection.
+    #[serde(default)]
+    pub projection: Option<ProjectionResult>,
+}
+
+/// The result of a projection.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ProjectionResult {
+    /// The value of each principal component, keyed by component name (such
+    /// as `"PC1"`).
+    pub result: HashMap<String, f64>,
+}
+
+/// Arguments used to create a projection.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Projection")]
+#[non_exhaustive]
+pub struct Args {
+    /// The PCA to use.
+    pub pca: Id<Pca>,
+
+    /// Input values, keyed by field ID or name.
+    pub input_data: HashMap<String, serde_json::Value>,
+
+    /// The name of this projection.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value.
+    pub fn new(pca: Id<Pca>, input_data: HashMap<String, serde_json::Value>) -> Args {
+        Args {
+            pca,
+            input_data,
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
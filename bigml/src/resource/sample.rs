@@ -0,0 +1,265 @@
+//! https://bigml.com/api/samples
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::id::*;
+use super::source::Optype;
+use super::status::*;
+use super::{Dataset, Project, Resource, ResourceCommon, Updatable};
+use crate::errors::*;
+
+/// An in-memory sample of a dataset, which can be used to quickly look at
+/// rows of data. Use `Client::fetch_sample_rows` to retrieve rows.
+///
+/// TODO: Still lots of missing fields.
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
+#[api_name = "sample"]
+#[non_exhaustive]
+pub struct Sample {
+    /// Common resource information. These fields will be serialized at the
+    /// top-level of this structure by `serde`.
+    #[serde(flatten)]
+    #[updatable(flatten)]
+    pub common: ResourceCommon,
+
+    /// The ID of this resource.
+    pub resource: Id<Sample>,
+
+    /// The current status of this sample.
+    pub status: GenericStatus,
+
+    /// The dataset this sample was taken from.
+    pub dataset: Option<Id<Dataset>>,
+
+    /// Rows from this sample. These are only present when the sample was
+    /// fetched with row options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<Rows>,
+}
+
+/// Arguments used to create a sample.
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Sample")]
+#[non_exhaustive]
+pub struct Args {
+    /// The dataset to sample.
+    pub dataset: Id<Dataset>,
+
+    /// The name of this sample.
+    pub name: Option<String>,
+
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// User-defined tags.
+    pub tags: Vec<String>,
+}
+
+impl Args {
+    /// Create a new `Args` value for sampling `dataset`.
+    pub fn new(dataset: Id<Dataset>) -> Args {
+        Args {
+            dataset,
+            name: None,
+            project: None,
+            tags: vec![],
+        }
+    }
+}
+
+/// Which rows to retrieve from a sample.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct RowsOptions {
+    /// The maximum number of rows to return.
+    pub rows: Option<u64>,
+
+    /// The number of rows to skip.
+    pub row_offset: Option<u64>,
+
+    /// The field ID to sort rows by. Prefix it with `-` to sort in
+    /// descending order.
+    pub row_order: Option<String>,
+
+    /// The field IDs to include in each row. If empty, all fields are
+    /// returned.
+    pub fields: Vec<String>,
+}
+
+impl RowsOptions {
+    /// Return at most `rows` rows.
+    pub fn rows(mut self, rows: u64) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    /// Skip the first `row_offset` rows.
+    pub fn row_offset(mut self, row_offset: u64) -> Self {
+        self.row_offset = Some(row_offset);
+        self
+    }
+
+    /// Sort rows by the field with ID `field`. Prefix it with `-` to sort in
+    /// descending order.
+    pub fn row_order<S: Into<String>>(mut self, field: S) -> Self {
+        self.row_order = Some(field.into());
+        self
+    }
+
+    /// Only include the field with ID `field` in each row. May be called
+    /// more than once.
+    pub fn field<S: Into<String>>(mut self, field: S) -> Self {
+        self.fields.push(field.into());
+        self
+    }
+
+    /// The query parameters BigML uses for these options.
+    pub(crate) fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![("rows", self.rows.unwrap_or(DEFAULT_ROWS).to_string())];
+        if let Some(row_offset) = self.row_offset {
+            pairs.push(("row_offset", row_offset.to_string()));
+        }
+        if let Some(row_order) = &self.row_order {
+            pairs.push(("row_order_by", row_order.to_owned()));
+        }
+        if !self.fields.is_empty() {
+            pairs.push(("row_fields", self.fields.join(",")));
+        }
+        pairs
+    }
+}
+
+/// BigML returns no rows unless asked, so ask for a reasonable number by
+/// default.
+const DEFAULT_ROWS: u64 = 10;
+
+/// Rows retrieved from a sample.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Rows {
+    /// The fields in each row, in order.
+    pub fields: Vec<RowField>,
+
+    /// The rows themselves. Each value corresponds to the field at the same
+    /// position in `fields`.
+    #[serde(default)]
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Rows {
+    /// Deserialize each row as a `D`, using field names as keys. This works
+    /// well with structs deriving `Deserialize`.
+    pub fn deserialize_rows<D: DeserializeOwned>(&self) -> Result<Vec<D>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let object = self
+                    .fields
+                    .iter()
+                    .zip(row)
+                    .map(|(field, value)| (field.name.clone(), value.clone()))
+                    .collect::<Map<String, Value>>();
+                Ok(serde_json::from_value(Value::Object(object))?)
+            })
+            .collect()
+    }
+}
+
+/// A field included in `Rows`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RowField {
+    /// The ID of this field.
+    pub id: String,
+
+    /// The name of this field.
+    pub name: String,
+
+    /// The type of this field.
+    pub optype: Optype,
+}
+
+#[test]
+fn rows_can_be_deserialized() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Flower {
+        species: String,
+        petal_length: f64,
+    }
+
+    let sample: Sample = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "sample/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The sample has been created" },
+        "dataset": "dataset/5e5d87f6e476845bd8000001",
+        "sample": {
+            "fields": [
+                { "id": "000002", "name": "petal_length", "optype": "numeric" },
+                { "id": "000004", "name": "species", "optype": "categorical" },
+            ],
+            "rows": [[1.4, "Iris-setosa"], [4.7, "Iris-versicolor"]],
+        },
+    }))
+    .unwrap();
+    let flowers: Vec<Flower> = sample.sample.unwrap().deserialize_rows().unwrap();
+    assert_eq!(
+        flowers,
+        vec![
+            Flower {
+                species: "Iris-setosa".to_owned(),
+                petal_length: 1.4,
+            },
+            Flower {
+                species: "Iris-versicolor".to_owned(),
+                petal_length: 4.7,
+            },
+        ],
+    );
+}
+
+#[test]
+fn rows_options_use_bigml_query_names() {
+    let options = RowsOptions::default()
+        .rows(5)
+        .row_offset(10)
+        .row_order("-000002")
+        .field("000002")
+        .field("000004");
+    assert_eq!(
+        options.query_pairs(),
+        vec![
+            ("rows", "5".to_owned()),
+            ("row_offset", "10".to_owned()),
+            ("row_order_by", "-000002".to_owned()),
+            ("row_fields", "000002,000004".to_owned()),
+        ],
+    );
+}
+
+#[test]
+fn args_have_builder_methods() {
+    let dataset: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse().unwrap();
+    let project: Id<Project> = "project/5e5d87f6e476845bd8000001".parse().unwrap();
+    let args = Args::new(dataset)
+        .with_name("iris sample")
+        .with_project(project)
+        .with_tags(vec!["a", "b"]);
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        serde_json::json!({
+            "dataset": "dataset/5e5d87f6e476845bd8000000",
+            "name": "iris sample",
+            "project": "project/5e5d87f6e476845bd8000001",
+            "tags": ["a", "b"],
+        }),
+    );
+}
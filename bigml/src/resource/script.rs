@@ -1,24 +1,32 @@
 //! A WhizzML script on BigML.
 
 use serde::{Deserialize, Serialize};
-use std::{fmt, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+};
 
 use super::id::*;
 use super::library::Library;
 use super::status::*;
-use super::{Resource, ResourceCommon};
+use super::{Project, Resource, ResourceCommon, Updatable};
+use crate::client::Client;
 use crate::errors::*;
+use crate::list::{ListOptions, ListQuery};
 
 /// A WhizzML script on BigML.
 ///
 /// TODO: Still lots of missing fields.
-#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "script"]
 #[non_exhaustive]
 pub struct Script {
     /// Common resource information. These fields will be serialized at the
     /// top-level of this structure by `serde`.
     #[serde(flatten)]
+    #[updatable(flatten)]
     pub common: ResourceCommon,
 
     /// The ID of this resource.
@@ -29,41 +37,53 @@ pub struct Script {
 
     /// The source code of this script.
     pub source_code: String,
+
+    /// The libraries imported by this script.
+    #[serde(default)]
+    pub imports: Vec<Id<Library>>,
+
+    /// The input declarations of this script.
+    #[serde(default)]
+    #[updatable]
+    pub inputs: Vec<Input>,
+
+    /// The output declarations of this script.
+    #[serde(default)]
+    #[updatable]
+    pub outputs: Vec<Output>,
 }
 
 /// Arguments used to create a new BigML script.
-#[derive(Debug, Serialize)]
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Script")]
 #[non_exhaustive]
 pub struct Args {
     /// The category code which best describes this script.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<i64>,
 
     /// A human-readable description of this script.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     /// A list of "library/..." identifiers to import.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub imports: Vec<Id<Library>>,
 
     /// A list of script input declarations.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub inputs: Vec<Input>,
 
     /// A human-readable name for this script.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// A list of script output declarations.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub outputs: Vec<Output>,
 
     /// The source code of this script.
     pub source_code: String,
 
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
     /// User-defined tags.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 }
 
@@ -78,17 +98,175 @@ impl Args {
             name: Default::default(),
             outputs: Default::default(),
             source_code: source_code.into(),
+            project: Default::default(),
             tags: Default::default(),
         }
     }
+
+    /// Add the libraries named in `names` to `imports`, looking up their IDs
+    /// in `libraries`. This is typically used with the output of
+    /// `Client::create_libraries`.
+    pub fn add_local_imports<S: AsRef<str>>(
+        &mut self,
+        names: &[S],
+        libraries: &HashMap<String, Id<Library>>,
+    ) -> Result<()> {
+        for name in names {
+            let name = name.as_ref();
+            let id = libraries.get(name).ok_or_else(|| {
+                format_err!("WhizzML script imports unknown library {:?}", name)
+            })?;
+            self.imports.push(id.to_owned());
+        }
+        Ok(())
+    }
+}
+
+/// WhizzML source code containing `{{name}}` placeholders, which are
+/// replaced with values supplied from Rust. This is useful for deploying
+/// parameterized scripts from build scripts or deployment tools.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> bigml::Result<()> {
+/// use bigml::{resource::{script::Template, Resource}, Client};
+///
+/// let client = Client::new_from_env()?;
+/// let script = Template::from_file("whizzml/train.whizzml")?
+///     .set("max-models", "10")
+///     .set_quoted("tag", "nightly")
+///     .deploy(&client, "train")
+///     .await?;
+/// println!("deployed {}", script.id());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Template {
+    /// Our unrendered source code.
+    source: String,
+
+    /// The values of our placeholders.
+    values: BTreeMap<String, String>,
 }
 
-impl super::Args for Args {
-    type Resource = Script;
+impl Template {
+    /// Create a template from WhizzML `source` code.
+    pub fn new<S: Into<String>>(source: S) -> Template {
+        Template {
+            source: source.into(),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Load a template from a WhizzML file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Template> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| format_err!("could not read {}: {}", path.display(), e))?;
+        Ok(Template::new(source))
+    }
+
+    /// Replace the placeholder `{{name}}` with `value`, exactly as written.
+    pub fn set<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// Replace the placeholder `{{name}}` with a WhizzML string literal
+    /// containing `value`.
+    pub fn set_quoted<N, V>(self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: AsRef<str>,
+    {
+        let literal = serde_json::Value::String(value.as_ref().to_owned()).to_string();
+        self.set(name, literal)
+    }
+
+    /// Substitute our values into our source code. Fails if a placeholder has
+    /// no value, or if a value has no placeholder.
+    pub fn render(&self) -> Result<String> {
+        let mut output = String::with_capacity(self.source.len());
+        let mut used = BTreeSet::new();
+        let mut missing = BTreeSet::new();
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| {
+                format_err!("unterminated placeholder in WhizzML template")
+            })?;
+            let name = after[..end].trim();
+            match self.values.get(name) {
+                Some(value) => {
+                    output.push_str(value);
+                    used.insert(name);
+                }
+                None => {
+                    missing.insert(name);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+
+        if !missing.is_empty() {
+            return Err(format_err!(
+                "no values for WhizzML template placeholders: {}",
+                missing.into_iter().collect::<Vec<_>>().join(", "),
+            ));
+        }
+        let unused = self
+            .values
+            .keys()
+            .filter(|name| !used.contains(name.as_str()))
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        if !unused.is_empty() {
+            return Err(format_err!(
+                "values supplied for unknown WhizzML template placeholders: {}",
+                unused.join(", "),
+            ));
+        }
+        Ok(output)
+    }
+
+    /// Render this template, and return `Args` for creating a script.
+    pub fn to_args(&self) -> Result<Args> {
+        Ok(Args::new(self.render()?))
+    }
+
+    /// Render this template and make sure a script named `name` with the
+    /// resulting source code exists, waiting until it is ready.
+    ///
+    /// BigML does not allow changing the source code of a script, so if the
+    /// newest script named `name` has different source code, we create a new
+    /// script with the same name.
+    pub async fn deploy(&self, client: &Client, name: &str) -> Result<Script> {
+        let mut args = self.to_args()?;
+        let query = ListQuery::new().name(name).order_by_desc("created");
+        let options = ListOptions::default().limit(1).query(query);
+        if let Some(existing) =
+            client.list::<Script>(&options).await?.into_iter().next()
+        {
+            let existing = client.fetch(existing.id()).await?;
+            if existing.source_code == args.source_code {
+                debug!("{} is already up to date", existing.id());
+                return client.wait(existing.id()).await;
+            }
+        }
+        args.name = Some(name.to_owned());
+        client.create_and_wait(&args).await
+    }
 }
 
 /// A script input declaration.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[non_exhaustive]
 pub struct Input {
     /// The variable name of this input.
@@ -102,6 +280,23 @@ pub struct Input {
     pub description: Option<String>,
 }
 
+/// Inputs can only be updated as a whole.
+impl Updatable for Input {
+    type Update = Self;
+
+    fn to_update(&self) -> Self::Update {
+        self.clone()
+    }
+
+    fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+        if self == other {
+            None
+        } else {
+            Some(other.clone())
+        }
+    }
+}
+
 impl Input {
     /// Create a new `Input` value.
     pub fn new<S: Into<String>>(name: S, type_: Type) -> Input {
@@ -115,7 +310,7 @@ impl Input {
 }
 
 /// A script output declaration.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[non_exhaustive]
 pub struct Output {
     /// The variable name of this output.
@@ -127,6 +322,23 @@ pub struct Output {
     pub description: Option<String>,
 }
 
+/// Outputs can only be updated as a whole.
+impl Updatable for Output {
+    type Update = Self;
+
+    fn to_update(&self) -> Self::Update {
+        self.clone()
+    }
+
+    fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+        if self == other {
+            None
+        } else {
+            Some(other.clone())
+        }
+    }
+}
+
 impl Output {
     /// Create a new `Output` value.
     pub fn new<S: Into<String>>(name: S, type_: Type) -> Output {
@@ -167,7 +379,7 @@ macro_rules! declare_type_enum {
                 match s {
                     $( $api_name => Ok(Type::$name), )*
                     _ => {
-                        Err(format_err!("Unknown BigML type: {:?}", s).into())
+                        Err(format_err!("Unknown BigML type: {:?}", s))
                     }
                 }
             }
@@ -225,6 +437,20 @@ declare_type_enum! {
     Configuration => "configuration-id",
 }
 
+#[test]
+fn render_template() {
+    let template = Template::new("(define n {{ count }})\n(define tag {{tag}})")
+        .set("count", "3")
+        .set_quoted("tag", "say \"hi\"");
+    assert_eq!(
+        template.render().unwrap(),
+        "(define n 3)\n(define tag \"say \\\"hi\\\"\")",
+    );
+    assert!(Template::new("{{missing}}").render().is_err());
+    assert!(Template::new("").set("unused", "1").render().is_err());
+    assert!(Template::new("{{open").render().is_err());
+}
+
 #[test]
 fn parse_type() {
     let ty: Type = "categorical".parse().unwrap();
@@ -235,3 +461,30 @@ fn parse_type() {
 fn display_type() {
     assert_eq!(format!("{}", Type::Categorical), "categorical");
 }
+
+#[test]
+fn serialize_script_update() {
+    use super::ResourceCommonUpdate;
+    let update = ScriptUpdate {
+        common: Some(ResourceCommonUpdate {
+            description: Some("Updated".to_owned()),
+            ..ResourceCommonUpdate::default()
+        }),
+        inputs: Some(vec![Input::new("dataset", Type::DatasetId)].into()),
+        ..ScriptUpdate::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        serde_json::json!({
+            "description": "Updated",
+            "inputs": [
+                {
+                    "name": "dataset",
+                    "type": "dataset-id",
+                    "default": null,
+                    "description": null,
+                },
+            ],
+        }),
+    );
+}
@@ -2,16 +2,19 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use url::Url;
 
 use super::id::*;
 use super::status::*;
-use super::{Resource, ResourceCommon, Updatable};
+use super::{ExternalConnector, Project, Resource, ResourceCommon, Updatable};
 
 /// A data source used by BigML.
 ///
 /// TODO: Still lots of missing fields.
 #[derive(Clone, Debug, Deserialize, Resource, Serialize, Updatable)]
 #[api_name = "source"]
+#[ready_when(summarized)]
 #[non_exhaustive]
 pub struct Source {
     /// Common resource information. These fields will be serialized at the
@@ -24,7 +27,7 @@ pub struct Source {
     pub resource: Id<Source>,
 
     /// The status of this source.
-    pub status: GenericStatus,
+    pub status: SourceStatus,
 
     /// The name of the file uploaded.
     pub file_name: Option<String>,
@@ -42,32 +45,239 @@ pub struct Source {
     /// The fields in this source, keyed by BigML internal ID.
     #[updatable]
     pub fields: Option<HashMap<String, Field>>,
+
+    /// How BigML parses this source.
+    #[serde(default)]
+    #[updatable]
+    pub source_parser: Option<SourceParser>,
+}
+
+/// Source-specific status information, including details about any data
+/// which BigML could not parse.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct SourceStatus {
+    /// Status code.
+    pub code: StatusCode,
+
+    /// Human-readable status message.
+    pub message: String,
+
+    /// Number of milliseconds which were needed to create this resource.
+    pub elapsed: Option<u64>,
+
+    /// Number between 0.0 and 1.0 representing the progress of creating
+    /// this resource.
+    pub progress: Option<f32>,
+
+    /// Values which could not be parsed, keyed by field ID.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub field_errors: HashMap<String, FieldErrors>,
+
+    /// Rows which could not be parsed at all, typically because they had
+    /// the wrong number of columns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_format_errors: Option<RowFormatErrors>,
+}
+
+impl Status for SourceStatus {
+    fn code(&self) -> StatusCode {
+        self.code
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn elapsed(&self) -> Option<u64> {
+        self.elapsed
+    }
+
+    fn progress(&self) -> Option<f32> {
+        self.progress
+    }
+
+    fn full_message(&self) -> String {
+        let mut message = self.message.clone();
+        let field_errors = self
+            .field_errors
+            .values()
+            .map(|errors| errors.total)
+            .sum::<u64>();
+        if field_errors > 0 {
+            message.push_str(&format!(" ({} unparseable values)", field_errors));
+        }
+        if let Some(row_errors) = &self.row_format_errors {
+            if row_errors.total > 0 {
+                message.push_str(&format!(
+                    " ({} badly formatted rows)",
+                    row_errors.total,
+                ));
+            }
+        }
+        message
+    }
+}
+
+impl SourceStatus {
+    /// Summarize the data which BigML could not parse.
+    pub fn error_report(&self) -> SourceErrorReport {
+        let mut errors = vec![];
+        let mut total_errors = 0;
+        if let Some(row_errors) = &self.row_format_errors {
+            total_errors += row_errors.total;
+            for row in &row_errors.sample {
+                errors.push(SourceError {
+                    line: Some(row.line),
+                    content: row.content.clone(),
+                    reason: "badly formatted row".to_owned(),
+                });
+            }
+        }
+        let mut field_ids = self.field_errors.keys().collect::<Vec<_>>();
+        field_ids.sort();
+        for field_id in field_ids {
+            let field_errors = &self.field_errors[field_id];
+            total_errors += field_errors.total;
+            let field_name = field_errors.name.as_deref().unwrap_or(field_id);
+            for value in &field_errors.sample {
+                let content = match value {
+                    serde_json::Value::String(s) => s.to_owned(),
+                    other => other.to_string(),
+                };
+                errors.push(SourceError {
+                    line: None,
+                    content,
+                    reason: format!("could not parse value of field {}", field_name),
+                });
+            }
+        }
+        SourceErrorReport {
+            total_errors,
+            errors,
+        }
+    }
+}
+
+/// Data which BigML could not parse while creating a source. See
+/// `Client::source_errors`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SourceErrorReport {
+    /// The total number of bad rows and values, including those which are
+    /// not included in `errors`.
+    pub total_errors: u64,
+
+    /// A sample of the errors.
+    pub errors: Vec<SourceError>,
+}
+
+impl SourceErrorReport {
+    /// Did BigML parse all our data successfully?
+    pub fn is_empty(&self) -> bool {
+        self.total_errors == 0
+    }
+}
+
+/// Something which BigML could not parse.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct SourceError {
+    /// The line number of the bad data, if known. BigML only reports line
+    /// numbers for badly formatted rows.
+    pub line: Option<u64>,
+
+    /// The raw content which could not be parsed. This is either a row or a
+    /// single value.
+    pub content: String,
+
+    /// Why this could not be parsed.
+    pub reason: String,
+}
+
+/// Values in a single field which BigML could not parse.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct FieldErrors {
+    /// The name of this field.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The total number of values which could not be parsed.
+    pub total: u64,
+
+    /// A sample of the values which could not be parsed.
+    #[serde(default)]
+    pub sample: Vec<serde_json::Value>,
+}
+
+/// Rows which BigML could not parse.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RowFormatErrors {
+    /// The total number of rows which could not be parsed.
+    pub total: u64,
+
+    /// A sample of the rows which could not be parsed.
+    #[serde(default)]
+    pub sample: Vec<BadRow>,
+}
+
+/// A row which BigML could not parse. BigML represents this as a
+/// `[line, content]` pair.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "(u64, String)", into = "(u64, String)")]
+#[non_exhaustive]
+pub struct BadRow {
+    /// The line number of this row in the original file.
+    pub line: u64,
+
+    /// The raw content of this row.
+    pub content: String,
+}
+
+impl From<(u64, String)> for BadRow {
+    fn from((line, content): (u64, String)) -> Self {
+        BadRow { line, content }
+    }
+}
+
+impl From<BadRow> for (u64, String) {
+    fn from(row: BadRow) -> Self {
+        (row.line, row.content)
+    }
 }
 
 /// Arguments used to create a data source.
 ///
 /// TODO: Add more fields so people need to use `update` less.
-#[derive(Debug, Serialize)]
+#[bigml_args]
+#[derive(Args, Debug, Serialize)]
+#[args(resource = "Source")]
 #[non_exhaustive]
 pub struct Args {
     /// The URL of the data source.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub remote: Option<String>,
 
     /// The raw data to use.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<String>,
 
+    /// A query to run against an external database.
+    pub external_data: Option<ExternalData>,
+
     /// Set to true if you want to avoid date expansion into year, day of week, etc.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub disable_datetime: Option<bool>,
 
     /// The name of this source.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// The project to put this resource in.
+    pub project: Option<Id<Project>>,
+
+    /// How BigML should parse this source.
+    pub source_parser: Option<SourceParser>,
+
     /// User-defined tags.
-    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 }
 
@@ -77,8 +287,11 @@ impl Args {
         Args {
             remote: Some(remote.into()),
             data: None,
+            external_data: None,
             disable_datetime: None,
             name: None,
+            project: None,
+            source_parser: None,
             tags: vec![],
         }
     }
@@ -88,15 +301,267 @@ impl Args {
         Args {
             remote: None,
             data: Some(data.into()),
+            external_data: None,
             disable_datetime: None,
             name: None,
+            project: None,
+            source_parser: None,
             tags: vec![],
         }
     }
+
+    /// Create a new `Args` which runs `query` against the database specified
+    /// by `connector`.
+    pub fn external_data<S: Into<String>>(
+        connector: Id<ExternalConnector>,
+        query: S,
+    ) -> Args {
+        Args {
+            remote: None,
+            data: None,
+            external_data: Some(ExternalData {
+                externalconnector_id: connector,
+                query: Some(query.into()),
+                tables: vec![],
+            }),
+            disable_datetime: None,
+            name: None,
+            project: None,
+            source_parser: None,
+            tags: vec![],
+        }
+    }
+
+    /// Create a new `Args` from a remote data source in cloud storage or on
+    /// the web.
+    pub fn remote_source(remote: &RemoteSource) -> Args {
+        Args::remote(remote.to_url())
+    }
 }
 
-impl super::Args for Args {
-    type Resource = Source;
+/// Data to import from an external database using an `ExternalConnector`.
+#[derive(Clone, Debug, Serialize)]
+#[non_exhaustive]
+pub struct ExternalData {
+    /// The connector to use.
+    pub externalconnector_id: Id<ExternalConnector>,
+
+    /// An SQL query (or for Elasticsearch, a JSON query) to run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Tables to import in their entirety, instead of running `query`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<String>,
+}
+
+/// A remote location from which BigML can fetch data, with any credentials
+/// it needs. BigML expects these to be encoded as URLs with special schemes,
+/// which `to_url` builds for you.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum RemoteSource {
+    /// A public HTTP or HTTPS URL.
+    Http(Url),
+    /// An object in Amazon S3.
+    S3 {
+        /// The S3 bucket.
+        bucket: String,
+        /// The key of the object in `bucket`.
+        key: String,
+        /// Credentials, if the object isn't public.
+        credentials: Option<S3Credentials>,
+    },
+    /// An object in Google Cloud Storage.
+    Gcs {
+        /// The GCS bucket.
+        bucket: String,
+        /// The name of the object in `bucket`.
+        object: String,
+        /// An OAuth access token, if the object isn't public.
+        token: Option<Secret>,
+    },
+    /// A blob in Azure Blob Storage.
+    AzureBlob {
+        /// The Azure storage container.
+        container: String,
+        /// The name of the blob in `container`.
+        blob: String,
+        /// Credentials, if the blob isn't public.
+        credentials: Option<AzureCredentials>,
+    },
+}
+
+impl RemoteSource {
+    /// Build the URL which BigML expects for this source. Note that this
+    /// will contain any credentials.
+    pub fn to_url(&self) -> String {
+        match self {
+            RemoteSource::Http(url) => url.to_string(),
+            RemoteSource::S3 {
+                bucket,
+                key,
+                credentials,
+            } => {
+                let mut url = storage_url("s3", bucket, key);
+                if let Some(credentials) = credentials {
+                    let mut query = url.query_pairs_mut();
+                    query.append_pair("access-key", &credentials.access_key);
+                    query.append_pair("secret-key", &credentials.secret_key.0);
+                    if let Some(region) = &credentials.region {
+                        query.append_pair("region", region);
+                    }
+                }
+                url.to_string()
+            }
+            RemoteSource::Gcs {
+                bucket,
+                object,
+                token,
+            } => {
+                let mut url = storage_url("gcs", bucket, object);
+                if let Some(token) = token {
+                    url.query_pairs_mut().append_pair("token", &token.0);
+                }
+                url.to_string()
+            }
+            RemoteSource::AzureBlob {
+                container,
+                blob,
+                credentials,
+            } => {
+                let mut url = storage_url("azure", container, blob);
+                if let Some(credentials) = credentials {
+                    url.query_pairs_mut()
+                        .append_pair("AccountName", &credentials.account_name)
+                        .append_pair("AccountKey", &credentials.account_key.0);
+                }
+                url.to_string()
+            }
+        }
+    }
+}
+
+/// Build a `scheme://bucket/path` URL for a cloud storage service.
+fn storage_url(scheme: &str, bucket: &str, path: &str) -> Url {
+    let mut url = Url::parse(&format!("{}://{}/", scheme, bucket))
+        // This should only fail for very strange bucket names.
+        .expect("could not build cloud storage URL");
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.clear().extend(path.split('/'));
+    }
+    url
+}
+
+/// A secret value, such as a password or access token, which we don't want
+/// to print in debug output.
+#[derive(Clone)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wrap a secret value.
+    pub fn new<S: Into<String>>(secret: S) -> Secret {
+        Secret(secret.into())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "*****")
+    }
+}
+
+/// Credentials for accessing an object in S3.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct S3Credentials {
+    /// The AWS access key ID.
+    pub access_key: String,
+    /// The AWS secret access key.
+    pub secret_key: Secret,
+    /// The AWS region containing the bucket, if needed.
+    pub region: Option<String>,
+}
+
+impl S3Credentials {
+    /// Create new S3 credentials.
+    pub fn new<S1, S2>(access_key: S1, secret_key: S2) -> S3Credentials
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        S3Credentials {
+            access_key: access_key.into(),
+            secret_key: Secret::new(secret_key),
+            region: None,
+        }
+    }
+
+    /// Specify the AWS region containing the bucket.
+    pub fn region<S: Into<String>>(mut self, region: S) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+}
+
+/// Credentials for accessing a blob in Azure Blob Storage.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AzureCredentials {
+    /// The name of the storage account.
+    pub account_name: String,
+    /// The storage account's access key.
+    pub account_key: Secret,
+}
+
+impl AzureCredentials {
+    /// Create new Azure credentials.
+    pub fn new<S1, S2>(account_name: S1, account_key: S2) -> AzureCredentials
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        AzureCredentials {
+            account_name: account_name.into(),
+            account_key: Secret::new(account_key),
+        }
+    }
+}
+
+/// How BigML should parse a CSV source. Any settings left as `None` will be
+/// guessed by BigML.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Updatable)]
+#[non_exhaustive]
+pub struct SourceParser {
+    /// Does the first row contain field names?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<bool>,
+
+    /// The locale used to parse numbers and dates, such as `"en-US"`.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Values which should be treated as missing.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing_tokens: Option<Vec<String>>,
+
+    /// The character used to quote fields.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+
+    /// The string which separates fields.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+
+    /// Should leading and trailing whitespace be removed from each field?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim: Option<bool>,
 }
 
 /// Information about a field in a data source.
@@ -117,6 +582,32 @@ pub struct Field {
     #[updatable]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub time_formats: Vec<String>,
+
+    /// A short, human-readable label for this field.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// A human-readable description of this field.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Should this field be used as an input when building models by
+    /// default?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred: Option<bool>,
+
+    /// How to split text fields into terms.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term_analysis: Option<TermAnalysis>,
+
+    /// Summary statistics computed by BigML for datasets. These can't be
+    /// updated. Use `Dataset::field_summary` to get typed statistics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<serde_json::Value>,
     // The locale of this field.
     //pub locale: Option<String>,
 
@@ -124,8 +615,46 @@ pub struct Field {
     //pub missing_tokens: Option<Vec<String>>,
 }
 
+/// How BigML should split a text field into terms. See [the BigML
+/// docs][docs] for details.
+///
+/// [docs]: https://bigml.com/api/sources#sr_text_processing
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Updatable)]
+#[non_exhaustive]
+pub struct TermAnalysis {
+    /// Should we analyze this field's text at all?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+
+    /// Should terms which differ only in case be treated as different?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+
+    /// The language of the text, such as `"en"`.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Should words be reduced to their stems?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stem_words: Option<bool>,
+
+    /// Whether to use `"tokens_only"`, `"full_terms_only"` or `"all"`.
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_mode: Option<String>,
+
+    /// Should common words like "the" be ignored?
+    #[updatable]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_stopwords: Option<bool>,
+}
+
 /// The type of a data field.
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, Updatable)]
 #[non_exhaustive]
 pub enum Optype {
     /// Treat this as a date value.
@@ -149,10 +678,6 @@ pub enum Optype {
     Items,
 }
 
-impl Updatable for Optype {
-    type Update = Self;
-}
-
 #[test]
 fn update_source_name() {
     use super::ResourceCommonUpdate;
@@ -166,3 +691,153 @@ fn update_source_name() {
     };
     assert_eq!(json!(source_update), json!({ "name": "example" }));
 }
+
+#[test]
+fn remote_sources_become_urls() {
+    let s3 = RemoteSource::S3 {
+        bucket: "my-bucket".to_owned(),
+        key: "data/iris.csv".to_owned(),
+        credentials: Some(S3Credentials::new("AKIA", "s3cr3t").region("us-east-1")),
+    };
+    assert_eq!(
+        s3.to_url(),
+        "s3://my-bucket/data/iris.csv?access-key=AKIA&secret-key=s3cr3t&region=us-east-1",
+    );
+    assert!(!format!("{:?}", s3).contains("s3cr3t"));
+
+    let gcs = RemoteSource::Gcs {
+        bucket: "my-bucket".to_owned(),
+        object: "iris.csv".to_owned(),
+        token: Some(Secret::new("ya29")),
+    };
+    assert_eq!(gcs.to_url(), "gcs://my-bucket/iris.csv?token=ya29");
+
+    let azure = RemoteSource::AzureBlob {
+        container: "csv".to_owned(),
+        blob: "iris.csv".to_owned(),
+        credentials: Some(AzureCredentials::new("account", "key")),
+    };
+    assert_eq!(
+        azure.to_url(),
+        "azure://csv/iris.csv?AccountName=account&AccountKey=key",
+    );
+
+    let args = Args::remote_source(&RemoteSource::Http(
+        "https://example.com/iris.csv".parse().unwrap(),
+    ));
+    assert_eq!(args.remote.as_deref(), Some("https://example.com/iris.csv"));
+}
+
+#[test]
+fn source_parser_can_be_updated() {
+    let update = SourceUpdate {
+        source_parser: Some(Some(SourceParserUpdate {
+            separator: Some(Some(";".to_owned())),
+            header: Some(Some(false)),
+            ..SourceParserUpdate::default()
+        })),
+        ..SourceUpdate::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        serde_json::json!({
+            "source_parser": { "header": false, "separator": ";" },
+        }),
+    );
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn summarized_sources_are_ready_for_use() {
+    use crate::mock::fixtures;
+
+    let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+    let mut json = fixtures::source(&id);
+    json["status"] = serde_json::json!({ "code": 4, "message": "Summarized" });
+    let source: Source = serde_json::from_value(json).unwrap();
+    assert!(source.is_ready_for(Readiness::Summarized));
+    assert!(!source.is_ready_for(Readiness::Finished));
+}
+
+#[test]
+fn source_status_includes_parse_errors() {
+    let status: SourceStatus = serde_json::from_value(serde_json::json!({
+        "code": 5,
+        "message": "The source has been created",
+        "field_errors": {
+            "000001": { "name": "age", "total": 2, "sample": ["n/a", "?"] },
+        },
+        "row_format_errors": {
+            "total": 1,
+            "sample": [[17, "a,b,c,d"]],
+        },
+    }))
+    .unwrap();
+    assert_eq!(status.field_errors["000001"].sample.len(), 2);
+    assert_eq!(
+        status.row_format_errors.as_ref().unwrap().sample,
+        vec![BadRow {
+            line: 17,
+            content: "a,b,c,d".to_owned(),
+        }],
+    );
+    assert_eq!(
+        status.full_message(),
+        "The source has been created (2 unparseable values) (1 badly formatted rows)",
+    );
+}
+
+#[test]
+fn source_error_report_includes_rows_and_values() {
+    let status: SourceStatus = serde_json::from_value(serde_json::json!({
+        "code": 5,
+        "message": "The source has been created",
+        "field_errors": {
+            "000001": { "name": "age", "total": 3, "sample": ["n/a"] },
+        },
+        "row_format_errors": { "total": 1, "sample": [[17, "a,b,c,d"]] },
+    }))
+    .unwrap();
+    let report = status.error_report();
+    assert_eq!(report.total_errors, 4);
+    assert_eq!(
+        report.errors,
+        vec![
+            SourceError {
+                line: Some(17),
+                content: "a,b,c,d".to_owned(),
+                reason: "badly formatted row".to_owned(),
+            },
+            SourceError {
+                line: None,
+                content: "n/a".to_owned(),
+                reason: "could not parse value of field age".to_owned(),
+            },
+        ],
+    );
+}
+
+#[test]
+fn source_survives_json_round_trip() {
+    let json = serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "description": "",
+        "name": "iris.csv",
+        "shared": false,
+        "subscription": false,
+        "tags": [],
+        "resource": "source/5e5d87f6e476845bd8000000",
+        "status": { "code": 5, "message": "The source has been created" },
+        "file_name": "iris.csv",
+        "md5": "d41d8cd98f00b204e9800998ecf8427e",
+        "size": 4608,
+    });
+    let source: Source = serde_json::from_value(json).unwrap();
+    let serialized = serde_json::to_value(&source).unwrap();
+    assert_eq!(serialized["size"], 4608);
+    assert!(serialized.get("creator").is_none());
+    assert!(serialized.get("project").is_none());
+    let source: Source = serde_json::from_value(serialized).unwrap();
+    assert_eq!(source.size, 4608);
+}
@@ -90,6 +90,19 @@ impl Serialize for StatusCode {
     }
 }
 
+/// How ready must a resource be before we stop waiting for it? See
+/// `Client::wait_until`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Readiness {
+    /// The resource has been completely processed.
+    Finished,
+    /// Summary statistics are available. Some resources, such as sources and
+    /// datasets, can already be used to create other resources at this
+    /// point. For other resources, this is the same as `Finished`.
+    Summarized,
+}
+
 /// Status of a resource.  BigML actually defines many different "status"
 /// types, one for each resource, but quite a few of them have are highly
 /// similar.  This interface tries to generalize over the most common
@@ -107,6 +120,12 @@ pub trait Status {
     /// Number between 0.0 and 1.0 representing the progress of creating
     /// this resource.
     fn progress(&self) -> Option<f32>;
+
+    /// A human-readable status message, plus any other information which
+    /// might help explain an error. Defaults to `message`.
+    fn full_message(&self) -> String {
+        self.message().to_owned()
+    }
 }
 
 /// Status of a generic resource.
@@ -0,0 +1,88 @@
+//! Support for decoding downloaded CSV data into rows.
+
+use bytes::{Buf, BytesMut};
+use csv::{ReaderBuilder, StringRecord};
+use tokio_util::codec::Decoder;
+
+/// A `Decoder` which splits CSV data into `StringRecord`s, correctly handling
+/// quoted fields containing newlines.
+///
+/// We don't try to do anything clever here: each time we're called, we parse
+/// a single record from the start of our buffer. If that record was
+/// terminated before the end of the buffer, we know it's complete. Otherwise,
+/// we wait for more data (or for the end of the stream).
+#[derive(Debug, Default)]
+pub(crate) struct CsvRecordDecoder {}
+
+impl CsvRecordDecoder {
+    /// Create a new decoder.
+    pub(crate) fn new() -> CsvRecordDecoder {
+        CsvRecordDecoder::default()
+    }
+
+    /// Try to parse a single record from the start of `src`. If `eof` is
+    /// false, only return records which we know are complete.
+    fn decode_record(
+        &mut self,
+        src: &mut BytesMut,
+        eof: bool,
+    ) -> Result<Option<StringRecord>, csv::Error> {
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(&src[..]);
+        let mut record = StringRecord::new();
+        if !rdr.read_record(&mut record)? {
+            // Nothing left but (at most) some trailing blank lines.
+            if eof {
+                src.clear();
+            }
+            return Ok(None);
+        }
+        let consumed = rdr.position().byte() as usize;
+        if consumed < src.len() || eof {
+            src.advance(consumed);
+            Ok(Some(record))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Decoder for CsvRecordDecoder {
+    type Item = StringRecord;
+    type Error = csv::Error;
+
+    fn decode(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<StringRecord>, csv::Error> {
+        self.decode_record(src, false)
+    }
+
+    fn decode_eof(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<StringRecord>, csv::Error> {
+        self.decode_record(src, true)
+    }
+}
+
+#[test]
+fn csv_record_decoder_waits_for_complete_records() {
+    let mut decoder = CsvRecordDecoder::new();
+    let mut buf = BytesMut::from(&b"a,\"multi\nline\"\nb,"[..]);
+    assert_eq!(
+        decoder.decode(&mut buf).unwrap().unwrap(),
+        vec!["a", "multi\nline"]
+    );
+    assert!(decoder.decode(&mut buf).unwrap().is_none());
+    buf.extend_from_slice(b"\"quoted, with comma\"\n");
+    assert!(decoder.decode(&mut buf).unwrap().is_none());
+    assert_eq!(
+        decoder.decode_eof(&mut buf).unwrap().unwrap(),
+        vec!["b", "quoted, with comma"],
+    );
+    assert!(decoder.decode_eof(&mut buf).unwrap().is_none());
+    assert!(buf.is_empty());
+}
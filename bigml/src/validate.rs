@@ -0,0 +1,451 @@
+//! Checking that a source or dataset looks the way we expect, before we
+//! spend time and money training models on it.
+//!
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> bigml::Result<()> {
+//! use bigml::{
+//!     resource::{source::Optype, Dataset, Id},
+//!     validate::Expectations,
+//!     Client,
+//! };
+//!
+//! let client = Client::new_from_env()?;
+//! let id: Id<Dataset> = "dataset/5e5d87f6e476845bd8000000".parse()?;
+//! let dataset = client.wait(&id).await?;
+//! let report = Expectations::new()
+//!     .optype("price", Optype::Numeric)
+//!     .max_missing_ratio("price", 0.05)
+//!     .categories_in("color", &["red", "green", "blue"])
+//!     .check_dataset(&dataset)?;
+//! report.ensure_valid()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::errors::*;
+use crate::resource::dataset::FieldSummary;
+use crate::resource::source::{Field, Optype};
+use crate::resource::{Dataset, Source};
+
+/// A set of expectations about the fields of a source or dataset. Fields may
+/// be specified using either their name or their BigML field ID.
+#[derive(Clone, Debug, Default)]
+pub struct Expectations {
+    /// The expectations we check, in the order they were declared.
+    expectations: Vec<Expectation>,
+}
+
+/// A single expectation about a field.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Expectation {
+    /// The field must exist.
+    Exists {
+        /// The name or ID of the field.
+        field: String,
+    },
+    /// The field must have the specified optype.
+    Optype {
+        /// The name or ID of the field.
+        field: String,
+        /// The expected optype.
+        optype: Optype,
+    },
+    /// At most `max` of the field's values may be missing, as a fraction of
+    /// all rows.
+    MaxMissingRatio {
+        /// The name or ID of the field.
+        field: String,
+        /// The largest acceptable fraction of missing values.
+        max: f64,
+    },
+    /// Every category of the field must be in `allowed`.
+    CategoriesIn {
+        /// The name or ID of the field.
+        field: String,
+        /// The categories which may appear.
+        allowed: BTreeSet<String>,
+    },
+}
+
+impl Expectation {
+    /// The name or ID of the field this expectation applies to.
+    pub fn field(&self) -> &str {
+        match self {
+            Expectation::Exists { field }
+            | Expectation::Optype { field, .. }
+            | Expectation::MaxMissingRatio { field, .. }
+            | Expectation::CategoriesIn { field, .. } => field,
+        }
+    }
+}
+
+impl Expectations {
+    /// Create an empty set of expectations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an arbitrary `expectation`.
+    pub fn expect(mut self, expectation: Expectation) -> Self {
+        self.expectations.push(expectation);
+        self
+    }
+
+    /// Expect `field` to exist.
+    pub fn exists<S: Into<String>>(self, field: S) -> Self {
+        self.expect(Expectation::Exists {
+            field: field.into(),
+        })
+    }
+
+    /// Expect `field` to have type `optype`.
+    pub fn optype<S: Into<String>>(self, field: S, optype: Optype) -> Self {
+        self.expect(Expectation::Optype {
+            field: field.into(),
+            optype,
+        })
+    }
+
+    /// Expect at most a `max` fraction of the values of `field` to be
+    /// missing. This can only be checked for datasets.
+    pub fn max_missing_ratio<S: Into<String>>(self, field: S, max: f64) -> Self {
+        self.expect(Expectation::MaxMissingRatio {
+            field: field.into(),
+            max,
+        })
+    }
+
+    /// Expect every category of `field` to appear in `allowed`. This can only
+    /// be checked for datasets.
+    pub fn categories_in<S, C>(self, field: S, allowed: &[C]) -> Self
+    where
+        S: Into<String>,
+        C: AsRef<str>,
+    {
+        self.expect(Expectation::CategoriesIn {
+            field: field.into(),
+            allowed: allowed.iter().map(|c| c.as_ref().to_owned()).collect(),
+        })
+    }
+
+    /// Check our expectations against the fields of `source`. Expectations
+    /// which require summary statistics are reported as
+    /// `ViolationKind::NoSummary`, because sources don't have them.
+    pub fn check_source(&self, source: &Source) -> ValidationReport {
+        let no_fields = HashMap::new();
+        let fields = source.fields.as_ref().unwrap_or(&no_fields);
+        let mut report = ValidationReport::default();
+        for expectation in &self.expectations {
+            if let Some(kind) = check_field(expectation, fields) {
+                report.push(expectation, kind);
+            } else if let Expectation::MaxMissingRatio { .. }
+            | Expectation::CategoriesIn { .. } = expectation
+            {
+                report.push(expectation, ViolationKind::NoSummary);
+            }
+        }
+        report
+    }
+
+    /// Check our expectations against the fields and summary statistics of
+    /// `dataset`. Fails if BigML's summary statistics can't be parsed.
+    pub fn check_dataset(&self, dataset: &Dataset) -> Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+        for expectation in &self.expectations {
+            if let Some(kind) = check_field(expectation, &dataset.fields) {
+                report.push(expectation, kind);
+                continue;
+            }
+            let (id, _) = find_field(&dataset.fields, expectation.field())
+                .expect("field should have been checked");
+            let summary = match expectation {
+                Expectation::MaxMissingRatio { .. }
+                | Expectation::CategoriesIn { .. } => {
+                    match dataset.field_summary(id)? {
+                        Some(summary) => summary,
+                        None => {
+                            report.push(expectation, ViolationKind::NoSummary);
+                            continue;
+                        }
+                    }
+                }
+                _ => continue,
+            };
+            match expectation {
+                Expectation::MaxMissingRatio { max, .. } => {
+                    let ratio = if dataset.rows > 0 {
+                        summary.missing_count() as f64 / dataset.rows as f64
+                    } else {
+                        0.0
+                    };
+                    if ratio > *max {
+                        report.push(
+                            expectation,
+                            ViolationKind::TooManyMissing { ratio, max: *max },
+                        );
+                    }
+                }
+                Expectation::CategoriesIn { allowed, .. } => {
+                    let categories = match &summary {
+                        FieldSummary::Categorical(summary) => &summary.categories,
+                        _ => continue,
+                    };
+                    let unexpected = categories
+                        .iter()
+                        .map(|(category, _)| category)
+                        .filter(|&category| !allowed.contains(category))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if !unexpected.is_empty() {
+                        report.push(
+                            expectation,
+                            ViolationKind::UnexpectedCategories {
+                                categories: unexpected,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Look up `field` in `fields`, first by ID and then by name.
+fn find_field<'a>(
+    fields: &'a HashMap<String, Field>,
+    field: &str,
+) -> Option<(&'a str, &'a Field)> {
+    if let Some((id, found)) = fields.get_key_value(field) {
+        return Some((id, found));
+    }
+    fields
+        .iter()
+        .find(|(_, found)| found.name == field)
+        .map(|(id, found)| (id.as_str(), found))
+}
+
+/// Check the parts of `expectation` which only need field metadata, and
+/// return a violation if we find one.
+fn check_field(
+    expectation: &Expectation,
+    fields: &HashMap<String, Field>,
+) -> Option<ViolationKind> {
+    let (_, field) = match find_field(fields, expectation.field()) {
+        Some(found) => found,
+        None => return Some(ViolationKind::MissingField),
+    };
+    match expectation {
+        Expectation::Optype { optype, .. } if field.optype != *optype => {
+            Some(ViolationKind::WrongOptype {
+                expected: *optype,
+                actual: field.optype,
+            })
+        }
+        Expectation::CategoriesIn { .. } if field.optype != Optype::Categorical => {
+            Some(ViolationKind::WrongOptype {
+                expected: Optype::Categorical,
+                actual: field.optype,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The result of checking a set of `Expectations`.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ValidationReport {
+    /// The expectations which weren't met, in the order they were declared.
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Were all our expectations met?
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Return an error describing our violations, if we have any.
+    pub fn ensure_valid(&self) -> Result<()> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(format_err!("validation failed: {}", self))
+        }
+    }
+
+    /// Record that `expectation` was violated.
+    fn push(&mut self, expectation: &Expectation, kind: ViolationKind) {
+        self.violations.push(Violation {
+            expectation: expectation.clone(),
+            kind,
+        });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "all expectations met");
+        }
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// An expectation which wasn't met.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Violation {
+    /// The expectation we checked.
+    pub expectation: Expectation,
+    /// What was wrong.
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "field {:?} ", self.expectation.field())?;
+        match &self.kind {
+            ViolationKind::MissingField => write!(f, "does not exist"),
+            ViolationKind::WrongOptype { expected, actual } => {
+                write!(f, "has optype {:?}, expected {:?}", actual, expected)
+            }
+            ViolationKind::TooManyMissing { ratio, max } => write!(
+                f,
+                "is missing in {:.1}% of rows, expected at most {:.1}%",
+                ratio * 100.0,
+                max * 100.0,
+            ),
+            ViolationKind::UnexpectedCategories { categories } => {
+                write!(f, "has unexpected categories {:?}", categories)
+            }
+            ViolationKind::NoSummary => {
+                write!(f, "has no summary statistics to check")
+            }
+        }
+    }
+}
+
+/// What was wrong with a field.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// The field does not exist.
+    MissingField,
+    /// The field has the wrong optype.
+    WrongOptype {
+        /// The optype we expected.
+        expected: Optype,
+        /// The field's actual optype.
+        actual: Optype,
+    },
+    /// Too many of the field's values are missing.
+    TooManyMissing {
+        /// The fraction of rows where the field is missing.
+        ratio: f64,
+        /// The largest fraction we expected.
+        max: f64,
+    },
+    /// The field contains categories which weren't allowed.
+    UnexpectedCategories {
+        /// The unexpected categories.
+        categories: Vec<String>,
+    },
+    /// We couldn't check this expectation, because BigML hasn't computed
+    /// summary statistics for the field.
+    NoSummary,
+}
+
+#[test]
+fn check_dataset_expectations() {
+    let dataset: Dataset = serde_json::from_value(serde_json::json!({
+        "category": 0,
+        "code": 200,
+        "columns": 2,
+        "description": "",
+        "excluded_fields": [],
+        "field_types": {"categorical": 1, "numeric": 1, "preferred": 2},
+        "fields": {
+            "000000": {
+                "name": "price",
+                "optype": "numeric",
+                "summary": { "missing_count": 1, "population": 9 },
+            },
+            "000001": {
+                "name": "color",
+                "optype": "categorical",
+                "summary": {
+                    "categories": [["red", 6], ["purple", 4]],
+                    "missing_count": 0,
+                },
+            },
+        },
+        "input_fields": ["000000", "000001"],
+        "name": "example",
+        "resource": "dataset/5e5d87f6e476845bd8000000",
+        "rows": 10,
+        "shared": false,
+        "status": {"code": 5, "message": "The dataset has been created"},
+        "subscription": false,
+        "tags": [],
+    }))
+    .unwrap();
+
+    let valid = Expectations::new()
+        .exists("000000")
+        .optype("price", Optype::Numeric)
+        .max_missing_ratio("price", 0.2)
+        .categories_in("color", &["red", "purple"])
+        .check_dataset(&dataset)
+        .unwrap();
+    assert!(valid.is_valid());
+    assert!(valid.ensure_valid().is_ok());
+
+    let invalid = Expectations::new()
+        .exists("size")
+        .optype("color", Optype::Text)
+        .max_missing_ratio("price", 0.05)
+        .categories_in("color", &["red", "green"])
+        .check_dataset(&dataset)
+        .unwrap();
+    let kinds = invalid
+        .violations
+        .iter()
+        .map(|violation| violation.kind.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ViolationKind::MissingField,
+            ViolationKind::WrongOptype {
+                expected: Optype::Text,
+                actual: Optype::Categorical,
+            },
+            ViolationKind::TooManyMissing {
+                ratio: 0.1,
+                max: 0.05,
+            },
+            ViolationKind::UnexpectedCategories {
+                categories: vec!["purple".to_owned()],
+            },
+        ],
+    );
+    assert_eq!(
+        invalid.violations[2].to_string(),
+        "field \"price\" is missing in 10.0% of rows, expected at most 5.0%",
+    );
+    assert!(invalid.ensure_valid().is_err());
+}
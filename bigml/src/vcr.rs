@@ -0,0 +1,366 @@
+//! Recording HTTP interactions with BigML to a "cassette" file, and
+//! replaying them later without talking to BigML.
+//!
+//! This is intended for test suites which want to run quickly and
+//! deterministically. To keep credentials out of cassettes, we strip the
+//! `username` and `api_key` query parameters from every URL we save, and we
+//! match replayed requests without them. We also redact passwords and tokens
+//! in JSON request bodies, including any credentials embedded in URLs such as
+//! a remote source's `"remote"`.
+
+use reqwest::ResponseBuilderExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use url::Url;
+
+use crate::errors::*;
+
+/// JSON keys whose values we redact from request bodies.
+const CREDENTIAL_KEYS: &[&str] = &["api_key", "password", "token"];
+
+/// URL query parameters whose values we redact from request bodies. These
+/// are used by `RemoteSource::to_url`.
+const CREDENTIAL_PARAMS: &[&str] =
+    &["AccountKey", "access-key", "api_key", "secret-key", "token"];
+
+/// What we replace redacted credentials with.
+const REDACTED: &str = "REDACTED";
+
+/// Should we record interactions, or replay them?
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum VcrMode {
+    /// Send requests to BigML, and record them along with their responses.
+    Record,
+    /// Answer requests using previously recorded responses.
+    Replay,
+}
+
+/// A recorded request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct RecordedRequest {
+    /// The HTTP method, such as `"GET"`.
+    method: String,
+    /// The path and query string, without any credentials.
+    url: String,
+    /// The request body, if it wasn't streamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+impl RecordedRequest {
+    /// Summarize `request`, removing any credentials.
+    pub(crate) fn new(request: &reqwest::Request) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method().as_str().to_owned(),
+            url: url_without_credentials(request.url()),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(body_without_credentials),
+        }
+    }
+}
+
+/// A recorded response.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RecordedResponse {
+    /// The HTTP status code.
+    status: u16,
+    /// Any headers with textual values.
+    headers: Vec<(String, String)>,
+    /// The response body.
+    body: String,
+}
+
+/// A single request and its response.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+/// Records or replays HTTP interactions.
+#[derive(Debug)]
+pub(crate) struct Vcr {
+    /// Are we recording or replaying?
+    mode: VcrMode,
+    /// The path to our cassette file.
+    path: PathBuf,
+    /// Our interactions. When replaying, the flag is set once an interaction
+    /// has been used, so that repeated requests (for example, when polling a
+    /// resource) get their recorded responses in order.
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl Vcr {
+    /// Create a new `Vcr`. If we're replaying, this loads the cassette at
+    /// `path`.
+    pub(crate) fn new(mode: VcrMode, path: PathBuf) -> Result<Vcr> {
+        let interactions = match mode {
+            VcrMode::Record => vec![],
+            VcrMode::Replay => {
+                let data = fs::read(&path)
+                    .map_err(|err| Error::could_not_read_file(&path, err))?;
+                let interactions: Vec<Interaction> = serde_json::from_slice(&data)
+                    .map_err(|err| Error::could_not_read_file(&path, err))?;
+                interactions.into_iter().map(|i| (i, false)).collect()
+            }
+        };
+        Ok(Vcr {
+            mode,
+            path,
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    /// Are we replaying recorded interactions?
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.mode == VcrMode::Replay
+    }
+
+    /// Return the first unused recorded response to `request`.
+    pub(crate) fn replay(
+        &self,
+        request: &reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        let recorded = RecordedRequest::new(request);
+        let mut interactions = self.interactions.lock().expect("lock poisoned");
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(interaction, used)| !used && interaction.request == recorded)
+            .ok_or_else(|| {
+                format_err!(
+                    "no recorded response to {} {} in {}",
+                    recorded.method,
+                    recorded.url,
+                    self.path.display(),
+                )
+            })?;
+        *used = true;
+        to_response(request.url(), &interaction.response)
+    }
+
+    /// Record `request` and its response `res`, returning a copy of `res`.
+    /// This reads the entire body of `res` into memory.
+    pub(crate) async fn record(
+        &self,
+        request: RecordedRequest,
+        res: reqwest::Response,
+    ) -> Result<reqwest::Response> {
+        let url = res.url().to_owned();
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.as_str().to_owned(), value.to_owned()))
+            })
+            .collect();
+        let body = res.bytes().await?;
+        let response = RecordedResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+        let interaction = Interaction {
+            request,
+            response: response.clone(),
+        };
+
+        // Rewrite the whole cassette each time, so that it's always complete
+        // even if the test using it fails.
+        let mut interactions = self.interactions.lock().expect("lock poisoned");
+        interactions.push((interaction, true));
+        save(&self.path, &interactions)?;
+        drop(interactions);
+
+        to_response(&url, &response)
+    }
+}
+
+/// Write `interactions` to `path`.
+fn save(path: &Path, interactions: &[(Interaction, bool)]) -> Result<()> {
+    let interactions = interactions.iter().map(|(i, _)| i).collect::<Vec<_>>();
+    let data = serde_json::to_vec_pretty(&interactions)?;
+    fs::write(path, data).map_err(|err| Error::could_not_write_file(path, err))
+}
+
+/// Build a `reqwest::Response` from a recorded response.
+fn to_response(url: &Url, response: &RecordedResponse) -> Result<reqwest::Response> {
+    let mut builder = http::Response::builder()
+        .status(response.status)
+        .url(url.to_owned());
+    for (name, value) in &response.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let res = builder.body(response.body.clone()).map_err(Error::other)?;
+    Ok(res.into())
+}
+
+/// Return the path and query string of `url`, minus any credentials.
+fn url_without_credentials(url: &Url) -> String {
+    let query = url
+        .query_pairs()
+        .filter(|(k, _)| k != "username" && k != "api_key")
+        .collect::<Vec<_>>();
+    let mut result = url.path().to_owned();
+    if !query.is_empty() {
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+        serializer.extend_pairs(query);
+        result.push('?');
+        result.push_str(&serializer.finish());
+    }
+    result
+}
+
+/// Return `body` as a string, redacting any credentials if it's JSON.
+fn body_without_credentials(body: &[u8]) -> String {
+    if let Ok(mut json) = serde_json::from_slice::<Value>(body) {
+        if redact_credentials(&mut json) {
+            return json.to_string();
+        }
+    }
+    String::from_utf8_lossy(body).into_owned()
+}
+
+/// Redact any credentials in `json`, returning true if we changed anything.
+fn redact_credentials(json: &mut Value) -> bool {
+    match json {
+        Value::Object(map) => {
+            let mut changed = false;
+            for (key, value) in map.iter_mut() {
+                if CREDENTIAL_KEYS.contains(&key.as_str()) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_owned());
+                    changed = true;
+                } else {
+                    changed |= redact_credentials(value);
+                }
+            }
+            changed
+        }
+        Value::Array(values) => {
+            let mut changed = false;
+            for value in values {
+                changed |= redact_credentials(value);
+            }
+            changed
+        }
+        Value::String(s) => match redact_url_credentials(s) {
+            Some(redacted) => {
+                *s = redacted;
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// If `s` is a URL with credentials in its query string, return it with the
+/// credentials redacted.
+fn redact_url_credentials(s: &str) -> Option<String> {
+    let mut url = Url::parse(s).ok()?;
+    let is_credential = |k: &str| CREDENTIAL_PARAMS.contains(&k);
+    if !url.query_pairs().any(|(k, _)| is_credential(&k)) {
+        return None;
+    }
+    let query = url
+        .query_pairs()
+        .map(|(k, v)| {
+            let v = if is_credential(&k) {
+                REDACTED.into()
+            } else {
+                v
+            };
+            (k.into_owned(), v.into_owned())
+        })
+        .collect::<Vec<_>>();
+    url.query_pairs_mut().clear().extend_pairs(query);
+    Some(url.to_string())
+}
+
+#[test]
+fn recorded_urls_have_no_credentials() {
+    let url = Url::parse(
+        "https://bigml.io/source/1?username=example&api_key=secret&project=project%2F2",
+    )
+    .unwrap();
+    assert_eq!(
+        url_without_credentials(&url),
+        "/source/1?project=project%2F2"
+    );
+}
+
+#[test]
+fn recorded_bodies_have_no_credentials() {
+    use crate::resource::source::{RemoteSource, S3Credentials};
+
+    let remote = RemoteSource::S3 {
+        bucket: "bucket".to_owned(),
+        key: "data.csv".to_owned(),
+        credentials: Some(S3Credentials::new("AKIAEXAMPLE", "s3cr3t")),
+    };
+    let body = serde_json::json!({
+        "remote": remote.to_url(),
+        "connection": { "host": "db.example.com", "password": "hunter2" },
+        "name": "example",
+    });
+    let recorded = body_without_credentials(body.to_string().as_bytes());
+    assert!(!recorded.contains("AKIAEXAMPLE"));
+    assert!(!recorded.contains("s3cr3t"));
+    assert!(!recorded.contains("hunter2"));
+    assert!(recorded.contains("db.example.com"));
+    assert!(recorded.contains("data.csv"));
+
+    // Bodies without credentials are saved exactly as they were sent.
+    let body = br#"{"name": "example", "remote": "https://example.com/a.csv"}"#;
+    assert_eq!(
+        body_without_credentials(body),
+        String::from_utf8_lossy(body)
+    );
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn recorded_interactions_can_be_replayed() {
+    use crate::{
+        mock::{fixtures, MockServer},
+        resource::{Id, Source},
+        Client, ClientBuilder,
+    };
+
+    let path = std::env::temp_dir()
+        .join(format!("bigml-vcr-test-{}.json", std::process::id(),));
+    let id: Id<Source> = "source/5e5d87f6e476845bd8000000".parse().unwrap();
+
+    // Record a request to our mock server.
+    let server = MockServer::start().await;
+    server.mount_fetch(&id, fixtures::source(&id)).await;
+    let base_url: Url = server.inner().uri().parse().unwrap();
+    let client = ClientBuilder::new("example", "secret")
+        .base_url(base_url)
+        .record_to(&path)
+        .build()
+        .unwrap();
+    client.fetch(&id).await.unwrap();
+    let cassette = fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("secret"));
+
+    // Replay it without a server, using different credentials.
+    drop(server);
+    let client = Client::builder("someone_else", "other_secret")
+        .replay_from(&path)
+        .build()
+        .unwrap();
+    let source = client.fetch(&id).await.unwrap();
+    assert_eq!(source.resource.as_str(), id.as_str());
+    assert!(client.fetch(&id).await.is_err());
+
+    fs::remove_file(&path).unwrap();
+}
@@ -1,14 +1,19 @@
 //! Utilities for waiting, timeouts and error retries.
 
+use futures::FutureExt;
+use rand::Rng;
 use std::{
-    cmp::max,
+    cmp::{max, min},
     fmt::Display,
     future::Future,
-    time::{Duration, SystemTime},
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 use tokio::time::sleep;
 
 use crate::errors::*;
+use crate::resource::StatusCode;
 
 /// Minimum sleep time recommended by BigML support to avoid ban.
 const MIN_SLEEP_SECS: u64 = 4;
@@ -23,6 +28,45 @@ pub enum BackoffType {
     Exponential,
 }
 
+impl FromStr for BackoffType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "linear" => Ok(BackoffType::Linear),
+            "exponential" => Ok(BackoffType::Exponential),
+            _ => Err(format_err!(
+                "unknown backoff type {:?}, expected \"linear\" or \"exponential\"",
+                s
+            )),
+        }
+    }
+}
+
+/// A percentage between 0 and 100, inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Percentage(f64);
+
+impl Percentage {
+    /// Create a new `Percentage`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `percent` is not between 0 and 100.
+    pub fn new(percent: f64) -> Self {
+        assert!(
+            (0.0..=100.0).contains(&percent),
+            "percentage must be between 0 and 100"
+        );
+        Percentage(percent)
+    }
+
+    /// Return this percentage as a fraction between 0.0 and 1.0.
+    pub fn as_fraction(self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
 /// Options controlling how long we wait and what makes us give up.
 /// This uses a "builder" pattern, so you can write:
 ///
@@ -34,10 +78,18 @@ pub enum BackoffType {
 ///     .timeout(Duration::from_secs(120))
 ///     .allowed_errors(5);
 /// ```
+///
+/// If you need several nested waits to share a single time budget, use
+/// [`WaitOptions::from_deadline`] or [`WaitOptions::start_clock`], and pass
+/// the same options to each.
+#[derive(Clone, Debug)]
 pub struct WaitOptions {
-    /// Time between each retry.
+    /// How long to wait before giving up, starting from when we begin waiting.
     timeout: Option<Duration>,
 
+    /// An absolute time at which to give up.
+    deadline: Option<Instant>,
+
     /// How long to wait between retries.
     retry_interval: Duration,
 
@@ -46,15 +98,65 @@ pub struct WaitOptions {
 
     /// How many errors are we allowed before giving up?
     allowed_errors: u16,
+
+    /// How much should we randomly vary each interval?
+    jitter: Percentage,
+
+    /// The longest we should ever wait between retries.
+    max_interval: Option<Duration>,
 }
 
 impl WaitOptions {
-    /// Set an optional timeout after which to abandon this `wait`.
+    /// Create `WaitOptions` which give up at `deadline`, no matter how many
+    /// times they're used.
+    pub fn from_deadline(deadline: Instant) -> Self {
+        Self::default().deadline(deadline)
+    }
+
+    /// Set an optional timeout after which to abandon this `wait`. This is
+    /// measured from the time each `wait` starts.
     pub fn timeout<D: Into<Option<Duration>>>(mut self, timeout: D) -> Self {
         self.timeout = timeout.into();
         self
     }
 
+    /// Set an optional absolute deadline, after which we'll abandon any `wait`
+    /// using these options. If we also have a `timeout`, we'll give up at
+    /// whichever comes first.
+    pub fn deadline<I: Into<Option<Instant>>>(mut self, deadline: I) -> Self {
+        self.deadline = deadline.into();
+        self
+    }
+
+    /// Convert any `timeout` into an absolute deadline, starting now. This
+    /// allows several nested waits to share a single time budget.
+    pub fn start_clock(mut self) -> Self {
+        self.deadline = self.effective_deadline(Instant::now());
+        self.timeout = None;
+        self
+    }
+
+    /// Give up no later than `parent` would, if it were started now. Use this
+    /// to nest a wait with its own options inside another wait.
+    pub fn inherit_deadline(mut self, parent: &WaitOptions) -> Self {
+        let now = Instant::now();
+        self.deadline =
+            match (self.effective_deadline(now), parent.effective_deadline(now)) {
+                (Some(ours), Some(theirs)) => Some(min(ours, theirs)),
+                (ours, theirs) => ours.or(theirs),
+            };
+        self.timeout = None;
+        self
+    }
+
+    /// How much time remains before we would give up, if we started waiting
+    /// now? Returns `None` if we have no timeout or deadline.
+    pub fn remaining(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.effective_deadline(now)
+            .map(|deadline| deadline.saturating_duration_since(now))
+    }
+
     /// How long should we wait between retries? Defaults to 10 seconds. Note
     /// that BigML has suggested not polling more often than every 4 seconds,
     /// (to avoid losing API access) so if you set a lower value, this will be
@@ -76,19 +178,124 @@ impl WaitOptions {
         self.allowed_errors = count;
         self
     }
+
+    /// Randomly vary each interval by up to this percentage, in either
+    /// direction. This prevents many tasks which started at the same time from
+    /// polling BigML in lockstep. Defaults to 0.
+    pub fn jitter(mut self, jitter: Percentage) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Never wait longer than `interval` between retries, no matter how much
+    /// we've backed off or how much jitter we've added.
+    pub fn max_interval<D: Into<Option<Duration>>>(mut self, interval: D) -> Self {
+        self.max_interval = interval.into();
+        self
+    }
+
+    /// When would a `wait` started at `now` give up?
+    fn effective_deadline(&self, now: Instant) -> Option<Instant> {
+        let timeout_deadline = self.timeout.map(|timeout| now + timeout);
+        match (self.deadline, timeout_deadline) {
+            (Some(deadline), Some(timeout_deadline)) => {
+                Some(min(deadline, timeout_deadline))
+            }
+            (deadline, timeout_deadline) => deadline.or(timeout_deadline),
+        }
+    }
+
+    /// Apply our `max_interval` to `interval`.
+    fn cap_interval(&self, interval: Duration) -> Duration {
+        match self.max_interval {
+            Some(max_interval) => min(interval, max_interval),
+            None => interval,
+        }
+    }
+
+    /// Randomly vary `interval` according to our `jitter`.
+    fn jittered(&self, interval: Duration) -> Duration {
+        let jitter = self.jitter.as_fraction();
+        if jitter == 0.0 {
+            return interval;
+        }
+        let factor = 1.0 + rand::thread_rng().gen_range(-jitter..=jitter);
+        interval.mul_f64(factor)
+    }
+
+    /// How long should we sleep before retrying, given our current
+    /// `retry_interval`? We apply jitter first, so that it can't push us past
+    /// our `max_interval`.
+    fn sleep_interval(&self, retry_interval: Duration) -> Duration {
+        max(
+            Duration::from_secs(MIN_SLEEP_SECS),
+            self.cap_interval(self.jittered(retry_interval)),
+        )
+    }
 }
 
 impl Default for WaitOptions {
     fn default() -> Self {
         Self {
             timeout: None,
+            deadline: None,
             retry_interval: Duration::from_secs(10),
             backoff_type: BackoffType::Linear,
             allowed_errors: 2,
+            jitter: Percentage(0.0),
+            max_interval: None,
         }
     }
 }
 
+/// Something which happened while waiting for a resource using
+/// `Client::wait_opt`. These are passed to the callback in `ProgressOptions`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum WaitEvent<R> {
+    /// We fetched the latest version of the resource.
+    Polled(R),
+
+    /// The status code of the resource changed. `old` will be `None` the first
+    /// time we see the resource.
+    StatusChanged {
+        /// The previous status code.
+        old: Option<StatusCode>,
+        /// The new status code.
+        new: StatusCode,
+    },
+
+    /// We encountered a temporary error, and we're going to try again.
+    Retrying {
+        /// A description of the error.
+        error: String,
+        /// The number of errors we've seen so far, including this one.
+        errors_seen: u16,
+        /// The number of errors we're allowed before giving up.
+        allowed_errors: u16,
+    },
+
+    /// We gave up, because our timeout would expire before we could try again.
+    TimedOut,
+}
+
+/// Something which happened inside `wait` that our caller might want to know
+/// about.
+pub(crate) enum WaitLoopEvent<'a, E> {
+    /// We're going to retry after `error`.
+    Retrying {
+        /// The error which occurred.
+        error: &'a E,
+        /// The number of errors we've seen so far, including this one.
+        errors_seen: u16,
+        /// The number of errors we're allowed before giving up.
+        allowed_errors: u16,
+    },
+
+    /// We're giving up, because our timeout would expire.
+    TimedOut,
+}
+
 /// Return this value from a `wait` callback.
 pub enum WaitStatus<T, E> {
     /// The task has finished.
@@ -106,9 +313,9 @@ pub enum WaitStatus<T, E> {
 
 impl<T> From<Error> for WaitStatus<T, Error> {
     /// Convert an [`Error`] to either [`WaitStatus::FailedTemporarily`] or
-    /// [`WaitStatus::FailedPermanently`] depending on [`Error::might_be_temporary`].
+    /// [`WaitStatus::FailedPermanently`] depending on [`Error::is_transient`].
     fn from(error: Error) -> Self {
-        if error.might_be_temporary() {
+        if error.is_transient() {
             WaitStatus::FailedTemporarily(error)
         } else {
             WaitStatus::FailedPermanently(error)
@@ -156,11 +363,10 @@ macro_rules! try_with_permanent_failure {
 /// ```
 /// # use futures::{FutureExt, TryFutureExt};
 /// # #[tokio::main]
-/// # async fn main() -> Result<(), failure::Error> {
+/// # async fn main() -> Result<(), bigml::Error> {
 /// use bigml::wait::{wait, WaitOptions, WaitStatus};
-/// use failure::Error;
 ///
-/// let value = wait::<_, failure::Error, _, _>(&WaitOptions::default(), || {
+/// let value = wait::<_, bigml::Error, _, _>(&WaitOptions::default(), || {
 ///     async { WaitStatus::Finished("my value") }
 /// }).await?;
 ///
@@ -172,15 +378,72 @@ macro_rules! try_with_permanent_failure {
 /// If you return `Ok(WaitStatus::Waiting)` instead, this function will wait
 /// some number of seconds, and then try again.
 #[allow(clippy::needless_lifetimes)]
-pub async fn wait<T, E, F, R>(options: &WaitOptions, mut f: F) -> Result<T, E>
+pub async fn wait<T, E, F, R>(options: &WaitOptions, f: F) -> Result<T, E>
 where
     F: FnMut() -> R,
     R: Future<Output = WaitStatus<T, E>>,
     E: Display,
     Error: Into<E>,
 {
-    let deadline = options.timeout.map(|to| SystemTime::now() + to);
-    let mut retry_interval = options.retry_interval;
+    wait_observed(options, f, |_| Ok(())).await
+}
+
+/// Call `f` repeatedly until it succeeds, fails with an error which isn't
+/// [`Error::is_transient`], or we time out. Honors `WaitOptions`.
+///
+/// This is a simpler alternative to `wait` for the common case where `f`
+/// either succeeds or fails, and never needs to report that it's still
+/// waiting.
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), bigml::Error> {
+/// use bigml::wait::{wait_for_result, WaitOptions};
+///
+/// let value = wait_for_result(&WaitOptions::default(), || async {
+///     Ok("my value")
+/// })
+/// .await?;
+///
+/// assert_eq!(value, "my value");
+/// #   Ok(())
+/// # }
+/// ```
+pub async fn wait_for_result<T, F, R>(options: &WaitOptions, mut f: F) -> Result<T>
+where
+    F: FnMut() -> R,
+    R: Future<Output = Result<T>>,
+{
+    wait(options, || f().map(WaitStatus::from)).await
+}
+
+impl<T> From<Result<T>> for WaitStatus<T, Error> {
+    /// Convert `Ok` to [`WaitStatus::Finished`], and errors as described
+    /// by `From<Error>`.
+    fn from(result: Result<T>) -> Self {
+        match result {
+            Ok(value) => WaitStatus::Finished(value),
+            Err(error) => WaitStatus::from(error),
+        }
+    }
+}
+
+/// Like `wait`, but call `observer` when we retry or time out. If `observer`
+/// fails, stop waiting and return its error.
+pub(crate) async fn wait_observed<T, E, F, R, O>(
+    options: &WaitOptions,
+    mut f: F,
+    mut observer: O,
+) -> Result<T, E>
+where
+    F: FnMut() -> R,
+    R: Future<Output = WaitStatus<T, E>>,
+    O: FnMut(WaitLoopEvent<'_, E>) -> Result<()>,
+    E: Display,
+    Error: Into<E>,
+{
+    let deadline = options.effective_deadline(Instant::now());
+    let mut retry_interval = options.cap_interval(options.retry_interval);
     trace!(
         "waiting with deadline {:?}, initial interval {:?}",
         deadline,
@@ -203,6 +466,12 @@ where
                     "got error, will retry ({}/{}): {}",
                     errors_seen, options.allowed_errors, e,
                 );
+                observer(WaitLoopEvent::Retrying {
+                    error: e,
+                    errors_seen,
+                    allowed_errors: options.allowed_errors,
+                })
+                .map_err(|err| err.into())?;
             }
             WaitStatus::FailedTemporarily(err) => {
                 trace!("too many temporary failures, giving up on wait: {}", err);
@@ -214,30 +483,341 @@ where
             }
         }
 
+        // Decide how long to sleep before our next call.
+        let duration = options.sleep_interval(retry_interval);
+
         // Check to see if we'll exceed our deadline (if we have one).
         if let Some(deadline) = deadline {
-            let next_attempt = SystemTime::now() + retry_interval;
+            let next_attempt = Instant::now() + duration;
             if next_attempt > deadline {
                 trace!(
                     "next attempt {:?} would fall after deadline {:?}, ending wait",
                     next_attempt,
                     deadline
                 );
+                observer(WaitLoopEvent::TimedOut).map_err(|err| err.into())?;
                 return Err(Error::Timeout.into());
             }
         }
 
         // Sleep until our next call.
-        let duration = max(Duration::from_secs(MIN_SLEEP_SECS), retry_interval);
         sleep(duration).await;
 
         // Update retry interval.
         match options.backoff_type {
             BackoffType::Linear => {}
             BackoffType::Exponential => {
-                retry_interval *= 2;
+                retry_interval = options.cap_interval(retry_interval * 2);
                 trace!("next retry doubled to {:?}", retry_interval);
             }
         }
     }
 }
+
+/// How a `Client` should retry individual HTTP requests which fail because of
+/// network problems or server errors, before the error is returned to the
+/// caller. This is separate from `WaitOptions`, which controls how we poll
+/// resources until BigML finishes building them.
+///
+/// By default, we make up to 3 attempts, waiting 1 second after the first
+/// failure and doubling that delay after each additional failure. We only
+/// retry idempotent requests like `GET` and `DELETE`, because retrying a
+/// `POST` could create a resource twice. Requests with streaming bodies,
+/// such as source uploads, are never retried.
+///
+/// ```
+/// use bigml::{ClientBuilder, RetryPolicy};
+///
+/// let client = ClientBuilder::new("username", "api_key")
+///     .retry_policy(RetryPolicy::default().max_attempts(5))
+///     .build()?;
+/// # Ok::<(), bigml::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// The maximum number of times to try a request, including the first.
+    max_attempts: u16,
+
+    /// How long to wait after the first failure. This doubles after each
+    /// additional failure.
+    retry_interval: Duration,
+
+    /// Should we retry requests like `POST` which aren't idempotent?
+    retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Set the maximum number of times we'll try a request, including the
+    /// first attempt. Setting this to 1 disables retries.
+    pub fn max_attempts(mut self, max_attempts: u16) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set how long to wait after the first failure. This doubles after each
+    /// additional failure.
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Should we also retry requests which aren't idempotent, such as the
+    /// `POST` requests used to create resources? This may create duplicate
+    /// resources if BigML received a request but we didn't get the response.
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    /// Should we retry a `method` request which has been tried `attempts`
+    /// times, and which has failed with `error`?
+    pub(crate) fn retries_error(
+        &self,
+        method: &reqwest::Method,
+        attempts: u16,
+        error: &reqwest::Error,
+    ) -> bool {
+        (error.is_connect() || error.is_timeout() || error.is_request())
+            && self.may_retry(method, attempts)
+    }
+
+    /// Should we retry a `method` request which has been tried `attempts`
+    /// times, and which has returned `status`?
+    pub(crate) fn retries_status(
+        &self,
+        method: &reqwest::Method,
+        attempts: u16,
+        status: reqwest::StatusCode,
+    ) -> bool {
+        status.is_server_error() && self.may_retry(method, attempts)
+    }
+
+    /// How long should we wait before the next attempt, after `attempts`
+    /// failed attempts?
+    pub(crate) fn delay(&self, attempts: u16) -> Duration {
+        let doublings = u32::from(attempts.saturating_sub(1)).min(16);
+        self.retry_interval * 2u32.pow(doublings)
+    }
+
+    /// Are we allowed to retry a `method` request which has been tried
+    /// `attempts` times?
+    fn may_retry(&self, method: &reqwest::Method, attempts: u16) -> bool {
+        attempts < self.max_attempts
+            && (self.retry_non_idempotent || is_idempotent(method))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            retry_interval: Duration::from_secs(1),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+/// Is it safe to send `method` requests more than once?
+fn is_idempotent(method: &reqwest::Method) -> bool {
+    use reqwest::Method;
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::PUT,
+        Method::DELETE,
+        Method::OPTIONS,
+    ]
+    .contains(method)
+}
+
+/// Limits how often we make requests, by spacing them out evenly.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// The minimum time between requests.
+    interval: Duration,
+
+    /// The earliest time at which we may make our next request.
+    next_request: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a new `RateLimiter` allowing `requests_per_second`.
+    pub(crate) fn new(requests_per_second: f64) -> Self {
+        assert!(
+            requests_per_second > 0.0,
+            "rate limit must be a positive number of requests per second"
+        );
+        Self {
+            interval: Duration::from_secs_f64(1.0 / requests_per_second),
+            next_request: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until we're allowed to make another request.
+    pub(crate) async fn wait(&self) {
+        let delay = self.reserve(Instant::now());
+        if delay > Duration::from_secs(0) {
+            sleep(delay).await;
+        }
+    }
+
+    /// Reserve the next available request slot, and return how long we need
+    /// to wait for it.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut next_request = self.next_request.lock().expect("lock poisoned");
+        let slot = max(*next_request, now);
+        *next_request = slot + self.interval;
+        slot - now
+    }
+}
+
+#[test]
+fn parse_backoff_type() {
+    assert_eq!(
+        "linear".parse::<BackoffType>().unwrap(),
+        BackoffType::Linear
+    );
+    assert_eq!(
+        "exponential".parse::<BackoffType>().unwrap(),
+        BackoffType::Exponential
+    );
+    assert!("random".parse::<BackoffType>().is_err());
+}
+
+#[test]
+fn rate_limiter_spaces_out_requests() {
+    let limiter = RateLimiter::new(4.0);
+    let now = Instant::now() + Duration::from_secs(1);
+    assert_eq!(limiter.reserve(now), Duration::from_secs(0));
+    assert_eq!(limiter.reserve(now), Duration::from_millis(250));
+    assert_eq!(limiter.reserve(now), Duration::from_millis(500));
+    let later = now + Duration::from_secs(10);
+    assert_eq!(limiter.reserve(later), Duration::from_secs(0));
+}
+
+#[test]
+fn wait_options_cap_and_jitter_intervals() {
+    let options = WaitOptions::default()
+        .max_interval(Duration::from_secs(60))
+        .jitter(Percentage::new(10.0));
+    assert_eq!(
+        options.cap_interval(Duration::from_secs(600)),
+        Duration::from_secs(60)
+    );
+    assert_eq!(
+        options.cap_interval(Duration::from_secs(30)),
+        Duration::from_secs(30)
+    );
+    for _ in 0..100 {
+        let interval = options.jittered(Duration::from_secs(100));
+        assert!(interval >= Duration::from_secs(90));
+        assert!(interval <= Duration::from_secs(110));
+    }
+    assert_eq!(
+        WaitOptions::default().jittered(Duration::from_secs(100)),
+        Duration::from_secs(100)
+    );
+}
+
+#[test]
+fn jitter_never_exceeds_max_interval() {
+    let options = WaitOptions::default()
+        .max_interval(Duration::from_secs(60))
+        .jitter(Percentage::new(50.0));
+    let mut saw_cap = false;
+    for _ in 0..100 {
+        let interval = options.sleep_interval(Duration::from_secs(60));
+        assert!(interval <= Duration::from_secs(60));
+        assert!(interval >= Duration::from_secs(30));
+        saw_cap |= interval == Duration::from_secs(60);
+    }
+    assert!(saw_cap);
+}
+
+#[test]
+fn wait_options_share_deadlines() {
+    let now = Instant::now();
+    let parent = WaitOptions::from_deadline(now + Duration::from_secs(60));
+    assert!(parent.remaining().unwrap() <= Duration::from_secs(60));
+
+    let child = WaitOptions::default()
+        .timeout(Duration::from_secs(3600))
+        .inherit_deadline(&parent);
+    assert!(child.remaining().unwrap() <= Duration::from_secs(60));
+
+    let started = WaitOptions::default()
+        .timeout(Duration::from_secs(30))
+        .start_clock();
+    assert!(started.deadline.unwrap() <= Instant::now() + Duration::from_secs(30));
+    assert!(WaitOptions::default().remaining().is_none());
+}
+
+#[tokio::test]
+async fn wait_observed_reports_timeouts() {
+    let options = WaitOptions::default()
+        .timeout(Duration::from_secs(1))
+        .retry_interval(Duration::from_secs(10));
+    let mut timed_out = false;
+    let result = wait_observed::<(), Error, _, _, _>(
+        &options,
+        || async { WaitStatus::Waiting },
+        |event| {
+            timed_out = matches!(event, WaitLoopEvent::TimedOut);
+            Ok(())
+        },
+    )
+    .await;
+    assert!(matches!(result, Err(Error::Timeout)));
+    assert!(timed_out);
+}
+
+#[tokio::test]
+async fn wait_for_result_retries_transient_errors() {
+    let options = WaitOptions::default();
+    let mut attempts = 0;
+    let result = wait_for_result(&options, || {
+        attempts += 1;
+        let attempt = attempts;
+        async move {
+            if attempt < 2 {
+                Err(Error::UnexpectedHttpStatus {
+                    url: "https://bigml.io/source".parse().unwrap(),
+                    status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                    body: String::new(),
+                })
+            } else {
+                Ok(attempt)
+            }
+        }
+    })
+    .await;
+    assert_eq!(result.unwrap(), 2);
+
+    let mut attempts = 0;
+    let result = wait_for_result::<(), _, _>(&options, || {
+        attempts += 1;
+        async { Err(format_err!("permanent failure")) }
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+}
+
+#[test]
+fn retry_policy_only_retries_idempotent_requests_by_default() {
+    use reqwest::{Method, StatusCode};
+
+    let policy = RetryPolicy::default();
+    let unavailable = StatusCode::SERVICE_UNAVAILABLE;
+    assert!(policy.retries_status(&Method::GET, 1, unavailable));
+    assert!(policy.retries_status(&Method::DELETE, 2, unavailable));
+    assert!(!policy.retries_status(&Method::GET, 3, unavailable));
+    assert!(!policy.retries_status(&Method::GET, 1, StatusCode::NOT_FOUND));
+    assert!(!policy.retries_status(&Method::POST, 1, unavailable));
+
+    let policy = policy.retry_non_idempotent(true);
+    assert!(policy.retries_status(&Method::POST, 1, unavailable));
+
+    assert_eq!(policy.delay(1), Duration::from_secs(1));
+    assert_eq!(policy.delay(3), Duration::from_secs(4));
+}
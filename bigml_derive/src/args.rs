@@ -0,0 +1,203 @@
+//! Implementation of `#[derive(Args)]` and `#[bigml_args]`.
+
+// In this macro, we want `proc_macro2::TokenStream` to manipulate the AST using
+// high-level APIs.
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::{
+    parse_quote, Attribute, Data, DeriveInput, GenericArgument, Lit, Meta, MetaList,
+    MetaNameValue, NestedMeta, PathArguments, Type,
+};
+
+/// Do the actual code generation for an `Args` type.
+pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let resource = get_resource_type(&ast.attrs);
+    let builders = builder_methods(ast);
+    quote! {
+        impl crate::resource::Args for #name {
+            type Resource = #resource;
+        }
+
+        impl #name {
+            #( #builders )*
+        }
+    }
+}
+
+/// Add `#[serde(skip_serializing_if = "...")]` to each `Option` and `Vec`
+/// field of `ast` which doesn't already say how it should be serialized.
+pub(crate) fn add_serde_attributes(mut ast: DeriveInput) -> TokenStream {
+    let data_struct = match ast.data {
+        Data::Struct(ref mut data_struct) => data_struct,
+        _ => panic!("`#[bigml_args]` may only be used on structs"),
+    };
+    for field in data_struct.fields.iter_mut() {
+        let always_serialize = take_always_serialize(&mut field.attrs);
+        if always_serialize || has_serialization_attr(&field.attrs) {
+            continue;
+        }
+        let skip_if = match container_type(&field.ty) {
+            Some("Option") => "Option::is_none",
+            Some("Vec") => "Vec::is_empty",
+            _ => continue,
+        };
+        field
+            .attrs
+            .push(parse_quote!(#[serde(skip_serializing_if = #skip_if)]));
+    }
+    quote! { #ast }
+}
+
+/// Remove any `#[args(always_serialize)]` attribute from `attrs`, returning
+/// true if we found one.
+fn take_always_serialize(attrs: &mut Vec<Attribute>) -> bool {
+    let before = attrs.len();
+    attrs.retain(|attr| {
+        if !attr.path.is_ident("args") {
+            return true;
+        }
+        match attr.parse_meta() {
+            Ok(Meta::List(MetaList { ref nested, .. })) if nested.len() == 1 => {
+                match nested.first() {
+                    Some(NestedMeta::Meta(Meta::Path(path)))
+                        if path.is_ident("always_serialize") =>
+                    {
+                        false
+                    }
+                    _ => panic!("Invalid field `args`, try #[args(always_serialize)]"),
+                }
+            }
+            _ => panic!("Invalid field `args`, try #[args(always_serialize)]"),
+        }
+    });
+    attrs.len() != before
+}
+
+/// Does `attrs` include a `serde` attribute which already controls whether
+/// this field is serialized?
+fn has_serialization_attr(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("serde"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(MetaList { nested, .. })) => Some(nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| {
+            let path = match nested {
+                NestedMeta::Meta(Meta::Path(path)) => path,
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, .. })) => path,
+                _ => return false,
+            };
+            ["flatten", "skip", "skip_serializing", "skip_serializing_if"]
+                .iter()
+                .any(|name| path.is_ident(name))
+        })
+}
+
+/// Search for an `#[args(resource = "MyResource")]` attribute and return
+/// `MyResource` as a `Type`.
+fn get_resource_type(attrs: &[Attribute]) -> Type {
+    const USAGE: &str = "Invalid `args`, try #[args(resource = \"MyResource\")]";
+    for attr in attrs {
+        if !attr.path.is_ident("args") {
+            continue;
+        }
+        if let Ok(Meta::List(MetaList { ref nested, .. })) = attr.parse_meta() {
+            if let Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(lit),
+                ..
+            }))) = nested.first()
+            {
+                if nested.len() == 1 && path.is_ident("resource") {
+                    return lit.parse().expect(USAGE);
+                }
+            }
+        }
+        panic!("{}", USAGE);
+    }
+    panic!("Missing attribute `args`, try `#[args(resource = \"...\")]`");
+}
+
+/// Generate `with_name`, `with_project` and `with_tags` builder methods for
+/// whichever of those fields are present.
+fn builder_methods(ast: &DeriveInput) -> Vec<TokenStream> {
+    let mut methods = vec![];
+    let data_struct = match ast.data {
+        Data::Struct(ref data_struct) => data_struct,
+        _ => panic!("`#[derive(Args)]` may only be used on structs"),
+    };
+    for field in &data_struct.fields {
+        let name = match field.ident.as_ref() {
+            Some(name) if name == "name" || name == "project" || name == "tags" => {
+                name
+            }
+            _ => continue,
+        };
+        let method = Ident::new(&format!("with_{}", name), Span::call_site());
+        let comment = format!("Set `{}`.", name);
+        let method = match (container_type(&field.ty), inner_type(&field.ty)) {
+            (Some("Option"), Some(inner)) => quote! {
+                #[doc = #comment]
+                pub fn #method<T: Into<#inner>>(mut self, #name: T) -> Self {
+                    self.#name = Some(#name.into());
+                    self
+                }
+            },
+            (Some("Vec"), Some(inner)) => quote! {
+                #[doc = #comment]
+                pub fn #method<I>(mut self, #name: I) -> Self
+                where
+                    I: IntoIterator,
+                    I::Item: Into<#inner>,
+                {
+                    self.#name = #name.into_iter().map(Into::into).collect();
+                    self
+                }
+            },
+            _ => {
+                let ty = &field.ty;
+                quote! {
+                    #[doc = #comment]
+                    pub fn #method<T: Into<#ty>>(mut self, #name: T) -> Self {
+                        self.#name = #name.into();
+                        self
+                    }
+                }
+            }
+        };
+        methods.push(method);
+    }
+    methods
+}
+
+/// If `ty` looks like `Option<...>` or `Vec<...>`, return the name of the
+/// container.
+fn container_type(ty: &Type) -> Option<&'static str> {
+    if let Type::Path(type_path) = ty {
+        let last = type_path.path.segments.last()?;
+        if last.ident == "Option" {
+            return Some("Option");
+        } else if last.ident == "Vec" {
+            return Some("Vec");
+        }
+    }
+    None
+}
+
+/// If `ty` has a single type parameter, return it.
+fn inner_type(ty: &Type) -> Option<&Type> {
+    if let Type::Path(type_path) = ty {
+        let last = type_path.path.segments.last()?;
+        if let PathArguments::AngleBracketed(args) = &last.arguments {
+            if args.args.len() == 1 {
+                if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                    return Some(inner);
+                }
+            }
+        }
+    }
+    None
+}
@@ -12,11 +12,41 @@ extern crate syn;
 // world.
 use proc_macro::TokenStream;
 
+mod args;
 mod resource;
 mod updatable;
 
+/// Derive boilerplate code for a resource's `Args` type, including the
+/// `Args` trait itself and `with_name`, `with_project` and `with_tags`
+/// builder methods for any of those fields which are present.
+///
+/// Derive macros can't add attributes to the fields of the type they're
+/// applied to, so use `#[bigml_args]` to omit empty fields when serializing.
+#[proc_macro_derive(Args, attributes(args))]
+pub fn args_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse(input).unwrap();
+    let gen = args::derive(&input);
+    gen.into()
+}
+
+/// Add `#[serde(skip_serializing_if = "...")]` to every `Option` and `Vec`
+/// field of a resource's `Args` type, so that we don't send empty fields to
+/// BigML. This must appear before `#[derive(Serialize)]`.
+///
+/// Fields which already have a `skip_serializing_if` or `flatten` attribute
+/// are left alone, as are fields marked with `#[args(always_serialize)]`.
+#[proc_macro_attribute]
+pub fn bigml_args(attr: TokenStream, input: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        panic!("`#[bigml_args]` does not take any arguments");
+    }
+    let input = syn::parse(input).unwrap();
+    let gen = args::add_serde_attributes(input);
+    gen.into()
+}
+
 /// Derive boilerplate code for `Resource`.
-#[proc_macro_derive(Resource, attributes(api_name))]
+#[proc_macro_derive(Resource, attributes(api_name, api_unsupported, ready_when))]
 pub fn resource_derive(input: TokenStream) -> TokenStream {
     // Rust procedural macros are really limited right now:
     //
@@ -2,16 +2,44 @@
 
 // In this macro, we want `proc_macro2::TokenStream` to manipulate the AST using
 // high-level APIs.
-use proc_macro2::TokenStream;
-use syn::{Attribute, DeriveInput, Lit, Meta, MetaNameValue};
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::{Attribute, DeriveInput, Lit, Meta, MetaList, MetaNameValue, NestedMeta};
 
 /// Do the actual code generation for a `Resource`.
 pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     let api_name = get_api_name(&ast.attrs);
+    let unsupported = get_unsupported_operations(&ast.attrs)
+        .into_iter()
+        .map(|operation| {
+            let name = Ident::new(
+                &format!("SUPPORTS_{}", operation.to_uppercase()),
+                Span::call_site(),
+            );
+            quote! { const #name: bool = false; }
+        })
+        .collect::<Vec<_>>();
+    let is_ready_for = if ready_when_summarized(&ast.attrs) {
+        quote! {
+            fn is_ready_for(&self, readiness: Readiness) -> bool {
+                let code = self.status().code();
+                code.is_ready()
+                    || (readiness == Readiness::Summarized
+                        && code == StatusCode::Summarized)
+            }
+        }
+    } else {
+        quote! {}
+    };
     quote! {
         impl #impl_generics Resource for #name #ty_generics #where_clause {
+            #( #unsupported )*
+
+            fn api_name() -> &'static str {
+                #api_name
+            }
+
             fn id_prefix() -> &'static str {
                 concat!(#api_name, "/")
             }
@@ -31,6 +59,8 @@ pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
             fn status(&self) -> &Status {
                 &self.status
             }
+
+            #is_ready_for
         }
     }
 }
@@ -52,3 +82,59 @@ fn get_api_name(attrs: &[Attribute]) -> Lit {
     }
     panic!("Missing attribute `api_name`, try `#[api_name = \"...\"]`");
 }
+
+/// Search for an `#[api_unsupported(create, update, delete)]` attribute, and
+/// return the operations it lists.
+fn get_unsupported_operations(attrs: &[Attribute]) -> Vec<String> {
+    const USAGE: &str = "Invalid `api_unsupported`, try #[api_unsupported(update)]";
+    let mut operations = vec![];
+    for attr in attrs {
+        if !attr.path.is_ident("api_unsupported") {
+            continue;
+        }
+        match attr.parse_meta() {
+            Ok(Meta::List(MetaList { ref nested, .. })) => {
+                for operation in nested {
+                    match operation {
+                        NestedMeta::Meta(Meta::Path(path))
+                            if path.is_ident("create")
+                                || path.is_ident("update")
+                                || path.is_ident("delete") =>
+                        {
+                            let ident = path.get_ident().expect(USAGE);
+                            operations.push(ident.to_string());
+                        }
+                        _ => panic!("{}", USAGE),
+                    }
+                }
+            }
+            _ => panic!("{}", USAGE),
+        }
+    }
+    operations
+}
+
+/// Search for a `#[ready_when(summarized)]` attribute, which indicates that
+/// this resource can be used once it has been summarized.
+fn ready_when_summarized(attrs: &[Attribute]) -> bool {
+    for attr in attrs {
+        if !attr.path.is_ident("ready_when") {
+            continue;
+        }
+        match attr.parse_meta() {
+            Ok(Meta::List(MetaList { ref nested, .. })) if nested.len() == 1 => {
+                match &nested[0] {
+                    NestedMeta::Meta(Meta::Path(path))
+                        if path.is_ident("summarized") =>
+                    {
+                        return true
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        panic!("Invalid `ready_when`, try #[ready_when(summarized)]");
+    }
+    false
+}
@@ -5,16 +5,70 @@
 use proc_macro2::{Ident, Span, TokenStream};
 use syn::{Data, DeriveInput, Field, Meta, MetaList, NestedMeta};
 
-/// Do the actual code generation for a `Resource`.
+/// Do the actual code generation for an `Updatable` type.
 pub(crate) fn derive(ast: &DeriveInput) -> TokenStream {
     let name = &ast.ident;
+
+    // Enums can only be updated as a whole.
+    if let Data::Enum(_) = ast.data {
+        return quote! {
+            impl Updatable for #name {
+                type Update = Self;
+
+                fn to_update(&self) -> Self::Update {
+                    self.clone()
+                }
+
+                fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+                    if self == other {
+                        None
+                    } else {
+                        Some(other.clone())
+                    }
+                }
+            }
+        };
+    }
+
     let vis = &ast.vis;
     let update_name = Ident::new(&format!("{}Update", name), Span::call_site());
     let update_comment = format!("An update to `{}`.", name);
     let update_fields = fields_for_update_type(ast);
+    let field_names = updatable_field_names(ast);
+    let diff_comment = format!(
+        "Return a `{}` which would change `self` into `other`, containing \
+         only the updatable fields which differ.",
+        update_name,
+    );
     quote! {
         impl Updatable for #name {
             type Update = #update_name;
+
+            fn to_update(&self) -> Self::Update {
+                #update_name {
+                    #( #field_names: Some(self.#field_names.to_update()), )*
+                    _placeholder: (),
+                }
+            }
+
+            fn diff_update(&self, other: &Self) -> Option<Self::Update> {
+                let update = #update_name {
+                    #( #field_names: self.#field_names.diff_update(&other.#field_names), )*
+                    _placeholder: (),
+                };
+                if update == #update_name::default() {
+                    None
+                } else {
+                    Some(update)
+                }
+            }
+        }
+
+        impl #name {
+            #[doc = #diff_comment]
+            #vis fn diff(&self, other: &Self) -> #update_name {
+                self.diff_update(other).unwrap_or_default()
+            }
         }
 
         #[doc = #update_comment]
@@ -56,12 +110,30 @@ fn fields_for_update_type(ast: &DeriveInput) -> Vec<TokenStream> {
             }
         }
     } else {
-        panic!("`#[derive(Updatable)]` may only be used on structs");
+        panic!("`#[derive(Updatable)]` may only be used on structs and enums");
     }
 
     new_fields
 }
 
+/// Return the names of all `#[updatable]` fields in the original struct.
+fn updatable_field_names(ast: &DeriveInput) -> Vec<&Ident> {
+    match ast.data {
+        Data::Struct(ref data_struct) => data_struct
+            .fields
+            .iter()
+            .filter(|field| updatable_field_options(field).is_some())
+            .map(|field| {
+                field
+                    .ident
+                    .as_ref()
+                    .expect("Cannot `#[derive(Updatable)]` for tuple struct")
+            })
+            .collect(),
+        _ => panic!("`#[derive(Updatable)]` may only be used on structs and enums"),
+    }
+}
+
 /// Options specified by an `#[updatable(...)]` attribute.
 #[derive(Debug, Default)]
 struct UpdatableFieldOptions {